@@ -0,0 +1,67 @@
+//! Decoding throughput of `MessageBusMessage` over representative messages.
+//!
+//! `single_pass` is the `Deserialize` implementation used by consumers. `via_value` parses into
+//! a `serde_json::Value` first and decodes that, the way messages were decoded before the
+//! single-pass decoder. `strict` is `StrictDecoder::decode_slice`.
+//!
+//! Run with `cargo bench --bench message_decoding`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mykobo_rs::message_bus::models::decode::StrictDecoder;
+use mykobo_rs::message_bus::models::schema::SCHEMA_REGISTRY;
+use mykobo_rs::message_bus::{EventType, MessageBusMessage};
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+
+const FIXTURES: &[(&str, &str)] = &[
+    ("payment", "message_bus/payment.json"),
+    ("transaction", "message_bus/transaction.json"),
+    ("raw", "message_bus/raw.json"),
+    (
+        "customer_notification",
+        "notification/customer_relay_completed.json",
+    ),
+    (
+        "platform_notification",
+        "notification/platform_relay_failed.json",
+    ),
+];
+
+fn fixture(path: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(path);
+    let json = fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    // Compact, as messages arrive from Kafka
+    serde_json::to_vec(&serde_json::from_slice::<serde_json::Value>(&json).unwrap()).unwrap()
+}
+
+fn bench_decoding(c: &mut Criterion) {
+    let strict = StrictDecoder::new().allow_raw_event(EventType::KycEvent);
+    let mut group = c.benchmark_group("decode");
+
+    for (name, path) in FIXTURES {
+        let json = fixture(path);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("single_pass", name), &json, |b, json| {
+            b.iter(|| serde_json::from_slice::<MessageBusMessage>(black_box(json)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("via_value", name), &json, |b, json| {
+            b.iter(|| {
+                let value: serde_json::Value = serde_json::from_slice(black_box(json)).unwrap();
+                MessageBusMessage::from_value(value, &SCHEMA_REGISTRY).unwrap()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("strict", name), &json, |b, json| {
+            b.iter(|| strict.decode_slice(black_box(json)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decoding);
+criterion_main!(benches);
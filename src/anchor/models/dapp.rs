@@ -1,3 +1,4 @@
+use crate::models::money::{Money, MoneyError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -46,6 +47,7 @@ pub enum TransactionSource {
 /// Payload for creating a transaction intent via the dApp REST API.
 /// Matches the request body of POST /v1/transactions/intent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "DappIntentPayloadJson", into = "DappIntentPayloadJson")]
 pub struct DappIntentPayload {
     /// "DEPOSIT" or "WITHDRAW"
     pub transaction_type: String,
@@ -53,10 +55,9 @@ pub struct DappIntentPayload {
     pub wallet_address: String,
     /// Customer's email address — must resolve to a registered profile
     pub email_address: String,
-    /// Transaction amount (must be > 0)
-    pub value: String,
-    /// "EURC" or "USDC"
-    pub currency: String,
+    /// Transaction amount (must be > 0) in "EURC" or "USDC", sent as the `value` and
+    /// `currency` strings
+    pub value: Money,
     /// Client IP address (IPv4 or IPv6)
     pub ip_address: String,
     /// Optional wallet memo (for Stellar shared wallets)
@@ -67,6 +68,52 @@ pub struct DappIntentPayload {
     pub client_domain: Option<String>,
 }
 
+/// The request body as sent, with the amount as strings.
+#[derive(Serialize, Deserialize)]
+struct DappIntentPayloadJson {
+    transaction_type: String,
+    wallet_address: String,
+    email_address: String,
+    value: String,
+    currency: String,
+    ip_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_domain: Option<String>,
+}
+
+impl TryFrom<DappIntentPayloadJson> for DappIntentPayload {
+    type Error = MoneyError;
+
+    fn try_from(json: DappIntentPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: Money::parse(&json.value, &json.currency)?,
+            transaction_type: json.transaction_type,
+            wallet_address: json.wallet_address,
+            email_address: json.email_address,
+            ip_address: json.ip_address,
+            memo: json.memo,
+            client_domain: json.client_domain,
+        })
+    }
+}
+
+impl From<DappIntentPayload> for DappIntentPayloadJson {
+    fn from(payload: DappIntentPayload) -> Self {
+        Self {
+            transaction_type: payload.transaction_type,
+            wallet_address: payload.wallet_address,
+            email_address: payload.email_address,
+            value: payload.value.amount_string(),
+            currency: payload.value.currency.to_string(),
+            ip_address: payload.ip_address,
+            memo: payload.memo,
+            client_domain: payload.client_domain,
+        }
+    }
+}
+
 /// Model for storing transaction records sent to the ledger.
 ///
 /// This stores a local copy of all transactions created through the dApp
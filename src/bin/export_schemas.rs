@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+use mykobo_rs::message_bus::models::json_schema::write_schemas;
+
+fn main() {
+    let out: PathBuf = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schemas"));
+    for path in write_schemas(&out).expect("write schemas") {
+        println!("wrote {}", path.display());
+    }
+}
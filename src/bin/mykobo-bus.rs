@@ -0,0 +1,668 @@
+//! `mykobo-bus`: tail, validate, produce, archive and replay message bus messages from the
+//! command line.
+//!
+//! Kafka credentials come from the same environment variables as `EventConsumer` and
+//! `EventProducer`; set `KAFKA_API_PROTOCOL=PLAINTEXT` for a local broker.
+
+use chrono::{DateTime, Utc};
+use futures::future::{select, Either};
+use mykobo_rs::message_bus::kafka::archive::{
+    archive_files, read_archives, ArchiveWriter, ReplayFilter, Replayer,
+};
+use mykobo_rs::message_bus::kafka::consumer::{EventConsumer, StartPosition};
+use mykobo_rs::message_bus::kafka::models::IncomingMessage;
+use mykobo_rs::message_bus::kafka::producer::EventProducer;
+use mykobo_rs::message_bus::models::{
+    AvroCodec, FileSchemaRegistry, MessageBuilder, Redact, SCHEMA_REGISTRY,
+};
+use mykobo_rs::message_bus::{EventType, InstructionType, MessageBusMessage, Payload, PayloadKind};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const USAGE: &str = "\
+Usage:
+  mykobo-bus tail <topic>... [--event <EVENT>]... [--instruction <INSTRUCTION>]...
+                  [--reference <REFERENCE>] [--from-beginning | --since <RFC3339>]
+                  [--validate] [--unredacted] [--brokers <BROKERS>] [--avro-registry <FILE>]
+  mykobo-bus validate [<file>]
+  mykobo-bus produce <topic> <file> [--source <SOURCE>] [--token <TOKEN>] [--key <KEY>]
+                  [--dry-run] [--brokers <BROKERS>] [--avro-registry <FILE>]
+  mykobo-bus archive <dir> <topic>... [--from-beginning | --since <RFC3339>] [--prefix <PREFIX>]
+                  [--max-bytes <BYTES>] [--max-records <COUNT>] [--gzip] [--timeout <SECONDS>]
+                  [--brokers <BROKERS>] [--avro-registry <FILE>]
+  mykobo-bus replay <topic> <archive file or dir>... [--event <EVENT>]...
+                  [--instruction <INSTRUCTION>]... [--source <SOURCE>]... [--from-topic <TOPIC>]...
+                  [--since <RFC3339>] [--until <RFC3339>] [--new-idempotency-keys]
+                  [--new-created-at] [--replay-id <ID>] [--dry-run] [--brokers <BROKERS>]
+                  [--avro-registry <FILE>]
+
+tail prints messages produced from now on, or from --since or the beginning of the topic,
+with sensitive fields redacted unless --unredacted is given. --event and --instruction may
+be repeated; --reference matches any `reference` or `*_reference` payload field.
+
+validate reads stdin when no file is given. The input may be a message, an array of
+messages or one message per line. It exits with 1 if any message is invalid.
+
+produce sends the message in <file>. Its meta_data needs source (or --source) and an
+instruction_type or event; created_at, idempotency_key and the other fields are optional and
+filled in as MessageBusMessage::instruction and ::event would. The Kafka key defaults to the
+idempotency key.
+
+archive writes the topics' messages, from the beginning unless --since is given, to rotating
+JSON lines files in <dir> and stops once it has caught up with the messages present when it
+started. It fails if it hasn't caught up within --timeout seconds, an hour by default.
+Encrypted payload fields are archived encrypted, as they were sent.
+
+replay republishes archived messages to <topic>, keeping their Kafka keys. --since and --until
+bound the messages' created_at; --from-topic selects the topics they were archived from.
+--new-idempotency-keys appends `:replay:<ID>` to each idempotency key, with a random ID unless
+--replay-id is given. Encrypted fields are bound to the original idempotency key, so
+messages with encrypted fields are counted as invalid instead. --dry-run prints what would be
+sent.
+
+Brokers default to $KAFKA_BROKERS, then localhost:9092. --avro-registry reads and writes Avro
+using the file-based schema registry at <FILE>.";
+
+const FLAGS: &[&str] = &[
+    "from-beginning",
+    "validate",
+    "unredacted",
+    "dry-run",
+    "gzip",
+    "new-idempotency-keys",
+    "new-created-at",
+];
+const OPTIONS: &[&str] = &[
+    "event",
+    "instruction",
+    "reference",
+    "since",
+    "brokers",
+    "avro-registry",
+    "source",
+    "token",
+    "key",
+    "prefix",
+    "max-bytes",
+    "max-records",
+    "from-topic",
+    "until",
+    "replay-id",
+    "timeout",
+];
+const DEFAULT_ARCHIVE_PREFIX: &str = "bus";
+const DEFAULT_ARCHIVE_TIMEOUT_SECS: u64 = 3600;
+const DEFAULT_BROKERS: &str = "localhost:9092";
+const PRODUCE_TIMEOUT_SECS: u64 = 10;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("mykobo-bus: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("build tokio runtime");
+    let result = match command.as_deref() {
+        Some("tail") => runtime.block_on(tail(&args)),
+        Some("validate") => validate(&args),
+        Some("produce") => runtime.block_on(produce(&args)),
+        Some("archive") => runtime.block_on(archive(&args)),
+        Some("replay") => runtime.block_on(replay(&args)),
+        None | Some("help") | Some("--help") | Some("-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(other) => Err(format!("unknown command {other:?}. Run `mykobo-bus help`.")),
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("mykobo-bus: {e}");
+        ExitCode::FAILURE
+    })
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if FLAGS.contains(&name) {
+                parsed.flags.insert(name.to_string());
+            } else if OPTIONS.contains(&name) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("--{name} needs a value"))?;
+                parsed
+                    .options
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value);
+            } else {
+                return Err(format!("unknown option --{name}"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    fn all(&self, name: &str) -> &[String] {
+        self.options
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn time(&self, name: &str) -> Result<Option<DateTime<Utc>>, String> {
+        self.option(name)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|e| format!("--{name} {value}: {e}"))
+            })
+            .transpose()
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u64>, String> {
+        self.option(name)
+            .map(|value| value.parse().map_err(|e| format!("--{name} {value}: {e}")))
+            .transpose()
+    }
+
+    fn events(&self) -> Result<Vec<EventType>, String> {
+        self.all("event")
+            .iter()
+            .map(|name| {
+                let event: EventType = serde_json::from_value(Value::String(name.clone()))
+                    .map_err(|e| e.to_string())?;
+                if event.is_unknown() {
+                    eprintln!("mykobo-bus: {name} is not an event type this version knows");
+                }
+                Ok(event)
+            })
+            .collect()
+    }
+
+    fn instruction_types(&self) -> Result<Vec<InstructionType>, String> {
+        self.all("instruction")
+            .iter()
+            .map(|name| {
+                let instruction_type: InstructionType =
+                    serde_json::from_value(Value::String(name.clone()))
+                        .map_err(|e| e.to_string())?;
+                if instruction_type.is_unknown() {
+                    eprintln!("mykobo-bus: {name} is not an instruction type this version knows");
+                }
+                Ok(instruction_type)
+            })
+            .collect()
+    }
+
+    fn start_position(&self, default: StartPosition) -> Result<StartPosition, String> {
+        match (self.flag("from-beginning"), self.time("since")?) {
+            (true, Some(_)) => Err("--from-beginning and --since can't be combined".into()),
+            (true, None) => Ok(StartPosition::Beginning),
+            (false, Some(since)) => Ok(StartPosition::Timestamp(since)),
+            (false, None) => Ok(default),
+        }
+    }
+
+    /// A consumer in a group of its own, so it never takes partitions from real consumers.
+    fn consumer(
+        &self,
+        topics: &[&str],
+        sender: mpsc::Sender<IncomingMessage<Value>>,
+    ) -> Result<EventConsumer<Value>, String> {
+        let group_id = format!("mykobo-bus-{}", uuid::Uuid::new_v4());
+        let mut consumer =
+            EventConsumer::new(&self.brokers(), &group_id, &group_id, 1, topics, sender)
+                .map_err(|e| e.to_string())?;
+        if let Some(codec) = self.avro_codec() {
+            consumer = consumer.with_avro(codec);
+        }
+        Ok(consumer)
+    }
+
+    fn producer(&self, topic: &str) -> Result<EventProducer, String> {
+        let mut producer = EventProducer::new(&self.brokers(), PRODUCE_TIMEOUT_SECS, topic)
+            .map_err(|e| e.to_string())?;
+        if let Some(codec) = self.avro_codec() {
+            producer = producer.with_avro(codec);
+        }
+        Ok(producer)
+    }
+
+    fn brokers(&self) -> String {
+        self.option("brokers")
+            .map(str::to_string)
+            .or_else(|| std::env::var("KAFKA_BROKERS").ok())
+            .unwrap_or_else(|| DEFAULT_BROKERS.to_string())
+    }
+
+    fn avro_codec(&self) -> Option<AvroCodec> {
+        self.option("avro-registry")
+            .map(|path| AvroCodec::new(Arc::new(FileSchemaRegistry::new(path))))
+    }
+}
+
+/// Which messages `tail` prints. Type filters match either an event or an instruction type.
+struct Filter {
+    types: Vec<(&'static str, Value)>,
+    reference: Option<String>,
+}
+
+impl Filter {
+    fn from_args(args: &Args) -> Result<Self, String> {
+        let mut types = Vec::new();
+        for event in args.events()? {
+            types.push((
+                "event",
+                serde_json::to_value(event).map_err(|e| e.to_string())?,
+            ));
+        }
+        for instruction_type in args.instruction_types()? {
+            let name = serde_json::to_value(instruction_type).map_err(|e| e.to_string())?;
+            types.push(("instruction_type", name));
+        }
+        Ok(Self {
+            types,
+            reference: args.option("reference").map(str::to_string),
+        })
+    }
+
+    fn matches(&self, message: &Value) -> bool {
+        let type_matches = self.types.is_empty()
+            || self
+                .types
+                .iter()
+                .any(|(field, name)| message["meta_data"][field] == *name);
+        let reference_matches = self
+            .reference
+            .as_deref()
+            .is_none_or(|reference| has_reference(&message["payload"], reference));
+        type_matches && reference_matches
+    }
+}
+
+fn has_reference(value: &Value, reference: &str) -> bool {
+    match value {
+        Value::Object(fields) => fields.iter().any(|(name, field)| {
+            let is_reference = name == "reference" || name.ends_with("_reference");
+            (is_reference && field.as_str() == Some(reference)) || has_reference(field, reference)
+        }),
+        Value::Array(items) => items.iter().any(|item| has_reference(item, reference)),
+        _ => false,
+    }
+}
+
+async fn tail(args: &Args) -> Result<ExitCode, String> {
+    let topics: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+    if topics.is_empty() {
+        return Err("tail needs at least one topic".to_string());
+    }
+    let filter = Filter::from_args(args)?;
+    let position = args.start_position(StartPosition::Timestamp(Utc::now()))?;
+
+    let (sender, mut receiver) = mpsc::channel(100);
+    let consumer = args.consumer(&topics, sender)?;
+    consumer.seek_to(position).map_err(|e| e.to_string())?;
+
+    let print = async {
+        while let Some(message) = receiver.recv().await {
+            if filter.matches(&message.payload) {
+                print_message(
+                    &message.payload,
+                    args.flag("validate"),
+                    args.flag("unredacted"),
+                );
+            }
+        }
+    };
+    let outcome = match select(Box::pin(consumer.start()), Box::pin(print)).await {
+        Either::Left((result, _)) => result.map(|_| ExitCode::SUCCESS).map_err(|e| e.to_string()),
+        Either::Right(_) => Ok(ExitCode::SUCCESS),
+    };
+    outcome
+}
+
+fn print_message(value: &Value, validate: bool, unredacted: bool) {
+    match MessageBusMessage::from_value(value.clone(), &SCHEMA_REGISTRY) {
+        Ok(message) => {
+            let shown = if unredacted {
+                serde_json::to_value(&message).unwrap_or(Value::Null)
+            } else {
+                message.redacted_value()
+            };
+            println!("{}", pretty(&shown));
+            if validate {
+                match message.validate() {
+                    Ok(()) => println!("valid"),
+                    Err(e) => println!("invalid: {e}"),
+                }
+            }
+        }
+        Err(e) => {
+            if unredacted {
+                println!("{}", pretty(value));
+            }
+            println!("not a MessageBusMessage: {e}");
+        }
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn validate(args: &Args) -> Result<ExitCode, String> {
+    let input = match args.positional.first() {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?,
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| e.to_string())?;
+            input
+        }
+    };
+
+    let mut invalid = 0;
+    for (i, value) in parse_messages(&input)?.into_iter().enumerate() {
+        let label = value
+            .pointer("/meta_data/idempotency_key")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("message {}", i + 1));
+        let result = MessageBusMessage::from_value(value, &SCHEMA_REGISTRY)
+            .map_err(|e| e.to_string())
+            .and_then(|message| message.validate().map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => println!("ok       {label}"),
+            Err(e) => {
+                invalid += 1;
+                println!("invalid  {label}: {e}");
+            }
+        }
+    }
+
+    Ok(if invalid == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn parse_messages(input: &str) -> Result<Vec<Value>, String> {
+    match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(messages)) => Ok(messages),
+        Ok(message) => Ok(vec![message]),
+        Err(_) => input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1)))
+            .collect(),
+    }
+}
+
+/// A message to produce. Everything but `payload`, `source` and the message type is optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Draft {
+    meta_data: DraftMetaData,
+    payload: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct DraftMetaData {
+    source: Option<String>,
+    created_at: Option<String>,
+    token: Option<String>,
+    idempotency_key: Option<String>,
+    instruction_type: Option<InstructionType>,
+    event: Option<EventType>,
+    ip_address: Option<String>,
+    schema_version: Option<u32>,
+    correlation_id: Option<String>,
+    expires_at: Option<String>,
+    tenant: Option<String>,
+}
+
+fn decode_payload(kind: Option<PayloadKind>, payload: Value) -> Result<Payload, String> {
+    match (kind, payload) {
+        (_, Value::String(raw)) => Ok(Payload::Raw(raw)),
+        (Some(kind), payload) => kind
+            .decode(payload)
+            .map_err(|e| format!("payload is not a valid {}: {e}", kind.type_name())),
+        (None, payload) => Ok(Payload::Unknown(payload)),
+    }
+}
+
+fn parse_time(field: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("meta_data.{field} {value}: {e}"))
+}
+
+fn build_message(draft: Draft, args: &Args) -> Result<MessageBusMessage, String> {
+    let meta_data = draft.meta_data;
+    let mut builder = match (meta_data.instruction_type, meta_data.event) {
+        (Some(instruction_type), None) => {
+            let kind = PayloadKind::for_instruction(&instruction_type);
+            MessageBuilder::for_instruction(instruction_type, decode_payload(kind, draft.payload)?)
+        }
+        (None, Some(event)) => {
+            let kind = PayloadKind::for_event(&event);
+            MessageBuilder::for_event(event, decode_payload(kind, draft.payload)?)
+        }
+        _ => return Err("meta_data needs exactly one of instruction_type and event".into()),
+    };
+
+    if let Some(source) = args
+        .option("source")
+        .map(str::to_string)
+        .or(meta_data.source)
+    {
+        builder = builder.source(source);
+    }
+    if let Some(token) = args.option("token").map(str::to_string).or(meta_data.token) {
+        builder = builder.token(token);
+    }
+    if let Some(created_at) = meta_data.created_at {
+        builder = builder.created_at(parse_time("created_at", &created_at)?);
+    }
+    if let Some(idempotency_key) = meta_data.idempotency_key {
+        builder = builder.idempotency_key(idempotency_key);
+    }
+    if let Some(ip_address) = meta_data.ip_address {
+        builder = builder.ip_address(ip_address);
+    }
+    if let Some(schema_version) = meta_data.schema_version {
+        builder = builder.schema_version(schema_version);
+    }
+    if let Some(correlation_id) = meta_data.correlation_id {
+        builder = builder.correlation_id(correlation_id);
+    }
+    if let Some(expires_at) = meta_data.expires_at {
+        builder = builder.expires_at(parse_time("expires_at", &expires_at)?);
+    }
+    if let Some(tenant) = meta_data.tenant {
+        builder = builder.tenant(tenant);
+    }
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+async fn produce(args: &Args) -> Result<ExitCode, String> {
+    let [topic, path] = args.positional.as_slice() else {
+        return Err("produce needs a topic and a file".to_string());
+    };
+    let contents = std::fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+    let draft: Draft = serde_json::from_slice(&contents).map_err(|e| format!("{path}: {e}"))?;
+    let message = build_message(draft, args)?;
+
+    if args.flag("dry-run") {
+        println!("{}", pretty(&message.redacted_value()));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let producer = args.producer(topic)?;
+    let key = args
+        .option("key")
+        .unwrap_or(&message.meta_data.idempotency_key)
+        .to_string();
+    producer
+        .send_event(key, &message)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!("sent {} to {topic}", message.meta_data.idempotency_key);
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn archive(args: &Args) -> Result<ExitCode, String> {
+    let Some((dir, topics)) = args.positional.split_first().filter(|(_, t)| !t.is_empty()) else {
+        return Err("archive needs a directory and at least one topic".to_string());
+    };
+    let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+    let position = args.start_position(StartPosition::Beginning)?;
+    let timeout = Duration::from_secs(
+        args.number("timeout")?
+            .unwrap_or(DEFAULT_ARCHIVE_TIMEOUT_SECS),
+    );
+
+    let mut writer =
+        ArchiveWriter::new(dir, args.option("prefix").unwrap_or(DEFAULT_ARCHIVE_PREFIX));
+    if let Some(max_bytes) = args.number("max-bytes")? {
+        writer = writer.with_max_bytes(max_bytes);
+    }
+    if let Some(max_records) = args.number("max-records")? {
+        writer = writer.with_max_records(max_records);
+    }
+    if args.flag("gzip") {
+        writer = writer.with_compression();
+    }
+
+    let (sender, receiver) = mpsc::channel(100);
+    let consumer = args.consumer(&topics, sender)?;
+    consumer.seek_to(position).map_err(|e| e.to_string())?;
+
+    // Dropping the consumer closes the channel, which lets the writer finish
+    let consume = async move { consumer.consume_until_now(timeout).await };
+    let (consumed, files) = futures::future::join(consume, writer.archive(receiver)).await;
+    let consumed = consumed.map_err(|e| e.to_string())?;
+    let files = files.map_err(|e| e.to_string())?;
+
+    println!("archived {consumed} messages");
+    for file in files {
+        println!("{}", file.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn replay(args: &Args) -> Result<ExitCode, String> {
+    let Some((topic, paths)) = args.positional.split_first().filter(|(_, p)| !p.is_empty()) else {
+        return Err("replay needs a topic and at least one archive file or directory".to_string());
+    };
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        if path.is_dir() {
+            files.extend(archive_files(path).map_err(|e| format!("{}: {e}", path.display()))?);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+
+    let mut filter = ReplayFilter::new();
+    for event in args.events()? {
+        filter = filter.event(event);
+    }
+    for instruction_type in args.instruction_types()? {
+        filter = filter.instruction_type(instruction_type);
+    }
+    for source in args.all("source") {
+        filter = filter.source(source);
+    }
+    for from_topic in args.all("from-topic") {
+        filter = filter.topic(from_topic);
+    }
+    if let Some(since) = args.time("since")? {
+        filter = filter.created_from(since);
+    }
+    if let Some(until) = args.time("until")? {
+        filter = filter.created_until(until);
+    }
+
+    let mut replayer = Replayer::new(args.producer(topic)?).with_filter(filter);
+    if args.flag("new-idempotency-keys") {
+        replayer = replayer.with_new_idempotency_keys();
+    }
+    if let Some(replay_id) = args.option("replay-id") {
+        replayer = replayer.with_replay_id(replay_id);
+    }
+    if args.flag("new-created-at") {
+        replayer = replayer.with_new_created_at();
+    }
+
+    if args.flag("dry-run") {
+        for archived in read_archives(&files) {
+            let archived = archived.map_err(|e| e.to_string())?;
+            match replayer.prepare(&archived) {
+                Ok(Some((key, message))) => {
+                    println!("key {key}\n{}", pretty(&message.redacted_value()))
+                }
+                Ok(None) => {}
+                Err(e) => println!(
+                    "invalid  {}/{}@{}: {e}",
+                    archived.topic, archived.partition, archived.offset
+                ),
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let report = replayer
+        .replay(read_archives(&files))
+        .await
+        .map_err(|e| e.to_string())?;
+    println!(
+        "replayed {} messages to {topic}, {} filtered out, {} invalid",
+        report.replayed, report.filtered, report.invalid
+    );
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,410 @@
+use crate::models::error::{KafkaError, KafkaResult};
+use log::{info, warn};
+use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, ResourceSpecifier, TopicReplication};
+use rdkafka::client::DefaultClientContext;
+use rdkafka::config::{ClientConfig, RDKafkaLogLevel};
+use rdkafka::types::RDKafkaErrorCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::time::Duration;
+
+pub const RETRY_TOPIC_SUFFIX: &str = ".retry";
+pub const DEAD_LETTER_TOPIC_SUFFIX: &str = ".dlq";
+pub const RETENTION_MS_CONFIG: &str = "retention.ms";
+
+/// Declarative description of a topic the service expects to exist.
+///
+/// `retry` and `dead_letter` derive `{name}.retry` and `{name}.dlq` companion topics
+/// with the same partitioning and configuration when the spec is expanded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicSpec {
+    pub name: String,
+    pub partitions: i32,
+    pub replication_factor: i32,
+    #[serde(default)]
+    pub retention_ms: Option<i64>,
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+    #[serde(default)]
+    pub retry: bool,
+    #[serde(default)]
+    pub dead_letter: bool,
+}
+
+impl TopicSpec {
+    pub fn new(name: &str, partitions: i32, replication_factor: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            partitions,
+            replication_factor,
+            retention_ms: None,
+            config: BTreeMap::new(),
+            retry: false,
+            dead_letter: false,
+        }
+    }
+
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention_ms = Some(retention.as_millis() as i64);
+        self
+    }
+
+    pub fn with_config(mut self, key: &str, value: &str) -> Self {
+        self.config.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_retry(mut self) -> Self {
+        self.retry = true;
+        self
+    }
+
+    pub fn with_dead_letter(mut self) -> Self {
+        self.dead_letter = true;
+        self
+    }
+
+    pub fn retry_topic_name(&self) -> String {
+        format!("{}{}", self.name, RETRY_TOPIC_SUFFIX)
+    }
+
+    pub fn dead_letter_topic_name(&self) -> String {
+        format!("{}{}", self.name, DEAD_LETTER_TOPIC_SUFFIX)
+    }
+
+    /// Expand the spec into the concrete topics it describes: the main topic followed by
+    /// its retry and dead letter topics when requested.
+    pub fn expand(&self) -> Vec<TopicSpec> {
+        let concrete = |name: String| TopicSpec {
+            name,
+            retry: false,
+            dead_letter: false,
+            ..self.clone()
+        };
+
+        let mut topics = vec![concrete(self.name.clone())];
+        if self.retry {
+            topics.push(concrete(self.retry_topic_name()));
+        }
+        if self.dead_letter {
+            topics.push(concrete(self.dead_letter_topic_name()));
+        }
+        topics
+    }
+
+    /// Topic level configuration the broker is expected to report, including `retention.ms`.
+    pub fn expected_config(&self) -> BTreeMap<String, String> {
+        let mut config = self.config.clone();
+        if let Some(retention_ms) = self.retention_ms {
+            config.insert(RETENTION_MS_CONFIG.to_string(), retention_ms.to_string());
+        }
+        config
+    }
+
+    /// Compare this (concrete) spec against what the broker reports for the topic.
+    pub fn drift(&self, observed: Option<&ObservedTopic>) -> Vec<TopicDrift> {
+        let Some(observed) = observed else {
+            return vec![TopicDrift::Missing {
+                topic: self.name.clone(),
+            }];
+        };
+
+        let mut drift = Vec::new();
+        if observed.partitions != self.partitions {
+            drift.push(TopicDrift::PartitionCount {
+                topic: self.name.clone(),
+                expected: self.partitions,
+                actual: observed.partitions,
+            });
+        }
+        if observed.replication_factor != self.replication_factor {
+            drift.push(TopicDrift::ReplicationFactor {
+                topic: self.name.clone(),
+                expected: self.replication_factor,
+                actual: observed.replication_factor,
+            });
+        }
+
+        for (key, expected) in self.expected_config() {
+            let actual = observed.config.get(&key);
+            if actual != Some(&expected) {
+                drift.push(TopicDrift::Config {
+                    topic: self.name.clone(),
+                    key,
+                    expected,
+                    actual: actual.cloned(),
+                });
+            }
+        }
+
+        drift
+    }
+
+    /// Parse a YAML list of topic specs.
+    pub fn from_yaml(yaml: &str) -> KafkaResult<Vec<TopicSpec>> {
+        serde_yaml::from_str(yaml).map_err(|e| KafkaError::Admin(e.to_string()))
+    }
+}
+
+/// Topic state as reported by the broker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedTopic {
+    pub name: String,
+    pub partitions: i32,
+    /// The fewest replicas assigned to any of the topic's partitions.
+    pub replication_factor: i32,
+    pub config: BTreeMap<String, String>,
+}
+
+/// A difference between a `TopicSpec` and the broker's view of the topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopicDrift {
+    Missing {
+        topic: String,
+    },
+    PartitionCount {
+        topic: String,
+        expected: i32,
+        actual: i32,
+    },
+    ReplicationFactor {
+        topic: String,
+        expected: i32,
+        actual: i32,
+    },
+    Config {
+        topic: String,
+        key: String,
+        expected: String,
+        actual: Option<String>,
+    },
+}
+
+impl fmt::Display for TopicDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopicDrift::Missing { topic } => write!(f, "{topic}: topic does not exist"),
+            TopicDrift::PartitionCount {
+                topic,
+                expected,
+                actual,
+            } => write!(f, "{topic}: expected {expected} partitions, found {actual}"),
+            TopicDrift::ReplicationFactor {
+                topic,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{topic}: expected replication factor {expected}, found {actual}"
+            ),
+            TopicDrift::Config {
+                topic,
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{topic}: expected {key}={expected}, found {}",
+                actual.as_deref().unwrap_or("<unset>")
+            ),
+        }
+    }
+}
+
+pub struct TopicAdmin {
+    client: AdminClient<DefaultClientContext>,
+    timeout: Duration,
+}
+
+impl TopicAdmin {
+    pub fn new(brokers: &str, timeout_in_secs: u64) -> KafkaResult<Self> {
+        let sasl_username = env::var("KAFKA_API_KEY").ok();
+        let sasl_password = env::var("KAFKA_API_SECRET").ok();
+        let protocol = env::var("KAFKA_API_PROTOCOL").unwrap_or("SASL_SSL".to_string());
+        let mechanism = env::var("KAFKA_API_SASL_MECHANISM").unwrap_or("PLAIN".to_string());
+
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("socket.keepalive.enable", "true")
+            .set("socket.connection.setup.timeout.ms", "10000")
+            .set("security.protocol", &protocol)
+            .set("sasl.mechanisms", mechanism);
+
+        if protocol == "SASL_SSL" {
+            let username = sasl_username.ok_or_else(|| {
+                KafkaError::ClientCreation(
+                    "KAFKA_API_KEY is required when protocol is SASL_SSL".into(),
+                )
+            })?;
+            let password = sasl_password.ok_or_else(|| {
+                KafkaError::ClientCreation(
+                    "KAFKA_API_SECRET is required when protocol is SASL_SSL".into(),
+                )
+            })?;
+            config
+                .set("sasl.username", username)
+                .set("sasl.password", password);
+        }
+
+        let client: AdminClient<DefaultClientContext> = config
+            .set_log_level(RDKafkaLogLevel::Info)
+            .create()
+            .map_err(|e| KafkaError::ClientCreation(e.to_string()))?;
+
+        Ok(TopicAdmin {
+            client,
+            timeout: Duration::from_secs(timeout_in_secs),
+        })
+    }
+
+    fn options(&self) -> AdminOptions {
+        AdminOptions::new()
+            .request_timeout(Some(self.timeout))
+            .operation_timeout(Some(self.timeout))
+    }
+
+    /// Create every topic described by `specs` (including retry and dead letter topics).
+    ///
+    /// Topics that already exist are left untouched. Returns the names of the topics created.
+    pub async fn create_topics(&self, specs: &[TopicSpec]) -> KafkaResult<Vec<String>> {
+        let topics: Vec<TopicSpec> = specs.iter().flat_map(TopicSpec::expand).collect();
+        let configs: Vec<Vec<(String, String)>> = topics
+            .iter()
+            .map(|t| t.expected_config().into_iter().collect())
+            .collect();
+
+        let new_topics: Vec<NewTopic> = topics
+            .iter()
+            .zip(configs.iter())
+            .map(|(topic, config)| {
+                config.iter().fold(
+                    NewTopic::new(
+                        &topic.name,
+                        topic.partitions,
+                        TopicReplication::Fixed(topic.replication_factor),
+                    ),
+                    |new_topic, (key, value)| new_topic.set(key, value),
+                )
+            })
+            .collect();
+
+        let results = self
+            .client
+            .create_topics(new_topics.iter(), &self.options())
+            .await
+            .map_err(|e| KafkaError::Admin(e.to_string()))?;
+
+        let mut created = Vec::new();
+        for result in results {
+            match result {
+                Ok(name) => {
+                    info!("Created topic [{name}]");
+                    created.push(name);
+                }
+                Err((name, RDKafkaErrorCode::TopicAlreadyExists)) => {
+                    info!("Topic [{name}] already exists");
+                }
+                Err((name, code)) => {
+                    return Err(KafkaError::Admin(format!(
+                        "failed to create topic {name}: {code}"
+                    )));
+                }
+            }
+        }
+
+        Ok(created)
+    }
+
+    /// Fetch the partition count, replication factor and topic configuration the broker
+    /// reports for `topic`.
+    ///
+    /// Returns `None` if the topic does not exist, and an error for any other metadata error.
+    pub async fn describe_topic(&self, topic: &str) -> KafkaResult<Option<ObservedTopic>> {
+        let metadata = self
+            .client
+            .inner()
+            .fetch_metadata(Some(topic), self.timeout)
+            .map_err(|e| KafkaError::Admin(e.to_string()))?;
+
+        let Some(metadata) = metadata.topics().iter().find(|t| t.name() == topic) else {
+            return Ok(None);
+        };
+        match metadata.error().map(RDKafkaErrorCode::from) {
+            None => {}
+            Some(RDKafkaErrorCode::UnknownTopicOrPartition) => return Ok(None),
+            Some(code) => {
+                return Err(KafkaError::Admin(format!(
+                    "failed to fetch metadata for topic {topic}: {code}"
+                )))
+            }
+        }
+        let partitions = metadata.partitions().len() as i32;
+        let replication_factor = metadata
+            .partitions()
+            .iter()
+            .map(|partition| partition.replicas().len() as i32)
+            .min()
+            .unwrap_or(0);
+
+        let resources = self
+            .client
+            .describe_configs([&ResourceSpecifier::Topic(topic)], &self.options())
+            .await
+            .map_err(|e| KafkaError::Admin(e.to_string()))?;
+
+        let mut config = BTreeMap::new();
+        for resource in resources {
+            let resource = resource.map_err(|code| {
+                KafkaError::Admin(format!("failed to describe topic {topic}: {code}"))
+            })?;
+            for entry in resource.entries {
+                if let Some(value) = entry.value {
+                    config.insert(entry.name, value);
+                }
+            }
+        }
+
+        Ok(Some(ObservedTopic {
+            name: topic.to_string(),
+            partitions,
+            replication_factor,
+            config,
+        }))
+    }
+
+    /// Report how the broker's topics differ from `specs`. An empty result means no drift.
+    pub async fn verify(&self, specs: &[TopicSpec]) -> KafkaResult<Vec<TopicDrift>> {
+        let mut drift = Vec::new();
+        for topic in specs.iter().flat_map(TopicSpec::expand) {
+            let observed = self.describe_topic(&topic.name).await?;
+            drift.extend(topic.drift(observed.as_ref()));
+        }
+        Ok(drift)
+    }
+
+    /// Create any missing topics and then verify the rest, failing on drift.
+    ///
+    /// Intended to be called once at service startup before consumers subscribe.
+    pub async fn ensure(&self, specs: &[TopicSpec]) -> KafkaResult<()> {
+        self.create_topics(specs).await?;
+
+        let drift = self.verify(specs).await?;
+        if drift.is_empty() {
+            return Ok(());
+        }
+
+        for d in &drift {
+            warn!("Topic drift detected: {d}");
+        }
+        Err(KafkaError::TopicDrift(
+            drift
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ))
+    }
+}
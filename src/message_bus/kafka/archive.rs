@@ -0,0 +1,580 @@
+//! Capture bus traffic to local JSON lines files and republish it later.
+//!
+//! `ArchiveWriter` drains the channel of an `EventConsumer<serde_json::Value>` into rotating,
+//! optionally gzipped, `.jsonl` files, one `ArchivedMessage` per line. `ArchiveReader` reads
+//! them back and `Replayer` republishes a filtered subset through an `EventProducer`.
+//!
+//! Encrypted payload fields stay encrypted in the archive: a writer with a `FieldEncryptor`
+//! re-encrypts fields its consumer decrypted. Encrypted values are bound to the message's
+//! idempotency key, so replaying with new idempotency keys needs a `FieldEncryptor` holding
+//! their KEK to decrypt and re-encrypt them.
+
+use crate::message_bus::kafka::models::IncomingMessage;
+use crate::message_bus::kafka::producer::EventProducer;
+use crate::message_bus::models::encryption::{EncryptionError, FieldEncryptor};
+use crate::message_bus::models::{EventType, InstructionType, MessageBusMessage, SCHEMA_REGISTRY};
+use crate::models::error::KafkaError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc::Receiver;
+
+pub const ARCHIVE_FILE_EXTENSION: &str = ".jsonl";
+pub const COMPRESSED_FILE_EXTENSION: &str = ".jsonl.gz";
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("archive I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{path}:{line}: {source}")]
+    Record {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+    #[error("{0}: encrypted fields need their KEK to be bound to a new idempotency key")]
+    EncryptedFields(String),
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+}
+
+/// One captured message: where it was read from, its key and headers, and its JSON body.
+///
+/// The body is what the consumer forwarded, so it is already decoded from Avro. Encrypted
+/// fields are as sent unless the consumer decrypted them; see `ArchiveWriter::with_field_encryptor`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub message: Value,
+}
+
+impl ArchivedMessage {
+    /// The body as a `MessageBusMessage`, upcast with the built-in `SCHEMA_REGISTRY`.
+    pub fn decode(&self) -> Result<MessageBusMessage, serde_json::Error> {
+        MessageBusMessage::from_value(self.message.clone(), &SCHEMA_REGISTRY)
+    }
+}
+
+impl From<IncomingMessage<Value>> for ArchivedMessage {
+    fn from(incoming: IncomingMessage<Value>) -> Self {
+        Self {
+            topic: incoming.topic,
+            partition: incoming.partition,
+            offset: incoming.offset,
+            key: incoming.key,
+            headers: incoming.headers,
+            message: incoming.payload,
+        }
+    }
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Sink {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Sink::Plain(writer) => writer,
+            Sink::Gzip(writer) => writer,
+        }
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Sink::Plain(mut writer) => writer.flush(),
+            Sink::Gzip(writer) => writer.finish()?.flush(),
+        }
+    }
+}
+
+struct OpenFile {
+    sink: Sink,
+    bytes: u64,
+    records: u64,
+}
+
+/// Writes `ArchivedMessage`s to `{prefix}-{started}-{sequence}.jsonl` files in a directory,
+/// starting a new file once the current one reaches the size or record limit.
+///
+/// Sizes count uncompressed bytes. A file is only complete once `finish` has run, which for
+/// gzip writes the trailer.
+pub struct ArchiveWriter {
+    dir: PathBuf,
+    prefix: String,
+    started: String,
+    max_bytes: u64,
+    max_records: Option<u64>,
+    compress: bool,
+    encryptor: Option<FieldEncryptor>,
+    current: Option<OpenFile>,
+    files: Vec<PathBuf>,
+}
+
+impl ArchiveWriter {
+    pub fn new(dir: impl AsRef<Path>, prefix: &str) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            started: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+            max_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_records: None,
+            compress: false,
+            encryptor: None,
+            current: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Start a new file once the current one holds `max_bytes` of JSON lines.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Start a new file once the current one holds `max_records` messages.
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Gzip each file, naming it `.jsonl.gz`.
+    pub fn with_compression(mut self) -> Self {
+        self.compress = true;
+        self
+    }
+
+    /// Encrypt each message's sensitive fields before writing it, so messages from a consumer
+    /// with a `FieldEncryptor` aren't archived in plaintext. Fields that are still encrypted
+    /// are written as they are.
+    pub fn with_field_encryptor(mut self, encryptor: FieldEncryptor) -> Self {
+        self.encryptor = Some(encryptor);
+        self
+    }
+
+    /// Files written so far, including the one currently open.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn write(&mut self, message: &ArchivedMessage) -> Result<(), ArchiveError> {
+        let mut line = match &self.encryptor {
+            Some(encryptor) => {
+                let mut message = message.clone();
+                encryptor.encrypt_json(&mut message.message)?;
+                serde_json::to_vec(&message)?
+            }
+            None => serde_json::to_vec(message)?,
+        };
+        line.push(b'\n');
+
+        if self.current.as_ref().is_some_and(|file| self.is_full(file)) {
+            self.close()?;
+        }
+        if self.current.is_none() {
+            self.current = Some(self.open()?);
+        }
+        let file = self.current.as_mut().expect("archive file is open");
+        file.sink.writer().write_all(&line)?;
+        file.bytes += line.len() as u64;
+        file.records += 1;
+        Ok(())
+    }
+
+    /// Archive everything received on `receiver` until its senders are dropped, e.g. when the
+    /// consumer feeding it stops. Returns the files written.
+    pub async fn archive(
+        mut self,
+        mut receiver: Receiver<IncomingMessage<Value>>,
+    ) -> Result<Vec<PathBuf>, ArchiveError> {
+        while let Some(incoming) = receiver.recv().await {
+            self.write(&ArchivedMessage::from(incoming))?;
+        }
+        self.finish()
+    }
+
+    /// Complete the current file and return every file written.
+    pub fn finish(mut self) -> Result<Vec<PathBuf>, ArchiveError> {
+        self.close()?;
+        Ok(self.files)
+    }
+
+    fn is_full(&self, file: &OpenFile) -> bool {
+        file.bytes >= self.max_bytes || self.max_records.is_some_and(|max| file.records >= max)
+    }
+
+    fn open(&mut self) -> Result<OpenFile, ArchiveError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let extension = if self.compress {
+            COMPRESSED_FILE_EXTENSION
+        } else {
+            ARCHIVE_FILE_EXTENSION
+        };
+        let path = self.dir.join(format!(
+            "{}-{}-{:05}{extension}",
+            self.prefix,
+            self.started,
+            self.files.len() + 1
+        ));
+        let writer = BufWriter::new(File::create_new(&path)?);
+        let sink = if self.compress {
+            Sink::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            Sink::Plain(writer)
+        };
+        info!("Archiving to {}", path.display());
+        self.files.push(path);
+        Ok(OpenFile {
+            sink,
+            bytes: 0,
+            records: 0,
+        })
+    }
+
+    fn close(&mut self) -> Result<(), ArchiveError> {
+        if let Some(file) = self.current.take() {
+            file.sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the messages of one archive file, gzipped or not, in the order they were written.
+pub struct ArchiveReader {
+    path: PathBuf,
+    lines: Lines<Box<dyn BufRead + Send>>,
+    line: usize,
+}
+
+impl ArchiveReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let reader: Box<dyn BufRead + Send> = if is_compressed(&path) {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        Ok(Self {
+            path,
+            lines: reader.lines(),
+            line: 0,
+        })
+    }
+}
+
+impl Iterator for ArchiveReader {
+    type Item = Result<ArchivedMessage, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                serde_json::from_str(&line).map_err(|source| ArchiveError::Record {
+                    path: self.path.clone(),
+                    line: self.line,
+                    source,
+                }),
+            );
+        }
+    }
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(COMPRESSED_FILE_EXTENSION)
+}
+
+/// The archive files in `dir`, sorted by name so files from one writer come in order.
+pub fn archive_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, ArchiveError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.to_string_lossy();
+        if name.ends_with(ARCHIVE_FILE_EXTENSION) || name.ends_with(COMPRESSED_FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Every message in `files`, in order.
+pub fn read_archives(
+    files: &[PathBuf],
+) -> impl Iterator<Item = Result<ArchivedMessage, ArchiveError>> + '_ {
+    files
+        .iter()
+        .flat_map(|path| match ArchiveReader::open(path) {
+            Ok(reader) => Box::new(reader) as Box<dyn Iterator<Item = _>>,
+            Err(e) => Box::new(std::iter::once(Err(e))),
+        })
+}
+
+/// Which archived messages to replay.
+///
+/// Instruction and event types are alternatives: a message matches if it has any of them.
+/// Every other condition that is set must also hold.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayFilter {
+    topics: Vec<String>,
+    instruction_types: Vec<InstructionType>,
+    events: Vec<EventType>,
+    sources: Vec<String>,
+    created_from: Option<DateTime<Utc>>,
+    created_until: Option<DateTime<Utc>>,
+}
+
+impl ReplayFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only messages archived from `topic`.
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topics.push(topic.into());
+        self
+    }
+
+    pub fn instruction_type(mut self, instruction_type: InstructionType) -> Self {
+        self.instruction_types.push(instruction_type);
+        self
+    }
+
+    pub fn event(mut self, event: EventType) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Only messages whose `meta_data.source` is `source`.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.sources.push(source.into());
+        self
+    }
+
+    /// Only messages whose `created_at` is at or after `from`.
+    pub fn created_from(mut self, from: DateTime<Utc>) -> Self {
+        self.created_from = Some(from);
+        self
+    }
+
+    /// Only messages whose `created_at` is before `until`.
+    pub fn created_until(mut self, until: DateTime<Utc>) -> Self {
+        self.created_until = Some(until);
+        self
+    }
+
+    pub fn matches(&self, archived: &ArchivedMessage, message: &MessageBusMessage) -> bool {
+        let meta_data = &message.meta_data;
+        let type_matches = (self.instruction_types.is_empty() && self.events.is_empty())
+            || meta_data
+                .instruction_type
+                .as_ref()
+                .is_some_and(|t| self.instruction_types.contains(t))
+            || meta_data
+                .event
+                .as_ref()
+                .is_some_and(|e| self.events.contains(e));
+        let created_at = DateTime::parse_from_rfc3339(&meta_data.created_at)
+            .ok()
+            .map(|created_at| created_at.with_timezone(&Utc));
+        let time_matches = match (self.created_from, self.created_until) {
+            (None, None) => true,
+            (from, until) => created_at.is_some_and(|created_at| {
+                from.is_none_or(|from| created_at >= from)
+                    && until.is_none_or(|until| created_at < until)
+            }),
+        };
+
+        type_matches
+            && time_matches
+            && (self.topics.is_empty() || self.topics.contains(&archived.topic))
+            && (self.sources.is_empty() || self.sources.contains(&meta_data.source))
+    }
+}
+
+/// What a replay did with the messages it read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub replayed: u64,
+    /// Left out by the `ReplayFilter`.
+    pub filtered: u64,
+    /// Not decodable as a `MessageBusMessage`, or with encrypted fields that couldn't be bound
+    /// to a new idempotency key, so not replayed.
+    pub invalid: u64,
+}
+
+/// Republishes archived messages through an `EventProducer`, which decides the target topic.
+///
+/// Messages keep their Kafka key; messages archived without one are keyed by their original
+/// idempotency key. Consumers deduplicate on `idempotency_key`, so replaying into a topic that
+/// has already seen the messages needs `with_new_idempotency_keys`.
+pub struct Replayer {
+    producer: EventProducer,
+    filter: ReplayFilter,
+    replay_id: String,
+    new_idempotency_keys: bool,
+    new_created_at: bool,
+    encryptor: Option<FieldEncryptor>,
+}
+
+impl Replayer {
+    pub fn new(producer: EventProducer) -> Self {
+        Self {
+            producer,
+            filter: ReplayFilter::default(),
+            replay_id: uuid::Uuid::new_v4().to_string(),
+            new_idempotency_keys: false,
+            new_created_at: false,
+            encryptor: None,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: ReplayFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Rewrite each `idempotency_key` to `{original}:replay:{replay_id}`.
+    pub fn with_new_idempotency_keys(mut self) -> Self {
+        self.new_idempotency_keys = true;
+        self
+    }
+
+    /// Use `replay_id` in rewritten idempotency keys instead of a random UUID, so running the
+    /// same replay twice produces the same keys.
+    pub fn with_replay_id(mut self, replay_id: impl Into<String>) -> Self {
+        self.replay_id = replay_id.into();
+        self
+    }
+
+    /// Set each `created_at` to the time it is replayed.
+    pub fn with_new_created_at(mut self) -> Self {
+        self.new_created_at = true;
+        self
+    }
+
+    /// Re-encrypt encrypted fields under rewritten idempotency keys with `encryptor`. Without
+    /// one, or for fields encrypted with a KEK it doesn't hold, messages with encrypted fields
+    /// can't be replayed with new idempotency keys.
+    pub fn with_field_encryptor(mut self, encryptor: FieldEncryptor) -> Self {
+        self.encryptor = Some(encryptor);
+        self
+    }
+
+    /// The key and message `archived` would be replayed as, or `None` if the filter leaves it
+    /// out.
+    pub fn prepare(
+        &self,
+        archived: &ArchivedMessage,
+    ) -> Result<Option<(String, MessageBusMessage)>, ArchiveError> {
+        let mut message = archived.decode()?;
+        if !self.filter.matches(archived, &message) {
+            return Ok(None);
+        }
+
+        let key = archived
+            .key
+            .clone()
+            .unwrap_or_else(|| message.meta_data.idempotency_key.clone());
+        if self.new_idempotency_keys {
+            let idempotency_key = format!(
+                "{}:replay:{}",
+                message.meta_data.idempotency_key, self.replay_id
+            );
+            message = self.rekey(message, idempotency_key)?;
+        }
+        if self.new_created_at {
+            message.meta_data.created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        }
+        Ok(Some((key, message)))
+    }
+
+    // Give `message` a new idempotency key, re-encrypting its encrypted fields under it
+    fn rekey(
+        &self,
+        message: MessageBusMessage,
+        idempotency_key: String,
+    ) -> Result<MessageBusMessage, ArchiveError> {
+        if !has_encrypted_fields(&message)? {
+            let mut message = message;
+            message.meta_data.idempotency_key = idempotency_key;
+            return Ok(message);
+        }
+        let Some(encryptor) = &self.encryptor else {
+            return Err(ArchiveError::EncryptedFields(
+                message.meta_data.idempotency_key,
+            ));
+        };
+
+        let mut decrypted = encryptor.decrypt(&message)?;
+        if has_encrypted_fields(&decrypted)? {
+            return Err(ArchiveError::EncryptedFields(
+                message.meta_data.idempotency_key,
+            ));
+        }
+        decrypted.meta_data.idempotency_key = idempotency_key;
+        Ok(encryptor.encrypt(&decrypted)?)
+    }
+
+    /// Replay `messages` in order. Stops at the first archive read or send error.
+    pub async fn replay(
+        &self,
+        messages: impl IntoIterator<Item = Result<ArchivedMessage, ArchiveError>>,
+    ) -> Result<ReplayReport, ArchiveError> {
+        let mut report = ReplayReport::default();
+        for archived in messages {
+            let archived = archived?;
+            match self.prepare(&archived) {
+                Ok(Some((key, message))) => {
+                    self.producer.send_event(key, &message).await?;
+                    report.replayed += 1;
+                }
+                Ok(None) => report.filtered += 1,
+                Err(e) => {
+                    warn!(
+                        "Skipping {}/{}@{}: {e}",
+                        archived.topic, archived.partition, archived.offset
+                    );
+                    report.invalid += 1;
+                }
+            }
+        }
+        info!("Replay {} finished: {report:?}", self.replay_id);
+        Ok(report)
+    }
+}
+
+fn has_encrypted_fields(message: &MessageBusMessage) -> Result<bool, serde_json::Error> {
+    Ok(serde_json::to_value(&message.payload)?
+        .as_object()
+        .is_some_and(|payload| {
+            payload
+                .values()
+                .any(|value| value.as_str().is_some_and(FieldEncryptor::is_encrypted))
+        }))
+}
@@ -0,0 +1,166 @@
+//! Authorisation of consumed messages by their `meta_data.token`.
+//!
+//! `AuthGate` maps instruction types to the scope a sender must hold, e.g. `Mint` to
+//! `ledger:mint`. Tokens are checked either offline against a JWT signing key or online with
+//! `IdentityServiceClient::check_scope`. Messages that fail the check are sent to the quarantine
+//! channel instead of the consumer's regular channel.
+
+use crate::identity::models::TokenClaims;
+use crate::identity::IdentityServiceClient;
+use crate::message_bus::models::base::InstructionType;
+use crate::models::error::{KafkaError, KafkaResult, MykoboStatusCode};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthorisationError {
+    #[error("message has no token")]
+    MissingToken,
+    #[error("message meta_data can't be read: {0}")]
+    UnreadableMetaData(String),
+    #[error("token is invalid: {0}")]
+    InvalidToken(String),
+    #[error("token does not grant scope {0:?}")]
+    MissingScope(String),
+    /// The identity service could not be asked. `EventConsumer` redelivers the message after a
+    /// backoff rather than quarantining it, and doesn't commit its offset.
+    #[error("token could not be checked: {0}")]
+    Unavailable(String),
+}
+
+/// Key and validation rules for checking tokens offline.
+pub struct JwtVerifier {
+    pub key: DecodingKey,
+    pub validation: Validation,
+}
+
+/// How an `AuthGate` checks a token against a scope.
+pub enum TokenVerifier {
+    /// Verify the signature and expiry locally and read the `scope` claim.
+    Jwt(Box<JwtVerifier>),
+    /// Ask the identity service. The client is locked for each check.
+    Identity(Arc<Mutex<IdentityServiceClient>>),
+}
+
+impl TokenVerifier {
+    pub fn jwt(key: DecodingKey, validation: Validation) -> Self {
+        TokenVerifier::Jwt(Box::new(JwtVerifier { key, validation }))
+    }
+
+    pub fn identity(client: IdentityServiceClient) -> Self {
+        TokenVerifier::Identity(Arc::new(Mutex::new(client)))
+    }
+
+    pub async fn check_scope(&self, token: &str, scope: &str) -> Result<(), AuthorisationError> {
+        match self {
+            TokenVerifier::Jwt(jwt) => {
+                let claims = decode::<TokenClaims>(token, &jwt.key, &jwt.validation)
+                    .map_err(|e| AuthorisationError::InvalidToken(e.to_string()))?
+                    .claims;
+                if claims.scope.iter().any(|granted| granted == scope) {
+                    Ok(())
+                } else {
+                    Err(AuthorisationError::MissingScope(scope.to_string()))
+                }
+            }
+            TokenVerifier::Identity(client) => {
+                match client.lock().await.check_scope(token, scope).await {
+                    Ok(response) if response.authorised => Ok(()),
+                    Ok(_) => Err(AuthorisationError::MissingScope(scope.to_string())),
+                    Err(e) if e.status == MykoboStatusCode::Unauthorised => {
+                        Err(AuthorisationError::InvalidToken(e.to_string()))
+                    }
+                    Err(e) => Err(AuthorisationError::Unavailable(e.to_string())),
+                }
+            }
+        }
+    }
+}
+
+/// A message the `AuthGate` refused, with enough of the Kafka record to inspect or replay it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuarantinedMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct TokenProbe {
+    meta_data: TokenMetaData,
+}
+
+#[derive(Deserialize)]
+struct TokenMetaData {
+    token: Option<String>,
+    instruction_type: Option<InstructionType>,
+}
+
+/// Consumer gate enforcing a required scope per `InstructionType`.
+///
+/// Only instruction types given a scope with `require_scope` are checked; events and other
+/// instructions pass through. Once any scope is required, messages whose `meta_data` can't be
+/// read are rejected, as they could carry a gated instruction.
+pub struct AuthGate {
+    verifier: TokenVerifier,
+    scopes: HashMap<InstructionType, String>,
+    quarantine: Sender<QuarantinedMessage>,
+}
+
+impl AuthGate {
+    pub fn new(verifier: TokenVerifier, quarantine: Sender<QuarantinedMessage>) -> Self {
+        Self {
+            verifier,
+            scopes: HashMap::new(),
+            quarantine,
+        }
+    }
+
+    pub fn require_scope(
+        mut self,
+        instruction_type: InstructionType,
+        scope: impl Into<String>,
+    ) -> Self {
+        self.scopes.insert(instruction_type, scope.into());
+        self
+    }
+
+    pub fn required_scope(&self, instruction_type: &InstructionType) -> Option<&str> {
+        self.scopes.get(instruction_type).map(String::as_str)
+    }
+
+    /// Check the token of a serialized `MessageBusMessage` against its instruction's scope.
+    pub async fn authorise(&self, payload: &[u8]) -> Result<(), AuthorisationError> {
+        if self.scopes.is_empty() {
+            return Ok(());
+        }
+        let probe = serde_json::from_slice::<TokenProbe>(payload)
+            .map_err(|e| AuthorisationError::UnreadableMetaData(e.to_string()))?;
+        let Some(scope) = probe
+            .meta_data
+            .instruction_type
+            .as_ref()
+            .and_then(|instruction_type| self.required_scope(instruction_type))
+        else {
+            return Ok(());
+        };
+
+        match probe.meta_data.token.as_deref() {
+            Some(token) if !token.is_empty() => self.verifier.check_scope(token, scope).await,
+            _ => Err(AuthorisationError::MissingToken),
+        }
+    }
+
+    pub async fn quarantine(&self, message: QuarantinedMessage) -> KafkaResult<()> {
+        self.quarantine.send(message).await.map_err(|e| {
+            KafkaError::MessageDelivery(format!("Failed to send message to quarantine: {e}"))
+        })
+    }
+}
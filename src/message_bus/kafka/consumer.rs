@@ -1,22 +1,174 @@
-use crate::message_bus::kafka::models::IncomingMessage;
+use crate::message_bus::kafka::auth::{AuthGate, AuthorisationError, QuarantinedMessage};
+#[cfg(feature = "metrics")]
+use crate::message_bus::kafka::metrics::{BusMetrics, STATISTICS_INTERVAL_MS};
+use crate::message_bus::kafka::models::{BusContext, IncomingMessage};
+use crate::message_bus::kafka::signing::SignatureVerifier;
+use crate::message_bus::models::claim_check::ClaimCheck;
+use crate::message_bus::models::decode::StrictDecoder;
+use crate::message_bus::models::encryption::FieldEncryptor;
+use crate::message_bus::models::schema::SchemaRegistry;
+use crate::message_bus::models::wire::{AvroCodec, WireFormat};
+use crate::message_bus::models::MessageBusMessage;
 use crate::models::error::{KafkaError, KafkaResult};
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use log::{debug, error, info, warn};
 use rdkafka::config::RDKafkaLogLevel;
 use rdkafka::consumer::stream_consumer::StreamConsumer;
 use rdkafka::consumer::{CommitMode, Consumer};
-use rdkafka::message::{Headers, OwnedMessage};
+use rdkafka::message::{BorrowedMessage, Headers, OwnedMessage};
+use rdkafka::topic_partition_list::{Offset, TopicPartitionList};
 use rdkafka::{ClientConfig, Message};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+const BACKFILL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Delay before redelivering a message the auth gate couldn't check, doubled for each further
+// consecutive outage up to `MAX_DEFERRAL_DELAY`
+const DEFERRAL_DELAY: Duration = Duration::from_secs(1);
+const MAX_DEFERRAL_DELAY: Duration = Duration::from_secs(30);
+
+/// Where a consumer should begin reading when it is repositioned with `EventConsumer::seek_to`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartPosition {
+    /// Resume from the consumer group's committed offsets.
+    Committed,
+    /// Re-read every message still retained on the subscribed topics.
+    Beginning,
+    /// Start the listed partitions at explicit offsets.
+    Offsets(Vec<PartitionOffset>),
+    /// Start every partition at the first message produced at or after the timestamp.
+    Timestamp(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionOffset {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+impl StartPosition {
+    /// Build the partition list to assign for the given `(topic, partition)` pairs.
+    ///
+    /// `Offsets` only assigns the partitions it lists. `Timestamp` encodes the timestamp in
+    /// milliseconds as the offset, ready to be resolved with `offsets_for_times`.
+    pub fn partition_list(&self, partitions: &[(String, i32)]) -> KafkaResult<TopicPartitionList> {
+        let mut list = TopicPartitionList::new();
+        let add = |list: &mut TopicPartitionList, topic: &str, partition: i32, offset: Offset| {
+            list.add_partition_offset(topic, partition, offset)
+                .map_err(|e| KafkaError::Seek(e.to_string()))
+        };
+
+        match self {
+            StartPosition::Committed => {
+                for (topic, partition) in partitions {
+                    add(&mut list, topic, *partition, Offset::Stored)?;
+                }
+            }
+            StartPosition::Beginning => {
+                for (topic, partition) in partitions {
+                    add(&mut list, topic, *partition, Offset::Beginning)?;
+                }
+            }
+            StartPosition::Offsets(offsets) => {
+                for o in offsets {
+                    add(&mut list, &o.topic, o.partition, Offset::Offset(o.offset))?;
+                }
+            }
+            StartPosition::Timestamp(timestamp) => {
+                for (topic, partition) in partitions {
+                    add(
+                        &mut list,
+                        topic,
+                        *partition,
+                        Offset::Offset(timestamp.timestamp_millis()),
+                    )?;
+                }
+            }
+        }
+
+        Ok(list)
+    }
+}
+
+/// The high watermark a backfill of a partition has to reach, or `None` if there is nothing to
+/// read because the partition is empty or its start is already at or past the watermark.
+///
+/// `start` is the offset the partition was assigned at; `committed` is the group's committed
+/// offset, which `Offset::Stored` starts from. Without a committed offset the consumer's
+/// `auto.offset.reset` of `earliest` applies.
+pub fn backfill_target(start: Offset, committed: Offset, (low, high): (i64, i64)) -> Option<i64> {
+    let start = match (start, committed) {
+        (Offset::Offset(offset), _) => offset,
+        (Offset::OffsetTail(tail), _) => high - tail,
+        (Offset::End, _) => high,
+        (Offset::Stored | Offset::Invalid, Offset::Offset(offset)) => offset,
+        _ => low,
+    };
+    (high > low && start.max(low) < high).then_some(high)
+}
+
+/// What the consumer did with a message it took off a topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Parsed and handed to the channel.
+    Forwarded,
+    /// Dropped without parsing because its `meta_data.expires_at` had passed. Checked after
+    /// signature verification and the auth gate. The offset is still committed.
+    Expired,
+    /// Refused by the `AuthGate` and sent to its quarantine channel. The offset is committed.
+    Quarantined,
+    /// Not checked because the `AuthGate` couldn't reach the identity service. The consumer
+    /// seeks back to the message after a backoff so it is redelivered; the offset is not
+    /// committed.
+    Deferred,
+}
+
+#[derive(Deserialize)]
+struct ExpiryProbe {
+    meta_data: ExpiryMetaData,
+}
+
+#[derive(Deserialize)]
+struct ExpiryMetaData {
+    expires_at: Option<String>,
+}
+
+/// Whether a serialized `MessageBusMessage` carries an `expires_at` at or before `now`.
+///
+/// Only `meta_data.expires_at` is read, so this works for any payload type. Messages that are
+/// not JSON, have no `expires_at` or an unreadable one are not expired.
+pub fn is_expired(payload: &[u8], now: DateTime<Utc>) -> bool {
+    serde_json::from_slice::<ExpiryProbe>(payload)
+        .ok()
+        .and_then(|probe| probe.meta_data.expires_at)
+        .and_then(|expires_at| DateTime::parse_from_rfc3339(&expires_at).ok())
+        .is_some_and(|expires_at| expires_at <= now)
+}
+
 pub struct EventConsumer<T> {
-    consumer: StreamConsumer,
+    consumer: StreamConsumer<BusContext>,
     max_retries: u32,
+    topics: Vec<String>,
     channel: Sender<IncomingMessage<T>>,
+    strict: Option<StrictDecoder>,
+    auth: Option<AuthGate>,
+    signatures: Option<SignatureVerifier>,
+    decryptor: Option<FieldEncryptor>,
+    claim_check: Option<ClaimCheck>,
+    avro: Option<AvroCodec>,
+    schemas: Option<Arc<SchemaRegistry>>,
+    // Partitions and start offsets assigned by `seek_to`
+    assignment: Mutex<Option<TopicPartitionList>>,
+    // Messages deferred in a row, for the redelivery backoff
+    deferrals: AtomicU32,
 }
 
 impl<T> EventConsumer<T>
@@ -30,6 +182,77 @@ where
         max_retries: u32,
         topics: &[&str],
         channel: Sender<IncomingMessage<T>>,
+    ) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            group_id,
+            client_id,
+            max_retries,
+            topics,
+            channel,
+            BusContext::default(),
+            &BTreeMap::new(),
+        )
+    }
+
+    /// Create a consumer with librdkafka settings that replace the defaults, such as the
+    /// lower-latency settings of a priority lane.
+    pub fn new_with_overrides(
+        brokers: &str,
+        group_id: &str,
+        client_id: &str,
+        max_retries: u32,
+        topics: &[&str],
+        channel: Sender<IncomingMessage<T>>,
+        overrides: &BTreeMap<String, String>,
+    ) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            group_id,
+            client_id,
+            max_retries,
+            topics,
+            channel,
+            BusContext::default(),
+            overrides,
+        )
+    }
+
+    /// Create a consumer that reports lag, throughput, retries and handler latency to `metrics`.
+    #[cfg(feature = "metrics")]
+    pub fn new_with_metrics(
+        brokers: &str,
+        group_id: &str,
+        client_id: &str,
+        max_retries: u32,
+        topics: &[&str],
+        channel: Sender<IncomingMessage<T>>,
+        metrics: Arc<BusMetrics>,
+    ) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            group_id,
+            client_id,
+            max_retries,
+            topics,
+            channel,
+            BusContext {
+                metrics: Some(metrics),
+            },
+            &BTreeMap::new(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn with_context(
+        brokers: &str,
+        group_id: &str,
+        client_id: &str,
+        max_retries: u32,
+        topics: &[&str],
+        channel: Sender<IncomingMessage<T>>,
+        context: BusContext,
+        overrides: &BTreeMap<String, String>,
     ) -> KafkaResult<Self> {
         let sasl_username = env::var("KAFKA_API_KEY").ok();
         let sasl_password = env::var("KAFKA_API_SECRET").ok();
@@ -46,6 +269,8 @@ where
             .set("session.timeout.ms", "45000")
             .set("heartbeat.interval.ms", "3000")
             .set("enable.auto.commit", "true")
+            // Only offsets of handled messages are stored, so a deferred message isn't skipped
+            .set("enable.auto.offset.store", "false")
             .set("socket.keepalive.enable", "true")
             .set("socket.connection.setup.timeout.ms", "10000")
             .set("connections.max.idle.ms", "540000")
@@ -68,75 +293,440 @@ where
                 .set("sasl.password", password);
         }
 
-        let stream_consumer: StreamConsumer = config
+        for (key, value) in overrides {
+            config.set(key, value);
+        }
+
+        #[cfg(feature = "metrics")]
+        if context.metrics.is_some() {
+            config.set("statistics.interval.ms", STATISTICS_INTERVAL_MS);
+        }
+
+        let stream_consumer: StreamConsumer<BusContext> = config
             .set_log_level(RDKafkaLogLevel::Info)
-            .create()
+            .create_with_context(context)
             .map_err(|e| KafkaError::ClientCreation(e.to_string()))?;
 
         stream_consumer
             .subscribe(topics)
-            .expect("Can't subscribe to specified topics");
+            .map_err(|e| KafkaError::Subscription(e.to_string()))?;
 
         Ok(EventConsumer {
             consumer: stream_consumer,
             max_retries,
+            topics: topics.iter().map(|t| t.to_string()).collect(),
             channel,
+            strict: None,
+            auth: None,
+            signatures: None,
+            decryptor: None,
+            claim_check: None,
+            avro: None,
+            schemas: None,
+            assignment: Mutex::new(None),
+            deferrals: AtomicU32::new(0),
         })
     }
 
+    /// Check every message with `decoder` before deserializing it, rejecting raw payloads for
+    /// types not registered as raw-capable and typed payloads with unknown fields.
+    pub fn with_strict_decoding(mut self, decoder: StrictDecoder) -> Self {
+        self.strict = Some(decoder);
+        self
+    }
+
+    /// Check each message's token with `gate` before it is parsed. Refused messages go to the
+    /// gate's quarantine channel; messages the identity service could not check are redelivered
+    /// after a backoff, see `Delivery::Deferred`.
+    pub fn with_auth_gate(mut self, gate: AuthGate) -> Self {
+        self.auth = Some(gate);
+        self
+    }
+
+    /// Verify envelope signatures before a message is parsed. Messages that fail verification
+    /// are not forwarded and count as failed.
+    pub fn with_signature_verifier(mut self, verifier: SignatureVerifier) -> Self {
+        self.signatures = Some(verifier);
+        self
+    }
+
+    /// Decrypt encrypted payload fields before a message is decoded. Fields encrypted with a
+    /// key `decryptor` doesn't hold are forwarded still encrypted.
+    pub fn with_field_decryptor(mut self, decryptor: FieldEncryptor) -> Self {
+        self.decryptor = Some(decryptor);
+        self
+    }
+
+    /// Fetch payloads that producers offloaded with a claim check from its blob store before a
+    /// message is decoded. Without one, such messages are forwarded with the reference as a raw
+    /// payload.
+    pub fn with_claim_check(mut self, claim_check: ClaimCheck) -> Self {
+        self.claim_check = Some(claim_check);
+        self
+    }
+
+    /// Decode messages whose `content-type` is Avro, fetching writer schemas from the codec's
+    /// schema registry. Without a codec only JSON messages are accepted.
+    pub fn with_avro(mut self, codec: AvroCodec) -> Self {
+        self.avro = Some(codec);
+        self
+    }
+
+    /// Upcast payloads with the upcasters in `schemas` rather than the built-in
+    /// `SCHEMA_REGISTRY`. Runs before strict decoding, so a strict decoder sees upcast payloads.
+    pub fn with_schemas(mut self, schemas: Arc<SchemaRegistry>) -> Self {
+        self.schemas = Some(schemas);
+        self
+    }
+
     pub async fn start(&self) -> KafkaResult<()> {
         let mut message_stream = self.consumer.stream();
 
         while let Some(message_result) = message_stream.next().await {
             match message_result {
-                Ok(message) => {
-                    match self
-                        .process_with_retry(message.detach(), self.channel.clone())
-                        .await
+                Ok(message) => self.handle_message(&message).await?,
+                Err(e) => {
+                    error!("Error receiving message: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reposition the consumer before calling `start` or `consume_until_now`.
+    ///
+    /// Anything other than `StartPosition::Committed` replaces the group subscription with a
+    /// manual assignment of the subscribed topics' partitions, so the group will not rebalance
+    /// them across other members. Offsets are still committed for the consumer group.
+    pub fn seek_to(&self, position: StartPosition) -> KafkaResult<()> {
+        if position == StartPosition::Committed {
+            return Ok(());
+        }
+
+        let partitions = self.partitions()?;
+        let mut assignment = position.partition_list(&partitions)?;
+        if let StartPosition::Timestamp(timestamp) = &position {
+            assignment = self
+                .consumer
+                .offsets_for_times(assignment, METADATA_TIMEOUT)
+                .map_err(|e| KafkaError::Seek(e.to_string()))?;
+            info!("Resolved offsets for {timestamp}: {assignment:?}");
+        }
+
+        self.consumer.unsubscribe();
+        self.consumer
+            .assign(&assignment)
+            .map_err(|e| KafkaError::Seek(e.to_string()))?;
+        *self
+            .assignment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(assignment);
+        Ok(())
+    }
+
+    /// Consume until every assigned partition reaches the high watermark it had when this was
+    /// called, then stop. Returns the number of messages processed.
+    ///
+    /// Intended for backfills after `seek_to`; messages produced while the backfill runs are
+    /// left for the next regular `start`. The consumer needs the manual assignment `seek_to`
+    /// makes for any position but `StartPosition::Committed`, as a group subscription can be
+    /// rebalanced away mid-backfill. Fails with `KafkaError::Timeout` if the backfill hasn't
+    /// caught up within `timeout`.
+    pub async fn consume_until_now(&self, timeout: Duration) -> KafkaResult<u64> {
+        let assignment = self
+            .assignment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| {
+                KafkaError::Seek(
+                    "consume_until_now needs a manual assignment, seek_to a position first"
+                        .to_string(),
+                )
+            })?;
+        let committed = self
+            .consumer
+            .committed_offsets(assignment.clone(), METADATA_TIMEOUT)
+            .map_err(|e| KafkaError::Seek(e.to_string()))?;
+
+        let mut remaining: HashMap<(String, i32), i64> = HashMap::new();
+        for element in assignment.elements() {
+            let (topic, partition) = (element.topic(), element.partition());
+            let watermarks = self
+                .consumer
+                .fetch_watermarks(topic, partition, METADATA_TIMEOUT)
+                .map_err(|e| KafkaError::Seek(e.to_string()))?;
+            let committed = committed
+                .find_partition(topic, partition)
+                .map_or(Offset::Invalid, |c| c.offset());
+            if let Some(high) = backfill_target(element.offset(), committed, watermarks) {
+                remaining.insert((topic.to_string(), partition), high);
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut processed = 0;
+        let mut finished = TopicPartitionList::new();
+        let mut message_stream = self.consumer.stream();
+        let mut timed_out = false;
+        while !remaining.is_empty() {
+            if tokio::time::Instant::now() >= deadline {
+                timed_out = true;
+                break;
+            }
+            match tokio::time::timeout(BACKFILL_POLL_INTERVAL, message_stream.next()).await {
+                Ok(Some(Ok(message))) => {
+                    // Messages already fetched past the target are processed rather than
+                    // skipped, since handling them commits their offsets.
+                    self.handle_message(&message).await?;
+                    processed += 1;
+
+                    let key = (message.topic().to_string(), message.partition());
+                    if remaining
+                        .get(&key)
+                        .is_some_and(|high| message.offset() + 1 >= *high)
                     {
-                        Ok(_) => {
-                            info!(
-                                "Message processed successfully, committing offset [{}]",
-                                message.offset()
-                            );
-                            self.consumer
-                                .commit_message(&message, CommitMode::Async)
-                                .map_err(|e| KafkaError::MessageDelivery(e.to_string()))?;
-                        }
-                        Err(e) => {
-                            error!("Failed to process message: {}", e);
-                            // Implement dead letter queue logic here
-                        }
+                        remaining.remove(&key);
+                        self.pause_partition(&mut finished, &key.0, key.1)?;
                     }
                 }
-                Err(e) => {
+                Ok(Some(Err(e))) => {
                     error!("Error receiving message: {}", e);
                     tokio::time::sleep(Duration::from_secs(1)).await;
                 }
+                Ok(None) => break,
+                Err(_) => {
+                    for (topic, partition) in self.caught_up(&remaining)? {
+                        remaining.remove(&(topic.clone(), partition));
+                        self.pause_partition(&mut finished, &topic, partition)?;
+                    }
+                }
+            }
+        }
+
+        if finished.count() > 0 {
+            self.consumer
+                .resume(&finished)
+                .map_err(|e| KafkaError::Seek(e.to_string()))?;
+        }
+
+        if timed_out {
+            return Err(KafkaError::Timeout(format!(
+                "backfill processed {processed} messages but {} partitions had not caught up \
+                 after {timeout:?}",
+                remaining.len()
+            )));
+        }
+        info!("Backfill complete, processed {processed} messages");
+        Ok(processed)
+    }
+
+    fn pause_partition(
+        &self,
+        paused: &mut TopicPartitionList,
+        topic: &str,
+        partition: i32,
+    ) -> KafkaResult<()> {
+        let mut list = TopicPartitionList::new();
+        list.add_partition(topic, partition);
+        self.consumer
+            .pause(&list)
+            .map_err(|e| KafkaError::Seek(e.to_string()))?;
+        paused.add_partition(topic, partition);
+        Ok(())
+    }
+
+    /// Partitions whose fetch position has already reached their target, e.g. because the
+    /// seek landed at or beyond the high watermark.
+    fn caught_up(
+        &self,
+        remaining: &HashMap<(String, i32), i64>,
+    ) -> KafkaResult<Vec<(String, i32)>> {
+        let position = self
+            .consumer
+            .position()
+            .map_err(|e| KafkaError::Seek(e.to_string()))?;
+        Ok(position
+            .elements()
+            .iter()
+            .filter(|element| {
+                let key = (element.topic().to_string(), element.partition());
+                match (element.offset(), remaining.get(&key)) {
+                    (Offset::Offset(offset), Some(high)) => offset >= *high,
+                    (Offset::End, Some(_)) => true,
+                    _ => false,
+                }
+            })
+            .map(|element| (element.topic().to_string(), element.partition()))
+            .collect())
+    }
+
+    fn partitions(&self) -> KafkaResult<Vec<(String, i32)>> {
+        let mut partitions = Vec::new();
+        for topic in &self.topics {
+            let metadata = self
+                .consumer
+                .fetch_metadata(Some(topic), METADATA_TIMEOUT)
+                .map_err(|e| KafkaError::Seek(e.to_string()))?;
+            for t in metadata.topics() {
+                partitions.extend(
+                    t.partitions()
+                        .iter()
+                        .map(|p| (t.name().to_string(), p.id())),
+                );
+            }
+        }
+        Ok(partitions)
+    }
+
+    async fn handle_message(&self, message: &BorrowedMessage<'_>) -> KafkaResult<()> {
+        #[cfg(feature = "metrics")]
+        let received_at = std::time::Instant::now();
+
+        let result = self
+            .process_with_retry(message.detach(), self.channel.clone())
+            .await;
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.consumer.context().metrics {
+            match &result {
+                Ok(Delivery::Forwarded) => {
+                    metrics.record_processed(message.topic(), received_at.elapsed())
+                }
+                Ok(Delivery::Expired) => metrics.record_expired(message.topic()),
+                Ok(Delivery::Quarantined) => metrics.record_quarantined(message.topic()),
+                Ok(Delivery::Deferred) => metrics.record_retry(message.topic()),
+                Err(_) => metrics.record_failed(message.topic(), received_at.elapsed()),
             }
         }
 
+        match result {
+            Ok(Delivery::Deferred) => self.redeliver(message).await?,
+            Ok(delivery) => {
+                self.deferrals.store(0, Ordering::Relaxed);
+                match delivery {
+                    Delivery::Forwarded => info!(
+                        "Message processed successfully, committing offset [{}]",
+                        message.offset()
+                    ),
+                    Delivery::Expired => info!(
+                        "Message expired, dropping and committing offset [{}]",
+                        message.offset()
+                    ),
+                    Delivery::Quarantined => warn!(
+                        "Message quarantined, committing offset [{}]",
+                        message.offset()
+                    ),
+                    Delivery::Deferred => unreachable!("deferred messages are redelivered"),
+                }
+                self.consumer
+                    .commit_message(message, CommitMode::Async)
+                    .map_err(|e| KafkaError::MessageDelivery(e.to_string()))?;
+            }
+            Err(e) => {
+                error!("Failed to process message: {}", e);
+                // Implement dead letter queue logic here
+            }
+        }
         Ok(())
     }
+    // Wait out the backoff, then seek back so the message is fetched again
+    async fn redeliver(&self, message: &BorrowedMessage<'_>) -> KafkaResult<()> {
+        let deferrals = self.deferrals.fetch_add(1, Ordering::Relaxed);
+        let delay = DEFERRAL_DELAY
+            .saturating_mul(2u32.saturating_pow(deferrals))
+            .min(MAX_DEFERRAL_DELAY);
+        warn!(
+            "Redelivering offset [{}] of {}/{} in {delay:?}",
+            message.offset(),
+            message.topic(),
+            message.partition()
+        );
+        tokio::time::sleep(delay).await;
+        self.consumer
+            .seek(
+                message.topic(),
+                message.partition(),
+                Offset::Offset(message.offset()),
+                METADATA_TIMEOUT,
+            )
+            .map_err(|e| KafkaError::Seek(e.to_string()))
+    }
+
     async fn process_with_retry(
         &self,
         message: OwnedMessage,
         message_channel: Sender<IncomingMessage<T>>,
-    ) -> KafkaResult<()> {
+    ) -> KafkaResult<Delivery> {
+        let payload = self.json_payload(&message).await?;
+
+        // Verified after Avro decoding, as producers sign the JSON form
+        if let Some(verifier) = &self.signatures {
+            verifier
+                .verify(&payload, &message_headers(&message))
+                .map_err(|e| {
+                    error!("Message failed signature verification: {e}");
+                    KafkaError::Signature(e.to_string())
+                })?;
+        }
+
+        if let Some(gate) = &self.auth {
+            match gate.authorise(&payload).await {
+                Ok(()) => {}
+                Err(AuthorisationError::Unavailable(e)) => {
+                    warn!("Auth gate could not check message, deferring it: {e}");
+                    return Ok(Delivery::Deferred);
+                }
+                Err(rejection) => {
+                    warn!("Message rejected by auth gate: {rejection}");
+                    gate.quarantine(QuarantinedMessage {
+                        topic: message.topic().to_string(),
+                        partition: message.partition(),
+                        offset: message.offset(),
+                        headers: message_headers(&message),
+                        payload: message.payload().unwrap_or_default().to_vec(),
+                        reason: rejection.to_string(),
+                    })
+                    .await?;
+                    return Ok(Delivery::Quarantined);
+                }
+            }
+        }
+
+        // Only once the signature and sender are checked, so a forged `expires_at` can't get a
+        // message dropped
+        if is_expired(&payload, Utc::now()) {
+            return Ok(Delivery::Expired);
+        }
+
+        let payload = match &self.claim_check {
+            Some(claim_check) => claim_check.rehydrate(&payload).await.map_err(|e| {
+                error!("Failed to fetch claim-checked payload: {}", e);
+                KafkaError::ClaimCheck(e.to_string())
+            })?,
+            None => payload,
+        };
+
         let mut retries = 0;
         let mut backoff = Duration::from_secs(1);
 
         while retries < self.max_retries {
-            match self.parse_message(&message) {
+            match self.parse_message(&message, &payload) {
                 Ok(incoming_message) => match message_channel.send(incoming_message).await {
                     Ok(_) => {
                         debug!("Successfully forwarded incoming message to channel");
-                        return Ok(());
+                        return Ok(Delivery::Forwarded);
                     }
                     Err(e) => error!("Failed to send message to channel: {e}"),
                 },
                 Err(e) => {
                     warn!("Retry {} failed: {}", retries, e);
+                    #[cfg(feature = "metrics")]
+                    if let Some(metrics) = &self.consumer.context().metrics {
+                        metrics.record_retry(message.topic());
+                    }
                     retries += 1;
                     tokio::time::sleep(backoff).await;
                     backoff *= 2;
@@ -147,32 +737,62 @@ where
         Err(KafkaError::MessageDelivery("Max retries exceeded".into()))
     }
 
-    fn parse_message(&self, message: &OwnedMessage) -> KafkaResult<IncomingMessage<T>> {
-        let headers = match message.headers() {
-            Some(maybe_headers) => maybe_headers
-                .iter()
-                .map(|h| {
-                    (
-                        h.key.to_string(),
-                        h.value
-                            .map(|header_value| {
-                                str::from_utf8(header_value).unwrap_or("").to_string()
-                            })
-                            .unwrap_or_default(),
-                    )
-                })
-                .collect::<HashMap<String, String>>(),
-            None => {
-                warn!("No headers found in the message");
-                HashMap::new()
-            }
-        };
+    /// The message body as JSON, decoding it first if it was sent as Avro.
+    async fn json_payload(&self, message: &OwnedMessage) -> KafkaResult<Vec<u8>> {
+        let body = message.payload().unwrap_or_default();
+        let format = WireFormat::from_headers(&message_headers(message))
+            .map_err(|e| KafkaError::Encoding(e.to_string()))?;
+        match (format, &self.avro) {
+            (WireFormat::Json, _) => Ok(body.to_vec()),
+            (WireFormat::Avro, Some(codec)) => codec.decode(body).await.map_err(|e| {
+                error!("Failed to decode Avro message: {}", e);
+                KafkaError::Encoding(e.to_string())
+            }),
+            (WireFormat::Avro, None) => Err(KafkaError::Encoding(
+                "received an Avro message without an Avro codec".into(),
+            )),
+        }
+    }
 
-        let payload = message.clone().payload().unwrap_or_default().to_vec();
+    fn parse_message(
+        &self,
+        message: &OwnedMessage,
+        payload: &[u8],
+    ) -> KafkaResult<IncomingMessage<T>> {
+        let headers = message_headers(message);
+
+        let mut payload = payload.to_vec();
+        if let Some(decryptor) = &self.decryptor {
+            payload = decryptor.decrypt_bytes(&payload).map_err(|e| {
+                error!("Failed to decrypt message fields: {}", e);
+                KafkaError::Encryption(e.to_string())
+            })?;
+        }
+        if let Some(schemas) = &self.schemas {
+            let mut json: serde_json::Value = serde_json::from_slice(&payload)?;
+            MessageBusMessage::upcast_json(&mut json, schemas).map_err(|e| {
+                error!("Failed to upcast message payload: {}", e);
+                KafkaError::Upcast(e.to_string())
+            })?;
+            payload = serde_json::to_vec(&json)?;
+        }
+        if let Some(strict) = &self.strict {
+            strict.decode_slice(payload.as_slice()).map_err(|e| {
+                error!("Message rejected by strict decoding: {}", e);
+                KafkaError::StrictDecoding(e.to_string())
+            })?;
+        }
         match serde_json::from_slice(payload.as_slice()) {
             Ok(content) => Ok(IncomingMessage {
                 headers,
                 payload: content,
+                key: message
+                    .key()
+                    .and_then(|key| str::from_utf8(key).ok())
+                    .map(str::to_string),
+                topic: message.topic().to_string(),
+                partition: message.partition(),
+                offset: message.offset(),
             }),
             Err(e) => {
                 error!("Failed to deserialize message payload: {}", e);
@@ -181,3 +801,23 @@ where
         }
     }
 }
+
+fn message_headers(message: &OwnedMessage) -> HashMap<String, String> {
+    match message.headers() {
+        Some(maybe_headers) => maybe_headers
+            .iter()
+            .map(|h| {
+                (
+                    h.key.to_string(),
+                    h.value
+                        .map(|header_value| str::from_utf8(header_value).unwrap_or("").to_string())
+                        .unwrap_or_default(),
+                )
+            })
+            .collect::<HashMap<String, String>>(),
+        None => {
+            warn!("No headers found in the message");
+            HashMap::new()
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Prometheus metrics for `EventConsumer` and `EventProducer`.
+//!
+//! Lag and producer queue depth are sourced from librdkafka statistics; processed, failed and
+//! retried counts and latencies are recorded by the consumer loop and `send_event`.
+
+use crate::models::error::{KafkaError, KafkaResult};
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use rdkafka::statistics::Statistics;
+use std::time::Duration;
+
+const NAMESPACE: &str = "mykobo_bus";
+
+/// How often clients created with metrics emit librdkafka statistics.
+pub const STATISTICS_INTERVAL_MS: &str = "5000";
+
+pub struct BusMetrics {
+    registry: Registry,
+    consumer_lag: IntGaugeVec,
+    messages_processed: IntCounterVec,
+    messages_failed: IntCounterVec,
+    messages_retried: IntCounterVec,
+    messages_expired: IntCounterVec,
+    messages_quarantined: IntCounterVec,
+    handler_latency: HistogramVec,
+    delivery_latency: HistogramVec,
+    producer_queue_depth: IntGauge,
+}
+
+impl BusMetrics {
+    /// Create the metric families and register them with a fresh registry.
+    pub fn new() -> KafkaResult<Self> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Create the metric families and register them with an existing registry, e.g. one
+    /// shared with the service's HTTP metrics.
+    pub fn with_registry(registry: Registry) -> KafkaResult<Self> {
+        let consumer_lag = IntGaugeVec::new(
+            Opts::new(
+                "consumer_lag",
+                "Messages between the committed offset and the high watermark",
+            )
+            .namespace(NAMESPACE),
+            &["topic", "partition"],
+        )
+        .map_err(metrics_error)?;
+        let messages_processed = IntCounterVec::new(
+            Opts::new(
+                "messages_processed_total",
+                "Messages forwarded to the handler channel",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let messages_failed = IntCounterVec::new(
+            Opts::new(
+                "messages_failed_total",
+                "Messages that exhausted their retries",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let messages_retried = IntCounterVec::new(
+            Opts::new(
+                "messages_retried_total",
+                "Failed processing attempts that were retried",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let messages_expired = IntCounterVec::new(
+            Opts::new(
+                "messages_expired_total",
+                "Messages dropped because their expires_at had passed",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let messages_quarantined = IntCounterVec::new(
+            Opts::new(
+                "messages_quarantined_total",
+                "Messages refused by the auth gate and sent to quarantine",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let handler_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "handler_latency_seconds",
+                "Time from receiving a message to handing it over, including retries",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let delivery_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "producer_delivery_latency_seconds",
+                "Time from send_event to broker acknowledgement",
+            )
+            .namespace(NAMESPACE),
+            &["topic"],
+        )
+        .map_err(metrics_error)?;
+        let producer_queue_depth = IntGauge::with_opts(
+            Opts::new(
+                "producer_queue_depth",
+                "Messages waiting in the producer queues",
+            )
+            .namespace(NAMESPACE),
+        )
+        .map_err(metrics_error)?;
+
+        let collectors: Vec<Box<dyn Collector>> = vec![
+            Box::new(consumer_lag.clone()),
+            Box::new(messages_processed.clone()),
+            Box::new(messages_failed.clone()),
+            Box::new(messages_retried.clone()),
+            Box::new(messages_expired.clone()),
+            Box::new(messages_quarantined.clone()),
+            Box::new(handler_latency.clone()),
+            Box::new(delivery_latency.clone()),
+            Box::new(producer_queue_depth.clone()),
+        ];
+        for collector in collectors {
+            registry.register(collector).map_err(metrics_error)?;
+        }
+
+        Ok(Self {
+            registry,
+            consumer_lag,
+            messages_processed,
+            messages_failed,
+            messages_retried,
+            messages_expired,
+            messages_quarantined,
+            handler_latency,
+            delivery_latency,
+            producer_queue_depth,
+        })
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Render every registered metric in the Prometheus text exposition format.
+    pub fn render(&self) -> KafkaResult<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(metrics_error)?;
+        String::from_utf8(buffer).map_err(metrics_error)
+    }
+
+    /// Update lag and queue depth gauges from a librdkafka statistics report.
+    pub fn record_statistics(&self, statistics: &Statistics) {
+        if statistics.client_type == "producer" {
+            self.producer_queue_depth.set(statistics.msg_cnt as i64);
+            return;
+        }
+
+        for (topic, topic_stats) in &statistics.topics {
+            for (partition, partition_stats) in &topic_stats.partitions {
+                // Partition -1 is librdkafka's internal unassigned partition, and a lag of -1
+                // means the partition is not being consumed.
+                if *partition < 0 || partition_stats.consumer_lag < 0 {
+                    continue;
+                }
+                self.consumer_lag
+                    .with_label_values(&[topic.as_str(), &partition.to_string()])
+                    .set(partition_stats.consumer_lag);
+            }
+        }
+    }
+
+    pub fn record_processed(&self, topic: &str, latency: Duration) {
+        self.messages_processed.with_label_values(&[topic]).inc();
+        self.handler_latency
+            .with_label_values(&[topic])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn record_failed(&self, topic: &str, latency: Duration) {
+        self.messages_failed.with_label_values(&[topic]).inc();
+        self.handler_latency
+            .with_label_values(&[topic])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn record_retry(&self, topic: &str) {
+        self.messages_retried.with_label_values(&[topic]).inc();
+    }
+
+    pub fn record_expired(&self, topic: &str) {
+        self.messages_expired.with_label_values(&[topic]).inc();
+    }
+
+    pub fn record_quarantined(&self, topic: &str) {
+        self.messages_quarantined.with_label_values(&[topic]).inc();
+    }
+
+    pub fn record_delivery(&self, topic: &str, latency: Duration) {
+        self.delivery_latency
+            .with_label_values(&[topic])
+            .observe(latency.as_secs_f64());
+    }
+}
+
+fn metrics_error(e: impl std::fmt::Display) -> KafkaError {
+    KafkaError::Metrics(e.to_string())
+}
@@ -1,3 +1,10 @@
+pub mod archive;
+pub mod admin;
+pub mod auth;
 pub mod consumer;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod models;
 pub mod producer;
+pub mod routing;
+pub mod signing;
@@ -1,7 +1,14 @@
+#[cfg(feature = "metrics")]
+use crate::message_bus::kafka::metrics::BusMetrics;
+use crate::message_bus::models::redaction::{debug_redacted, Redact, SensitiveField};
 use log::info;
 use rdkafka::client::ClientContext;
+#[cfg(feature = "metrics")]
+use rdkafka::statistics::Statistics;
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt;
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
 
 use rdkafka::consumer::{BaseConsumer, ConsumerContext, Rebalance};
 use rdkafka::error::KafkaResult;
@@ -24,17 +31,99 @@ impl ConsumerContext for CustomContext {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Client context shared by `EventConsumer` and `EventProducer`.
+///
+/// With the `metrics` feature it forwards librdkafka statistics to `BusMetrics`.
+#[derive(Default, Clone)]
+pub struct BusContext {
+    #[cfg(feature = "metrics")]
+    pub metrics: Option<Arc<BusMetrics>>,
+}
+
+impl ClientContext for BusContext {
+    #[cfg(feature = "metrics")]
+    fn stats(&self, statistics: Statistics) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_statistics(&statistics);
+        }
+    }
+}
+impl ConsumerContext for BusContext {}
+
+/// A message forwarded by `EventConsumer`, with the Kafka metadata it was read with.
+///
+/// Build one with `new` and the `with_*` methods; fields may be added in minor releases.
+#[derive(Serialize, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct IncomingMessage<T> {
     pub headers: HashMap<String, String>,
     pub payload: T,
+    /// The Kafka message key, if it had one that is valid UTF-8.
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub topic: String,
+    #[serde(default)]
+    pub partition: i32,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+impl<T> IncomingMessage<T> {
+    /// A message with no key, read from no particular topic, partition or offset.
+    pub fn new(headers: HashMap<String, String>, payload: T) -> Self {
+        Self {
+            headers,
+            payload,
+            key: None,
+            topic: String::new(),
+            partition: 0,
+            offset: 0,
+        }
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Record where the message was read from.
+    pub fn with_position(mut self, topic: impl Into<String>, partition: i32, offset: i64) -> Self {
+        self.topic = topic.into();
+        self.partition = partition;
+        self.offset = offset;
+        self
+    }
+}
+
+impl<T: Redact> Redact for IncomingMessage<T> {
+    const SENSITIVE_FIELDS: &'static [SensitiveField] = &[];
+
+    fn redacted_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "headers": self.headers,
+            "payload": self.payload.redacted_value(),
+            "key": self.key,
+            "topic": self.topic,
+            "partition": self.partition,
+            "offset": self.offset,
+        })
+    }
+}
+
+/// JSON with sensitive payload fields redacted. Use `unredacted()` for the full message.
+///
+/// A `serde_json::Value` payload is redacted whole. Other payload types implement `Redact`,
+/// with empty `SENSITIVE_FIELDS` to print them in full.
+impl<T: Redact> fmt::Display for IncomingMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.redacted_value())
+    }
 }
 
-impl<T> Display for IncomingMessage<T>
-where
-    for<'a> T: Serialize + Deserialize<'a>,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::json!(self))
+/// Redacted the same way as `Display`.
+impl<T: Redact> fmt::Debug for IncomingMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_redacted("IncomingMessage", &self.redacted_value(), f)
     }
 }
@@ -1,9 +1,19 @@
+#[cfg(feature = "metrics")]
+use crate::message_bus::kafka::metrics::{BusMetrics, STATISTICS_INTERVAL_MS};
+use crate::message_bus::kafka::models::BusContext;
+use crate::message_bus::kafka::signing::MessageSigner;
+use crate::message_bus::models::claim_check::ClaimCheck;
+use crate::message_bus::models::encryption::FieldEncryptor;
+use crate::message_bus::models::wire::{AvroCodec, WireFormat, CONTENT_TYPE_HEADER};
 use crate::models::error::{KafkaError, KafkaResult};
 use rdkafka::config::{ClientConfig, RDKafkaLogLevel};
 use rdkafka::message::{Header, OwnedHeaders};
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::env;
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
 use std::time::Duration;
 
 pub const MESSAGE_SOURCE: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -22,13 +32,71 @@ pub fn build_message_headers() -> OwnedHeaders {
 }
 
 pub struct EventProducer {
-    producer: FutureProducer,
+    producer: FutureProducer<BusContext>,
     topic: String,
     timeout: Duration,
+    signer: Option<MessageSigner>,
+    encryptor: Option<FieldEncryptor>,
+    claim_check: Option<ClaimCheck>,
+    avro: Option<AvroCodec>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<BusMetrics>>,
 }
 
 impl EventProducer {
     pub fn new(brokers: &str, timeout_in_secs: u64, topic: &str) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            timeout_in_secs,
+            topic,
+            BusContext::default(),
+            &BTreeMap::new(),
+        )
+    }
+
+    /// Create a producer with librdkafka settings that replace the defaults, such as the
+    /// lower-latency settings of a priority lane.
+    pub fn new_with_overrides(
+        brokers: &str,
+        timeout_in_secs: u64,
+        topic: &str,
+        overrides: &BTreeMap<String, String>,
+    ) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            timeout_in_secs,
+            topic,
+            BusContext::default(),
+            overrides,
+        )
+    }
+
+    /// Create a producer that reports delivery latency and queue depth to `metrics`.
+    #[cfg(feature = "metrics")]
+    pub fn new_with_metrics(
+        brokers: &str,
+        timeout_in_secs: u64,
+        topic: &str,
+        metrics: Arc<BusMetrics>,
+    ) -> KafkaResult<Self> {
+        Self::with_context(
+            brokers,
+            timeout_in_secs,
+            topic,
+            BusContext {
+                metrics: Some(metrics),
+            },
+            &BTreeMap::new(),
+        )
+    }
+
+    fn with_context(
+        brokers: &str,
+        timeout_in_secs: u64,
+        topic: &str,
+        context: BusContext,
+        overrides: &BTreeMap<String, String>,
+    ) -> KafkaResult<Self> {
         let sasl_username = env::var("KAFKA_API_KEY").ok();
         let sasl_password = env::var("KAFKA_API_SECRET").ok();
         let protocol = env::var("KAFKA_API_PROTOCOL").unwrap_or("SASL_SSL".to_string());
@@ -66,31 +134,130 @@ impl EventProducer {
                 .set("sasl.password", password);
         }
 
-        let producer: FutureProducer = config
+        for (key, value) in overrides {
+            config.set(key, value);
+        }
+
+        #[cfg(feature = "metrics")]
+        let metrics = context.metrics.clone();
+        #[cfg(feature = "metrics")]
+        if metrics.is_some() {
+            config.set("statistics.interval.ms", STATISTICS_INTERVAL_MS);
+        }
+
+        let producer: FutureProducer<BusContext> = config
             .set_log_level(RDKafkaLogLevel::Info)
-            .create()
+            .create_with_context(context)
             .map_err(|e| KafkaError::ClientCreation(e.to_string()))?;
 
         Ok(EventProducer {
             producer,
             topic: topic.to_string(),
             timeout: Duration::from_secs(timeout_in_secs),
+            signer: None,
+            encryptor: None,
+            claim_check: None,
+            avro: None,
+            #[cfg(feature = "metrics")]
+            metrics,
         })
     }
 
+    /// Sign every message sent, adding the signature and key id headers.
+    pub fn with_signer(mut self, signer: MessageSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Encrypt sensitive payload fields of every message sent. Encryption happens before
+    /// signing, so the signature covers the encrypted values.
+    pub fn with_encryptor(mut self, encryptor: FieldEncryptor) -> Self {
+        self.encryptor = Some(encryptor);
+        self
+    }
+
+    /// Offload the payload of messages larger than the claim check's threshold to its blob
+    /// store, sending a reference in its place. Offloading happens after encryption, so
+    /// encrypted fields are stored encrypted.
+    pub fn with_claim_check(mut self, claim_check: ClaimCheck) -> Self {
+        self.claim_check = Some(claim_check);
+        self
+    }
+
+    /// Send every message as Avro instead of JSON. The message schema is registered with the
+    /// codec's schema registry on the first send.
+    pub fn with_avro(mut self, codec: AvroCodec) -> Self {
+        self.avro = Some(codec);
+        self
+    }
+
     pub async fn send_event<T: Serialize>(&self, key: String, payload: T) -> KafkaResult<()> {
-        let payload_json =
-            serde_json::to_string(&payload).map_err(|e| KafkaError::MessageSend(e.to_string()))?;
-        let record: FutureRecord<String, String> = FutureRecord::to(&self.topic)
-            .headers(build_message_headers())
+        self.send_event_to(&self.topic, key, payload).await
+    }
+
+    /// `send_event` to `topic` instead of the producer's own topic.
+    pub async fn send_event_to<T: Serialize>(
+        &self,
+        topic: &str,
+        key: String,
+        payload: T,
+    ) -> KafkaResult<()> {
+        let mut payload_json =
+            serde_json::to_vec(&payload).map_err(|e| KafkaError::MessageSend(e.to_string()))?;
+        if let Some(encryptor) = &self.encryptor {
+            payload_json = encryptor
+                .encrypt_bytes(&payload_json)
+                .map_err(|e| KafkaError::Encryption(e.to_string()))?;
+        }
+        if let Some(claim_check) = &self.claim_check {
+            payload_json = claim_check
+                .offload(&payload_json)
+                .await
+                .map_err(|e| KafkaError::ClaimCheck(e.to_string()))?;
+        }
+        let mut headers = build_message_headers();
+        // Signed as JSON, so consumers verify the decoded message whatever the wire format
+        if let Some(signer) = &self.signer {
+            let signature = signer
+                .sign(&payload_json)
+                .map_err(|e| KafkaError::MessageSend(e.to_string()))?;
+            for (key, value) in signature.headers() {
+                headers = headers.insert(Header {
+                    key,
+                    value: Some(value),
+                });
+            }
+        }
+        let mut format = WireFormat::Json;
+        if let Some(codec) = &self.avro {
+            payload_json = codec
+                .encode(&payload_json)
+                .await
+                .map_err(|e| KafkaError::Encoding(e.to_string()))?;
+            format = WireFormat::Avro;
+        }
+        let headers = headers.insert(Header {
+            key: CONTENT_TYPE_HEADER,
+            value: Some(format.content_type()),
+        });
+        let record: FutureRecord<String, Vec<u8>> = FutureRecord::to(topic)
+            .headers(headers)
             .payload(&payload_json)
             .key(&key);
 
+        #[cfg(feature = "metrics")]
+        let sent_at = std::time::Instant::now();
+
         self.producer
             .send(record, self.timeout)
             .await
             .map_err(|(err, _)| KafkaError::MessageSend(err.to_string()))?;
 
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_delivery(topic, sent_at.elapsed());
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,366 @@
+//! Declarative routing of messages to topics by instruction and event type.
+//!
+//! The routing table maps every `InstructionType` and `EventType` to a topic and to the message
+//! field used as the Kafka key. `ROUTING_TABLE` is the built-in table from `routing.yaml`;
+//! services with their own topics load one with `RoutingTable::from_yaml`.
+//!
+//! A table can also declare priority lanes: events whose `Registry::severity_of` has a lane
+//! go to the lane's topic instead, so critical alerts don't queue behind high-volume info
+//! events. Each lane carries the librdkafka settings for its own producer and consumer.
+
+use crate::message_bus::kafka::consumer::EventConsumer;
+use crate::message_bus::kafka::models::IncomingMessage;
+use crate::message_bus::kafka::producer::EventProducer;
+use crate::message_bus::models::{EventType, InstructionType, MessageBusMessage};
+use crate::models::error::{KafkaError, KafkaResult};
+use crate::notification_contract::{Registry, Severity, REGISTRY};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use tokio::sync::mpsc::Sender;
+
+pub const ROUTING_TABLE_VERSION: u32 = 1;
+
+const KEY_ROOTS: &[&str] = &["meta_data", "payload"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoutingError {
+    #[error("routing table: {0}")]
+    Invalid(String),
+    #[error("routing table yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("routing table io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("no route for {0}")]
+    NoRoute(String),
+    #[error("{message_type} message has no key field {fields:?}")]
+    MissingKey {
+        message_type: String,
+        fields: Vec<String>,
+    },
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Where messages of one type are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSpec {
+    pub topic: String,
+    /// Dotted paths into the message, tried in order. The first that holds a non-empty string
+    /// or a number is the key.
+    pub key_fields: Vec<String>,
+    /// The priority lane the topic belongs to, if any.
+    pub lane: Option<Severity>,
+}
+
+impl RouteSpec {
+    fn key(&self, message: &Value) -> Option<String> {
+        self.key_fields.iter().find_map(|field| {
+            match field
+                .split('.')
+                .try_fold(message, |value, name| value.get(name))?
+            {
+                Value::String(key) if !key.is_empty() => Some(key.clone()),
+                Value::Number(key) => Some(key.to_string()),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// The topic and key a message is sent with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route<'a> {
+    pub topic: &'a str,
+    pub key: String,
+    pub lane: Option<Severity>,
+}
+
+/// A dedicated topic for events of one severity, with its own client settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lane {
+    pub topic: String,
+    /// librdkafka settings for the lane's producer, replacing `EventProducer`'s defaults.
+    pub producer: BTreeMap<String, String>,
+    /// librdkafka settings for the lane's consumer, replacing `EventConsumer`'s defaults.
+    pub consumer: BTreeMap<String, String>,
+}
+
+impl Lane {
+    /// A producer for the lane's topic with the lane's producer settings.
+    pub fn producer(&self, brokers: &str, timeout_in_secs: u64) -> KafkaResult<EventProducer> {
+        EventProducer::new_with_overrides(brokers, timeout_in_secs, &self.topic, &self.producer)
+    }
+
+    /// A consumer subscribed to the lane's topic with the lane's consumer settings.
+    pub fn consumer<T>(
+        &self,
+        brokers: &str,
+        group_id: &str,
+        client_id: &str,
+        max_retries: u32,
+        channel: Sender<IncomingMessage<T>>,
+    ) -> KafkaResult<EventConsumer<T>>
+    where
+        for<'a> T: Deserialize<'a>,
+    {
+        EventConsumer::new_with_overrides(
+            brokers,
+            group_id,
+            client_id,
+            max_retries,
+            &[&self.topic],
+            channel,
+            &self.consumer,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutingTable {
+    pub instructions: HashMap<InstructionType, RouteSpec>,
+    pub events: HashMap<EventType, RouteSpec>,
+    pub lanes: BTreeMap<Severity, Lane>,
+}
+
+impl RoutingTable {
+    /// Parse and check a routing table, placing events in lanes by their severity in the
+    /// notification `REGISTRY`. Every `InstructionType::ALL` and `EventType::ALL` member must
+    /// have a route.
+    pub fn from_yaml(yaml: &str) -> Result<Self, RoutingError> {
+        Self::from_yaml_with_registry(yaml, &REGISTRY)
+    }
+
+    /// Parse and check a routing table, taking event severities from `registry`.
+    pub fn from_yaml_with_registry(yaml: &str, registry: &Registry) -> Result<Self, RoutingError> {
+        let raw: RawTable = serde_yaml::from_str(yaml)?;
+        if raw.version != ROUTING_TABLE_VERSION {
+            return Err(RoutingError::Invalid(format!(
+                "unsupported version {}; expected {ROUTING_TABLE_VERSION}",
+                raw.version
+            )));
+        }
+
+        let mut instructions = HashMap::new();
+        for (name, raw_route) in raw.instructions {
+            let instruction_type: InstructionType =
+                serde_json::from_value(Value::String(name.clone()))?;
+            if instruction_type.is_unknown() {
+                return Err(RoutingError::Invalid(format!(
+                    "{name:?} is not an InstructionType"
+                )));
+            }
+            instructions.insert(instruction_type, parse_route(&name, raw_route)?);
+        }
+
+        let mut lanes = BTreeMap::new();
+        for (severity, raw_lane) in raw.lanes {
+            if raw_lane.topic.trim().is_empty() {
+                return Err(RoutingError::Invalid(format!(
+                    "{severity:?} lane: empty topic"
+                )));
+            }
+            lanes.insert(
+                severity,
+                Lane {
+                    topic: raw_lane.topic,
+                    producer: raw_lane.producer,
+                    consumer: raw_lane.consumer,
+                },
+            );
+        }
+
+        let mut events = HashMap::new();
+        for (name, raw_route) in raw.events {
+            let event = EventType::try_from(name.as_str())
+                .map_err(|_| RoutingError::Invalid(format!("{name:?} is not an EventType")))?;
+            let mut route = parse_route(&name, raw_route)?;
+            if let Some((severity, lane)) = registry
+                .severity_of(event.clone())
+                .and_then(|severity| Some((severity, lanes.get(&severity)?)))
+            {
+                route.topic = lane.topic.clone();
+                route.lane = Some(severity);
+            }
+            events.insert(event, route);
+        }
+
+        let missing: Vec<String> = InstructionType::ALL
+            .iter()
+            .filter(|t| !instructions.contains_key(*t))
+            .map(|t| format!("instruction {t}"))
+            .chain(
+                EventType::ALL
+                    .iter()
+                    .filter(|e| !events.contains_key(*e))
+                    .map(|e| format!("event {}", e.as_str())),
+            )
+            .collect();
+        if !missing.is_empty() {
+            return Err(RoutingError::Invalid(format!("no route for {missing:?}")));
+        }
+
+        Ok(Self {
+            instructions,
+            events,
+            lanes,
+        })
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Self, RoutingError> {
+        Self::from_yaml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Every topic the table routes to.
+    pub fn topics(&self) -> BTreeSet<&str> {
+        self.instructions
+            .values()
+            .chain(self.events.values())
+            .map(|route| route.topic.as_str())
+            .collect()
+    }
+
+    /// The topic `message` is sent to and its key, taken from the route's key fields.
+    pub fn route(&self, message: &MessageBusMessage) -> Result<Route<'_>, RoutingError> {
+        let meta_data = &message.meta_data;
+        let (message_type, spec) = match (&meta_data.instruction_type, &meta_data.event) {
+            (Some(instruction_type), _) => (
+                format!("instruction {instruction_type}"),
+                self.instructions.get(instruction_type),
+            ),
+            (None, Some(event)) => (format!("event {event}"), self.events.get(event)),
+            (None, None) => ("message without a type".to_string(), None),
+        };
+        let spec = spec.ok_or_else(|| RoutingError::NoRoute(message_type.clone()))?;
+
+        let key =
+            spec.key(&serde_json::to_value(message)?)
+                .ok_or_else(|| RoutingError::MissingKey {
+                    message_type,
+                    fields: spec.key_fields.clone(),
+                })?;
+        Ok(Route {
+            topic: &spec.topic,
+            key,
+            lane: spec.lane,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTable {
+    version: u32,
+    #[serde(default)]
+    instructions: BTreeMap<String, RawRoute>,
+    #[serde(default)]
+    events: BTreeMap<String, RawRoute>,
+    #[serde(default)]
+    lanes: BTreeMap<Severity, RawLane>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawLane {
+    topic: String,
+    #[serde(default)]
+    producer: BTreeMap<String, String>,
+    #[serde(default)]
+    consumer: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRoute {
+    topic: String,
+    key: RawKey,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawKey {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn parse_route(name: &str, raw: RawRoute) -> Result<RouteSpec, RoutingError> {
+    if raw.topic.trim().is_empty() {
+        return Err(RoutingError::Invalid(format!("{name}: empty topic")));
+    }
+    let key_fields = match raw.key {
+        RawKey::One(field) => vec![field],
+        RawKey::Many(fields) => fields,
+    };
+    if key_fields.is_empty() {
+        return Err(RoutingError::Invalid(format!("{name}: empty key")));
+    }
+    for field in &key_fields {
+        let mut parts = field.split('.');
+        let root = parts.next().unwrap_or_default();
+        if !KEY_ROOTS.contains(&root) || parts.any(str::is_empty) || !field.contains('.') {
+            return Err(RoutingError::Invalid(format!(
+                "{name}: key {field:?} must be a meta_data. or payload. path"
+            )));
+        }
+    }
+    Ok(RouteSpec {
+        topic: raw.topic,
+        key_fields,
+        lane: None,
+    })
+}
+
+pub static ROUTING_TABLE: Lazy<RoutingTable> = Lazy::new(|| {
+    const YAML: &str = include_str!("routing.yaml");
+    RoutingTable::from_yaml(YAML).unwrap_or_else(|e| {
+        panic!("failed to load message bus routing table: {e}");
+    })
+});
+
+/// Sends each `MessageBusMessage` to the topic the routing table gives for its type, keyed by
+/// the route's key field.
+///
+/// Signing, encryption, claim checks and Avro are configured on the wrapped `EventProducer`;
+/// the topic it was created with is not used. Messages for a lane with its own producer are
+/// sent through that producer instead.
+pub struct RoutedProducer {
+    producer: EventProducer,
+    lanes: HashMap<Severity, EventProducer>,
+    table: RoutingTable,
+}
+
+impl RoutedProducer {
+    pub fn new(producer: EventProducer, table: RoutingTable) -> Self {
+        Self {
+            producer,
+            lanes: HashMap::new(),
+            table,
+        }
+    }
+
+    /// Send messages routed to the `severity` lane through `producer`, usually one created
+    /// with `Lane::producer`.
+    pub fn with_lane_producer(mut self, severity: Severity, producer: EventProducer) -> Self {
+        self.lanes.insert(severity, producer);
+        self
+    }
+
+    pub fn table(&self) -> &RoutingTable {
+        &self.table
+    }
+
+    pub async fn send(&self, message: &MessageBusMessage) -> KafkaResult<()> {
+        let route = self
+            .table
+            .route(message)
+            .map_err(|e| KafkaError::Routing(e.to_string()))?;
+        route
+            .lane
+            .and_then(|severity| self.lanes.get(&severity))
+            .unwrap_or(&self.producer)
+            .send_event_to(route.topic, route.key, message)
+            .await
+    }
+}
@@ -0,0 +1,303 @@
+//! Envelope signing of `MessageBusMessage`.
+//!
+//! The signature covers a canonical form of `meta_data` and `payload`: a JSON object holding
+//! just those two fields with every object's keys sorted, `null` members left out and no
+//! whitespace. Leaving out `null`s lets a message decoded from Avro, which has every optional
+//! field, verify against the JSON it was signed as. The signature travels in the
+//! `signature`, `signature_key_id` and `signature_algorithm` Kafka headers, so the message body
+//! is unchanged for consumers that don't verify.
+//!
+//! `SignatureVerifier` holds every key id that may still be in use, which lets a producer move
+//! to a new key while messages signed with the old one are still being consumed.
+
+use crate::message_bus::models::base::InstructionType;
+use jsonwebtoken::crypto::{sign, verify};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+pub const SIGNATURE_HEADER: &str = "signature";
+pub const KEY_ID_HEADER: &str = "signature_key_id";
+pub const ALGORITHM_HEADER: &str = "signature_algorithm";
+
+/// Instructions that move money or mint and burn tokens.
+pub const HIGH_VALUE_INSTRUCTIONS: &[InstructionType] = &[
+    InstructionType::BankPaymentRequest,
+    InstructionType::Mint,
+    InstructionType::Burn,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SigningError {
+    #[error("message can't be signed: {0}")]
+    InvalidMessage(String),
+    #[error("invalid key {key_id:?}: {reason}")]
+    InvalidKey { key_id: String, reason: String },
+    #[error("{0} messages must be signed")]
+    MissingSignature(String),
+    #[error("signature, key id and algorithm headers must be sent together")]
+    IncompleteHeaders,
+    #[error("unknown signing key {0:?}")]
+    UnknownKey(String),
+    #[error("key {key_id:?} signs with {expected}, message claims {actual}")]
+    AlgorithmMismatch {
+        key_id: String,
+        expected: SigningAlgorithm,
+        actual: String,
+    },
+    #[error("signature does not match for key {0:?}")]
+    InvalidSignature(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningAlgorithm {
+    HmacSha256,
+    Ed25519,
+}
+
+impl SigningAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::HmacSha256 => "HS256",
+            SigningAlgorithm::Ed25519 => "EdDSA",
+        }
+    }
+
+    fn jwt_algorithm(&self) -> Algorithm {
+        match self {
+            SigningAlgorithm::HmacSha256 => Algorithm::HS256,
+            SigningAlgorithm::Ed25519 => Algorithm::EdDSA,
+        }
+    }
+}
+
+impl fmt::Display for SigningAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A signature and the key that made it, as carried in the Kafka headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub key_id: String,
+    pub algorithm: SigningAlgorithm,
+    pub value: String,
+}
+
+impl Signature {
+    pub fn headers(&self) -> [(&'static str, &str); 3] {
+        [
+            (SIGNATURE_HEADER, self.value.as_str()),
+            (KEY_ID_HEADER, self.key_id.as_str()),
+            (ALGORITHM_HEADER, self.algorithm.as_str()),
+        ]
+    }
+}
+
+/// Canonical bytes of a serialized message: `{"meta_data": …, "payload": …}` with sorted keys
+/// and without `null` members.
+pub fn canonical_bytes(message: &[u8]) -> Result<Vec<u8>, SigningError> {
+    let value: Value =
+        serde_json::from_slice(message).map_err(|e| SigningError::InvalidMessage(e.to_string()))?;
+    let mut envelope = Map::new();
+    for field in ["meta_data", "payload"] {
+        let part = value
+            .get(field)
+            .ok_or_else(|| SigningError::InvalidMessage(format!("missing {field}")))?;
+        envelope.insert(field.to_string(), canonicalize(part));
+    }
+    serde_json::to_vec(&Value::Object(envelope))
+        .map_err(|e| SigningError::InvalidMessage(e.to_string()))
+}
+
+fn canonicalize(v: &Value) -> Value {
+    match v {
+        Value::Object(m) => {
+            let mut keys: Vec<&String> = m.keys().collect();
+            keys.sort();
+            let mut out = Map::new();
+            for k in keys.into_iter().filter(|k| !m[*k].is_null()) {
+                out.insert(k.clone(), canonicalize(&m[k]));
+            }
+            Value::Object(out)
+        }
+        Value::Array(a) => Value::Array(a.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Signs outgoing messages with one key.
+pub struct MessageSigner {
+    key_id: String,
+    algorithm: SigningAlgorithm,
+    key: EncodingKey,
+}
+
+impl MessageSigner {
+    pub fn hmac(key_id: impl Into<String>, secret: &[u8]) -> Self {
+        Self {
+            key_id: key_id.into(),
+            algorithm: SigningAlgorithm::HmacSha256,
+            key: EncodingKey::from_secret(secret),
+        }
+    }
+
+    /// Sign with a PEM encoded Ed25519 private key.
+    pub fn ed25519(
+        key_id: impl Into<String>,
+        private_key_pem: &[u8],
+    ) -> Result<Self, SigningError> {
+        let key_id = key_id.into();
+        let key =
+            EncodingKey::from_ed_pem(private_key_pem).map_err(|e| SigningError::InvalidKey {
+                key_id: key_id.clone(),
+                reason: e.to_string(),
+            })?;
+        Ok(Self {
+            key_id,
+            algorithm: SigningAlgorithm::Ed25519,
+            key,
+        })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Sign a serialized `MessageBusMessage`.
+    pub fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        let canonical = canonical_bytes(message)?;
+        let value = sign(&canonical, &self.key, self.algorithm.jwt_algorithm()).map_err(|e| {
+            SigningError::InvalidKey {
+                key_id: self.key_id.clone(),
+                reason: e.to_string(),
+            }
+        })?;
+        Ok(Signature {
+            key_id: self.key_id.clone(),
+            algorithm: self.algorithm,
+            value,
+        })
+    }
+}
+
+/// Verifies consumed messages against a set of trusted keys.
+///
+/// A message carrying signature headers must verify against the key they name. Unsigned
+/// messages are accepted unless their instruction type was registered with
+/// `require_signature`.
+#[derive(Default)]
+pub struct SignatureVerifier {
+    keys: HashMap<String, (SigningAlgorithm, DecodingKey)>,
+    required: HashSet<InstructionType>,
+}
+
+impl SignatureVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_hmac_key(mut self, key_id: impl Into<String>, secret: &[u8]) -> Self {
+        self.keys.insert(
+            key_id.into(),
+            (
+                SigningAlgorithm::HmacSha256,
+                DecodingKey::from_secret(secret),
+            ),
+        );
+        self
+    }
+
+    /// Trust a PEM encoded Ed25519 public key.
+    pub fn with_ed25519_key(
+        mut self,
+        key_id: impl Into<String>,
+        public_key_pem: &[u8],
+    ) -> Result<Self, SigningError> {
+        let key_id = key_id.into();
+        let key =
+            DecodingKey::from_ed_pem(public_key_pem).map_err(|e| SigningError::InvalidKey {
+                key_id: key_id.clone(),
+                reason: e.to_string(),
+            })?;
+        self.keys.insert(key_id, (SigningAlgorithm::Ed25519, key));
+        Ok(self)
+    }
+
+    /// Stop trusting a key, e.g. once everything signed with it has been consumed.
+    pub fn without_key(mut self, key_id: &str) -> Self {
+        self.keys.remove(key_id);
+        self
+    }
+
+    pub fn require_signature(mut self, instruction_type: InstructionType) -> Self {
+        self.required.insert(instruction_type);
+        self
+    }
+
+    pub fn require_signatures(self, instruction_types: &[InstructionType]) -> Self {
+        instruction_types
+            .iter()
+            .fold(self, |verifier, instruction_type| {
+                verifier.require_signature(instruction_type.clone())
+            })
+    }
+
+    /// Verify a serialized `MessageBusMessage` against the signature in its headers.
+    pub fn verify(
+        &self,
+        message: &[u8],
+        headers: &HashMap<String, String>,
+    ) -> Result<(), SigningError> {
+        let header = |name: &str| headers.get(name).filter(|value| !value.is_empty());
+        let (value, key_id, algorithm) = match (
+            header(SIGNATURE_HEADER),
+            header(KEY_ID_HEADER),
+            header(ALGORITHM_HEADER),
+        ) {
+            (Some(value), Some(key_id), Some(algorithm)) => (value, key_id, algorithm),
+            (None, None, None) => return self.check_unsigned(message),
+            _ => return Err(SigningError::IncompleteHeaders),
+        };
+
+        let (expected, key) = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| SigningError::UnknownKey(key_id.clone()))?;
+        if expected.as_str() != algorithm {
+            return Err(SigningError::AlgorithmMismatch {
+                key_id: key_id.clone(),
+                expected: *expected,
+                actual: algorithm.clone(),
+            });
+        }
+
+        let canonical = canonical_bytes(message)?;
+        match verify(value, &canonical, key, expected.jwt_algorithm()) {
+            Ok(true) => Ok(()),
+            _ => Err(SigningError::InvalidSignature(key_id.clone())),
+        }
+    }
+
+    // A body whose instruction type can't be read is rejected: it could be one that must be
+    // signed
+    fn check_unsigned(&self, message: &[u8]) -> Result<(), SigningError> {
+        let value: Value = serde_json::from_slice(message)
+            .map_err(|e| SigningError::InvalidMessage(e.to_string()))?;
+        let instruction_type = match &value["meta_data"]["instruction_type"] {
+            Value::Null => None,
+            instruction_type => Some(
+                serde_json::from_value::<InstructionType>(instruction_type.clone())
+                    .map_err(|e| SigningError::InvalidMessage(e.to_string()))?,
+            ),
+        };
+        match instruction_type {
+            Some(instruction_type) if self.required.contains(&instruction_type) => {
+                Err(SigningError::MissingSignature(instruction_type.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -3,6 +3,6 @@ pub mod models;
 
 // Re-export the new models for convenience
 pub use models::{
-    EventType, InstructionType, MessageBusMessage, MetaData, Payload, TransactionType,
-    ValidationError,
+    EventType, InstructionType, MessageBusMessage, MetaData, Payload, PayloadKind,
+    TransactionType, ValidationError,
 };
@@ -0,0 +1,480 @@
+//! Avro schema and binary encoding of `MessageBusMessage`.
+//!
+//! The Avro schema is generated from the envelope JSON Schema in `json_schema`, so it follows
+//! the Rust types the same way. Record fields are in name order, so the schema doesn't change
+//! with the order the JSON Schema generator lists properties in. `payload` is a union of every
+//! payload record and `string` for `Payload::Raw`. Instruction and event types are `string`s
+//! rather than Avro enums, so types added after a consumer was built can still be written and
+//! read. Fields typed as arbitrary JSON, such as `CustomerNotificationPayload.data`, are
+//! `JsonValue` records holding the JSON as text. The committed copy is
+//! `schemas/MessageBusMessage.avsc`.
+//!
+//! Messages are converted from and to their JSON form, so everything that works on JSON
+//! (upcasting, strict decoding, field encryption) applies to Avro messages unchanged.
+
+use super::base::{EventType, InstructionType};
+use super::json_schema::envelope_schema;
+use super::message::PayloadKind;
+use apache_avro::schema::{Name, ResolvedSchema, Schema};
+use apache_avro::types::Value as AvroValue;
+use once_cell::sync::Lazy;
+use serde_json::{json, Map, Number, Value};
+use std::collections::HashSet;
+
+pub const AVRO_NAMESPACE: &str = "com.mykobo.bus";
+pub const AVRO_SCHEMA_FILE_EXTENSION: &str = ".avsc";
+
+// Named record carrying arbitrary JSON as text
+const JSON_RECORD: &str = "JsonValue";
+const JSON_FIELD: &str = "json";
+
+// Enums that have an `Unknown` variant for values newer than this crate
+const OPEN_ENUMS: &[&str] = &["InstructionType", "EventType"];
+
+/// The Avro schema of `MessageBusMessage` for this version of the crate.
+pub static AVRO_SCHEMA: Lazy<Schema> =
+    Lazy::new(|| Schema::parse(&avro_schema()).expect("generated Avro schema is valid"));
+
+static AVRO_NAMES: Lazy<ResolvedSchema<'static>> =
+    Lazy::new(|| ResolvedSchema::try_from(&*AVRO_SCHEMA).expect("generated Avro schema resolves"));
+
+static AVRO_CANONICAL_FORM: Lazy<String> = Lazy::new(|| AVRO_SCHEMA.canonical_form());
+
+#[derive(Debug, thiserror::Error)]
+pub enum AvroError {
+    #[error("{path}: {reason}")]
+    Encode { path: String, reason: String },
+    #[error("Avro datum does not match the message schema: {0}")]
+    Decode(String),
+    #[error("Avro: {0}")]
+    Avro(String),
+}
+
+/// Avro schema JSON of `MessageBusMessage`, generated from its JSON Schema.
+pub fn avro_schema() -> Value {
+    let envelope = envelope_schema();
+    let mut converter = Converter {
+        definitions: envelope["$defs"].as_object().cloned().unwrap_or_default(),
+        defined: HashSet::new(),
+    };
+    converter.convert(&envelope, &format!("{AVRO_NAMESPACE}.MessageBusMessage"))
+}
+
+/// Encode the JSON form of a `MessageBusMessage` as an Avro datum of `AVRO_SCHEMA`.
+pub fn encode_datum(message: &Value) -> Result<Vec<u8>, AvroError> {
+    let encoder = Encoder {
+        payload_record: payload_record(message),
+    };
+    let value = encoder.encode(message, &AVRO_SCHEMA, "message")?;
+    apache_avro::to_avro_datum(&AVRO_SCHEMA, value).map_err(|e| AvroError::Avro(e.to_string()))
+}
+
+/// Decode an Avro datum written with `writer` into the JSON form of a `MessageBusMessage`.
+///
+/// A datum written with an older or newer schema is resolved against `AVRO_SCHEMA` first.
+pub fn decode_datum(mut datum: &[u8], writer: &Schema) -> Result<Value, AvroError> {
+    let reader = (writer.canonical_form() != *AVRO_CANONICAL_FORM).then_some(&*AVRO_SCHEMA);
+    let value = apache_avro::from_avro_datum(writer, &mut datum, reader)
+        .map_err(|e| AvroError::Avro(e.to_string()))?;
+    decode(value, &AVRO_SCHEMA)
+}
+
+// Record name of the payload struct paired with the message's instruction or event
+fn payload_record(message: &Value) -> Option<&'static str> {
+    let meta_data = message.get("meta_data")?;
+    let kind = match meta_data.get("instruction_type").filter(|t| !t.is_null()) {
+        Some(instruction_type) => {
+            let instruction_type: InstructionType =
+                serde_json::from_value(instruction_type.clone()).ok()?;
+            PayloadKind::for_instruction(&instruction_type)
+        }
+        None => {
+            let event: EventType = serde_json::from_value(meta_data.get("event")?.clone()).ok()?;
+            PayloadKind::for_event(&event)
+        }
+    };
+    kind.map(|kind| kind.type_name())
+}
+
+struct Converter {
+    definitions: Map<String, Value>,
+    // Definitions already emitted as named Avro types
+    defined: HashSet<String>,
+}
+
+impl Converter {
+    fn convert(&mut self, schema: &Value, name: &str) -> Value {
+        let Some(object) = schema.as_object() else {
+            return self.json_value();
+        };
+
+        if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+            let definition = reference.trim_start_matches("#/$defs/");
+            if OPEN_ENUMS.contains(&definition) {
+                return json!("string");
+            }
+            if self.defined.contains(definition) {
+                return json!(definition);
+            }
+            let schema = self
+                .definitions
+                .get(definition)
+                .cloned()
+                .unwrap_or(Value::Bool(true));
+            let converted = self.convert(&schema, definition);
+            if converted.get("name").is_some() {
+                self.defined.insert(definition.to_string());
+            }
+            return converted;
+        }
+
+        match object.get("type") {
+            Some(Value::Array(types)) => {
+                let branches: Vec<Value> = types
+                    .iter()
+                    .map(|t| {
+                        let mut branch = object.clone();
+                        branch.insert("type".to_string(), t.clone());
+                        Value::Object(branch)
+                    })
+                    .collect();
+                self.union(&branches, name)
+            }
+            Some(Value::String(t)) => match t.as_str() {
+                "object" => match (object.get("properties"), object.get("additionalProperties")) {
+                    (Some(_), _) => self.record(object, name),
+                    (None, Some(values)) => {
+                        json!({ "type": "map", "values": self.convert(values, &format!("{name}Value")) })
+                    }
+                    (None, None) => self.json_value(),
+                },
+                "string" => match object.get("enum") {
+                    Some(symbols) => {
+                        let mut avro_enum = json!({ "type": "enum", "name": name });
+                        if let Some(doc) = object.get("description") {
+                            avro_enum["doc"] = doc.clone();
+                        }
+                        avro_enum["symbols"] = symbols.clone();
+                        avro_enum
+                    }
+                    None => json!("string"),
+                },
+                "array" => {
+                    let items = object.get("items").unwrap_or(&Value::Bool(true));
+                    json!({ "type": "array", "items": self.convert(items, &format!("{name}Item")) })
+                }
+                "integer" => json!("long"),
+                "number" => json!("double"),
+                "boolean" => json!("boolean"),
+                "null" => json!("null"),
+                _ => self.json_value(),
+            },
+            _ => match object.get("anyOf").or_else(|| object.get("oneOf")) {
+                Some(Value::Array(branches)) => self.union(branches, name),
+                _ => self.json_value(),
+            },
+        }
+    }
+
+    fn record(&mut self, object: &Map<String, Value>, name: &str) -> Value {
+        let properties = object
+            .get("properties")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        let mut properties: Vec<(&String, &Value)> = properties.iter().collect();
+        properties.sort_by_key(|(field, _)| *field);
+        let fields: Vec<Value> = properties
+            .into_iter()
+            .map(|(field, schema)| {
+                let field_type = self.convert(schema, &format!("{name}{}", pascal_case(field)));
+                let nullable = field_type
+                    .as_array()
+                    .is_some_and(|variants| variants.first() == Some(&json!("null")));
+                let mut avro_field = json!({ "name": field, "type": field_type });
+                if nullable {
+                    avro_field["default"] = Value::Null;
+                }
+                if let Some(doc) = schema.get("description") {
+                    avro_field["doc"] = doc.clone();
+                }
+                avro_field
+            })
+            .collect();
+
+        let mut record = json!({ "type": "record", "name": name });
+        if let Some(doc) = object.get("description") {
+            record["doc"] = doc.clone();
+        }
+        record["fields"] = Value::Array(fields);
+        record
+    }
+
+    // Avro unions can't nest and list `null` first so it can be the field default
+    fn union(&mut self, branches: &[Value], name: &str) -> Value {
+        let mut variants = Vec::new();
+        let mut nullable = false;
+        for (i, branch) in branches.iter().enumerate() {
+            if branch.get("type") == Some(&json!("null")) {
+                nullable = true;
+                continue;
+            }
+            // Internally tagged enum variants are named after their tag
+            let branch_name = branch
+                .pointer("/properties/type/const")
+                .and_then(Value::as_str)
+                .map(|tag| format!("{name}{}", pascal_case(tag)))
+                .unwrap_or_else(|| format!("{name}{i}"));
+            match self.convert(branch, &branch_name) {
+                Value::Array(nested) => variants.extend(nested),
+                variant => variants.push(variant),
+            }
+        }
+        if nullable {
+            variants.retain(|variant| variant != "null");
+            variants.insert(0, json!("null"));
+        }
+        match variants.len() {
+            1 => variants.remove(0),
+            _ => Value::Array(variants),
+        }
+    }
+
+    fn json_value(&mut self) -> Value {
+        if !self.defined.insert(JSON_RECORD.to_string()) {
+            return json!(JSON_RECORD);
+        }
+        json!({
+            "type": "record",
+            "name": JSON_RECORD,
+            "doc": "Arbitrary JSON, as text.",
+            "fields": [{ "name": JSON_FIELD, "type": "string" }]
+        })
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn named<'a>(schema: &'a Schema, path: &str) -> Result<&'a Schema, AvroError> {
+    match schema {
+        Schema::Ref { name } => lookup(name).ok_or_else(|| AvroError::Encode {
+            path: path.to_string(),
+            reason: format!("unknown Avro type {name}"),
+        }),
+        schema => Ok(schema),
+    }
+}
+
+fn lookup(name: &Name) -> Option<&'static Schema> {
+    AVRO_NAMES.get_names().get(name).copied()
+}
+
+fn schema_name(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Ref { name } => Some(name.name.as_str()),
+        Schema::Record(record) => Some(record.name.name.as_str()),
+        _ => None,
+    }
+}
+
+fn mismatch(path: &str, expected: &str) -> AvroError {
+    AvroError::Encode {
+        path: path.to_string(),
+        reason: format!("expected {expected}"),
+    }
+}
+
+struct Encoder {
+    // Payload union branch to use, so structurally identical payloads keep their own record
+    payload_record: Option<&'static str>,
+}
+
+impl Encoder {
+    fn encode(&self, value: &Value, schema: &Schema, path: &str) -> Result<AvroValue, AvroError> {
+        match named(schema, path)? {
+            Schema::Null => match value {
+                Value::Null => Ok(AvroValue::Null),
+                _ => Err(mismatch(path, "null")),
+            },
+            Schema::Boolean => value
+                .as_bool()
+                .map(AvroValue::Boolean)
+                .ok_or_else(|| mismatch(path, "a boolean")),
+            Schema::Long => value
+                .as_i64()
+                .map(AvroValue::Long)
+                .ok_or_else(|| mismatch(path, "an integer")),
+            Schema::Double => value
+                .as_f64()
+                .map(AvroValue::Double)
+                .ok_or_else(|| mismatch(path, "a number")),
+            Schema::String => value
+                .as_str()
+                .map(|s| AvroValue::String(s.to_string()))
+                .ok_or_else(|| mismatch(path, "a string")),
+            Schema::Enum(enum_schema) => {
+                let symbol = value.as_str().ok_or_else(|| mismatch(path, "a string"))?;
+                let index = enum_schema
+                    .symbols
+                    .iter()
+                    .position(|s| s == symbol)
+                    .ok_or_else(|| AvroError::Encode {
+                        path: path.to_string(),
+                        reason: format!("{symbol:?} is not a {} symbol", enum_schema.name.name),
+                    })?;
+                Ok(AvroValue::Enum(index as u32, symbol.to_string()))
+            }
+            Schema::Array(array) => value
+                .as_array()
+                .ok_or_else(|| mismatch(path, "an array"))?
+                .iter()
+                .enumerate()
+                .map(|(i, item)| self.encode(item, &array.items, &format!("{path}[{i}]")))
+                .collect::<Result<_, _>>()
+                .map(AvroValue::Array),
+            Schema::Map(map) => value
+                .as_object()
+                .ok_or_else(|| mismatch(path, "an object"))?
+                .iter()
+                .map(|(key, item)| {
+                    self.encode(item, &map.types, &format!("{path}.{key}"))
+                        .map(|item| (key.clone(), item))
+                })
+                .collect::<Result<_, _>>()
+                .map(AvroValue::Map),
+            Schema::Record(record) if record.name.name == JSON_RECORD => Ok(AvroValue::Record(
+                vec![(JSON_FIELD.to_string(), AvroValue::String(value.to_string()))],
+            )),
+            Schema::Record(record) => {
+                let object = value
+                    .as_object()
+                    .ok_or_else(|| mismatch(path, "an object"))?;
+                if let Some(extra) = object.keys().find(|key| !record.lookup.contains_key(*key)) {
+                    return Err(AvroError::Encode {
+                        path: format!("{path}.{extra}"),
+                        reason: format!("not a field of {}", record.name.name),
+                    });
+                }
+                record
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let item = object.get(&field.name).unwrap_or(&Value::Null);
+                        self.encode(item, &field.schema, &format!("{path}.{}", field.name))
+                            .map(|item| (field.name.clone(), item))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(AvroValue::Record)
+            }
+            Schema::Union(union) => {
+                let variants = union.variants();
+                let union_value = |i: usize, v: AvroValue| AvroValue::Union(i as u32, Box::new(v));
+
+                if value.is_null() {
+                    if let Some(i) = variants.iter().position(|s| matches!(s, Schema::Null)) {
+                        return Ok(union_value(i, AvroValue::Null));
+                    }
+                }
+                let preferred =
+                    self.payload_record
+                        .filter(|_| value.is_object())
+                        .and_then(|record| {
+                            variants.iter().position(|s| schema_name(s) == Some(record))
+                        });
+                if let Some(i) = preferred {
+                    return self
+                        .encode(value, &variants[i], path)
+                        .map(|v| union_value(i, v));
+                }
+
+                let mut error = mismatch(path, "a value matching one of the union's types");
+                for (i, variant) in variants.iter().enumerate() {
+                    if matches!(variant, Schema::Null) {
+                        continue;
+                    }
+                    match self.encode(value, variant, path) {
+                        Ok(v) => return Ok(union_value(i, v)),
+                        Err(e) => error = e,
+                    }
+                }
+                Err(error)
+            }
+            other => Err(AvroError::Encode {
+                path: path.to_string(),
+                reason: format!("unsupported Avro type {other:?}"),
+            }),
+        }
+    }
+}
+
+fn decode(value: AvroValue, schema: &Schema) -> Result<Value, AvroError> {
+    let schema = match schema {
+        Schema::Ref { name } => {
+            lookup(name).ok_or_else(|| AvroError::Decode(format!("unknown Avro type {name}")))?
+        }
+        schema => schema,
+    };
+    let mismatch = |value: &AvroValue| AvroError::Decode(format!("unexpected value {value:?}"));
+
+    match (value, schema) {
+        (AvroValue::Union(i, value), Schema::Union(union)) => {
+            let variant = union
+                .variants()
+                .get(i as usize)
+                .ok_or_else(|| AvroError::Decode(format!("union index {i} out of range")))?;
+            decode(*value, variant)
+        }
+        (AvroValue::Record(fields), Schema::Record(record)) if record.name.name == JSON_RECORD => {
+            match fields.into_iter().next() {
+                Some((_, AvroValue::String(json))) => {
+                    serde_json::from_str(&json).map_err(|e| AvroError::Decode(e.to_string()))
+                }
+                other => Err(AvroError::Decode(format!(
+                    "unexpected JSON value {other:?}"
+                ))),
+            }
+        }
+        (AvroValue::Record(fields), Schema::Record(record)) => {
+            let mut object = Map::new();
+            for (name, value) in fields {
+                let field = record
+                    .lookup
+                    .get(&name)
+                    .and_then(|i| record.fields.get(*i))
+                    .ok_or_else(|| AvroError::Decode(format!("unknown field {name}")))?;
+                object.insert(name, decode(value, &field.schema)?);
+            }
+            Ok(Value::Object(object))
+        }
+        (AvroValue::Array(items), Schema::Array(array)) => items
+            .into_iter()
+            .map(|item| decode(item, &array.items))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        (AvroValue::Map(items), Schema::Map(map)) => items
+            .into_iter()
+            .map(|(key, item)| decode(item, &map.types).map(|item| (key, item)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        (AvroValue::Null, _) => Ok(Value::Null),
+        (AvroValue::Boolean(b), _) => Ok(Value::Bool(b)),
+        (AvroValue::Int(n), _) => Ok(Value::from(n)),
+        (AvroValue::Long(n), _) => Ok(Value::from(n)),
+        (AvroValue::Float(n), _) => {
+            Ok(Number::from_f64(n.into()).map_or(Value::Null, Value::Number))
+        }
+        (AvroValue::Double(n), _) => Ok(Number::from_f64(n).map_or(Value::Null, Value::Number)),
+        (AvroValue::String(s), _) | (AvroValue::Enum(_, s), _) => Ok(Value::String(s)),
+        (value, _) => Err(mismatch(&value)),
+    }
+}
@@ -1,9 +1,13 @@
+use schemars::{JsonSchema, Schema};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fmt;
+use std::str::FromStr;
 
 /// Enum for message instruction types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[schemars(transform = known_names_only)]
 pub enum InstructionType {
     Payment, // ledger payment instruction
     StatusUpdate,
@@ -14,6 +18,29 @@ pub enum InstructionType {
     UpdateProfile,      // profile update instruction
     Mint,               // mint instruction - to convert FIAT to Crypto asset
     Burn,               // burn instruction - to convert Crypto asset to FIAT
+    /// A type added by a newer producer, holding the name as sent.
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Unknown(String),
+}
+
+impl InstructionType {
+    pub const ALL: &'static [InstructionType] = &[
+        InstructionType::Payment,
+        InstructionType::StatusUpdate,
+        InstructionType::Correction,
+        InstructionType::Transaction,
+        InstructionType::BankPaymentRequest,
+        InstructionType::ChainPayment,
+        InstructionType::UpdateProfile,
+        InstructionType::Mint,
+        InstructionType::Burn,
+    ];
+
+    /// Whether this type was not recognised when the message was deserialized.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, InstructionType::Unknown(_))
+    }
 }
 
 impl fmt::Display for InstructionType {
@@ -29,8 +56,24 @@ impl fmt::Display for InstructionType {
     }
 }
 
+// The untagged `Unknown` variant makes the derived schema wrap the known names in a single
+// `anyOf` branch. Unwrap it so the published schema stays a plain string enum.
+fn known_names_only(schema: &mut Schema) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+    if let Some(Value::Array(branches)) = object.get("anyOf") {
+        if let [Value::Object(known)] = branches.as_slice() {
+            let mut flattened = known.clone();
+            object.remove("anyOf");
+            flattened.append(object);
+            *object = flattened;
+        }
+    }
+}
+
 /// Enum for transaction types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TransactionType {
     Deposit,
@@ -52,7 +95,7 @@ impl fmt::Display for TransactionType {
 }
 
 /// Enum for payment direction
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[derive(Default)]
 pub enum PaymentDirection {
@@ -75,17 +118,53 @@ impl fmt::Display for PaymentDirection {
     }
 }
 
-impl From<String> for PaymentDirection {
-    fn from(value: String) -> Self {
+impl FromStr for PaymentDirection {
+    type Err = PayloadParseError;
+
+    /// Parse a direction case-insensitively, with or without surrounding quotes.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         let normalized = format!("\"{}\"", value.trim_matches('"').to_uppercase());
-        serde_json::from_str(&normalized)
+        serde_json::from_str(&normalized).map_err(|source| PayloadParseError::Json {
+            class_name: "PaymentDirection",
+            source,
+        })
+    }
+}
+
+impl TryFrom<&str> for PaymentDirection {
+    type Error = PayloadParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl PaymentDirection {
+    /// Parse a direction, panicking if it is unknown. `From<String>` calls this.
+    #[deprecated(
+        note = "panics on an unknown direction, use `str::parse` or `TryFrom<&str>` instead"
+    )]
+    pub fn from_string(value: String) -> Self {
+        value
+            .parse()
             .expect("Failed to deserialize PaymentDirection from String")
     }
 }
 
+/// Panics on an unknown direction. Prefer `str::parse` or `TryFrom<&str>`.
+impl From<String> for PaymentDirection {
+    #[allow(deprecated)]
+    fn from(value: String) -> Self {
+        Self::from_string(value)
+    }
+}
+
 /// Enum for event types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[schemars(transform = known_names_only)]
 pub enum EventType {
     BankPaymentBalanceInsufficientAlert,
     BankPaymentExecutionFailedAlert,
@@ -136,6 +215,10 @@ pub enum EventType {
     OnchainPaymentSentInfo,
     TransactionApprovedInfo,
     TransactionFulfilledInfo,
+    /// An event added by a newer producer, holding the name as sent.
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Unknown(String),
 }
 
 impl fmt::Display for EventType {
@@ -203,7 +286,8 @@ impl EventType {
         EventType::TransactionFulfilledInfo,
     ];
 
-    pub fn as_str(&self) -> &'static str {
+    /// The serialized name, which for `EventType::Unknown` is the name it was sent with.
+    pub fn as_str(&self) -> &str {
         match self {
             Self::BankPaymentBalanceInsufficientAlert => "BANK_PAYMENT_BALANCE_INSUFFICIENT_ALERT",
             Self::BankPaymentExecutionFailedAlert => "BANK_PAYMENT_EXECUTION_FAILED_ALERT",
@@ -253,18 +337,25 @@ impl EventType {
             Self::OnchainPaymentSentInfo => "ONCHAIN_PAYMENT_SENT_INFO",
             Self::TransactionApprovedInfo => "TRANSACTION_APPROVED_INFO",
             Self::TransactionFulfilledInfo => "TRANSACTION_FULFILLED_INFO",
+            Self::Unknown(name) => name,
         }
     }
+
+    /// Whether this event was not recognised when the message was deserialized.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, EventType::Unknown(_))
+    }
 }
 
 impl TryFrom<&str> for EventType {
     type Error = String;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        Self::ALL.iter().find(|e| e.as_str() == s).copied().ok_or_else(|| s.to_string())
+        Self::ALL.iter().find(|e| e.as_str() == s).cloned().ok_or_else(|| s.to_string())
     }
 }
 
-/// Validation error for required fields
+/// Validation error listing every failing field. Missing required fields are given by name,
+/// other violations as `"<field>: <reason>"`.
 #[derive(Debug, Clone, thiserror::Error)]
 pub struct ValidationError {
     pub class_name: String,
@@ -282,6 +373,84 @@ impl fmt::Display for ValidationError {
     }
 }
 
+/// Error returned when parsing a payload from a JSON string with `str::parse` or
+/// `TryFrom<&str>`.
+///
+/// Use these rather than the `From<String>` impls on payloads and `PaymentDirection`, which
+/// panic on malformed input and are only kept for existing callers.
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadParseError {
+    #[error("failed to deserialize {class_name}: {source}")]
+    Json {
+        class_name: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+/// Implement `FromStr` and `TryFrom<&str>` for payloads, deserializing the JSON and then
+/// running the payload's `validate()`.
+///
+/// Also implements the older `From<String>`, which panics on malformed JSON and skips
+/// `validate()`, through a deprecated `from_json_string` constructor.
+macro_rules! impl_payload_parsing {
+    ($($payload:ident),+ $(,)?) => {
+        $(
+            impl $payload {
+                #[doc = concat!(
+                    "Deserialize a `", stringify!($payload), "` from JSON, panicking if it is ",
+                    "malformed. `From<String>` calls this."
+                )]
+                #[deprecated(
+                    note = "panics on malformed JSON, use `str::parse` or `TryFrom<&str>` instead"
+                )]
+                pub fn from_json_string(value: String) -> Self {
+                    serde_json::from_str(&value).expect(concat!(
+                        "Failed to deserialize ",
+                        stringify!($payload),
+                        " from String"
+                    ))
+                }
+            }
+
+            /// Panics on malformed JSON and doesn't run `validate()`. Prefer `str::parse` or
+            /// `TryFrom<&str>`, which return a `PayloadParseError`.
+            impl From<String> for $payload {
+                #[allow(deprecated)]
+                fn from(value: String) -> Self {
+                    Self::from_json_string(value)
+                }
+            }
+
+            impl std::str::FromStr for $payload {
+                type Err = $crate::message_bus::models::base::PayloadParseError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    let payload: $payload = serde_json::from_str(value).map_err(|source| {
+                        $crate::message_bus::models::base::PayloadParseError::Json {
+                            class_name: stringify!($payload),
+                            source,
+                        }
+                    })?;
+                    payload.validate()?;
+                    Ok(payload)
+                }
+            }
+
+            impl TryFrom<&str> for $payload {
+                type Error = $crate::message_bus::models::base::PayloadParseError;
+
+                fn try_from(value: &str) -> Result<Self, Self::Error> {
+                    value.parse()
+                }
+            }
+        )+
+    };
+}
+pub(crate) use impl_payload_parsing;
+
 /// Validate that required string fields are not empty or whitespace only
 pub fn validate_required_fields(
     fields: &[(&str, &str)],
@@ -0,0 +1,313 @@
+//! Typed construction of `MessageBusMessage`.
+//!
+//! `MessageBusMessage::instruction` takes the `InstructionType` from the payload type, so an
+//! instruction can't be sent with the wrong type. Event payloads such as
+//! `CustomerNotificationPayload` serve several events, so `MessageBusMessage::event` takes the
+//! `EventType` explicitly and `build` checks it against the payload.
+
+use super::base::{EventType, InstructionType, ValidationError};
+use super::event::*;
+use super::instruction::*;
+use super::message::{MessageBusMessage, MetaData, Payload, PayloadKind};
+use super::notification::{CustomerNotificationPayload, PlatformNotificationPayload};
+use crate::notification::IdempotencyKey;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use uuid::Uuid;
+
+/// A typed payload that maps to exactly one `Payload` variant.
+pub trait TypedPayload: Into<Payload> {
+    const KIND: PayloadKind;
+}
+
+/// An instruction payload, carrying the one `InstructionType` it is sent with.
+pub trait InstructionPayload: TypedPayload {
+    const INSTRUCTION_TYPE: InstructionType;
+}
+
+/// An event payload. The event is chosen by the caller and checked against `KIND`.
+pub trait EventPayload: TypedPayload {}
+
+macro_rules! typed_payload {
+    ($($payload:ident => $variant:ident),+ $(,)?) => {
+        $(
+            impl From<$payload> for Payload {
+                fn from(payload: $payload) -> Self {
+                    Payload::$variant(payload)
+                }
+            }
+
+            impl TypedPayload for $payload {
+                const KIND: PayloadKind = PayloadKind::$variant;
+            }
+        )+
+    };
+}
+
+macro_rules! instruction_payload {
+    ($($payload:ident => $instruction_type:ident),+ $(,)?) => {
+        $(
+            impl InstructionPayload for $payload {
+                const INSTRUCTION_TYPE: InstructionType = InstructionType::$instruction_type;
+            }
+        )+
+    };
+}
+
+typed_payload! {
+    PaymentPayload => Payment,
+    StatusUpdatePayload => StatusUpdate,
+    CorrectionPayload => Correction,
+    TransactionPayload => Transaction,
+    BankPaymentRequestPayload => BankPaymentRequest,
+    ChainPaymentPayload => ChainPayment,
+    UpdateProfilePayload => UpdateProfile,
+    MintPayload => Mint,
+    BurnPayload => Burn,
+    NewTransactionEventPayload => NewTransaction,
+    TransactionStatusEventPayload => TransactionStatus,
+    PaymentEventPayload => PaymentEvent,
+    BankPaymentEventPayload => BankPayment,
+    ProfileEventPayload => Profile,
+    NewUserEventPayload => NewUser,
+    KycEventPayload => Kyc,
+    PasswordResetEventPayload => PasswordReset,
+    VerificationRequestedEventPayload => VerificationRequested,
+    AddressOnboardedEventPayload => AddressOnboarded,
+    CustomerNotificationPayload => CustomerNotification,
+    PlatformNotificationPayload => PlatformNotification,
+}
+
+instruction_payload! {
+    PaymentPayload => Payment,
+    StatusUpdatePayload => StatusUpdate,
+    CorrectionPayload => Correction,
+    TransactionPayload => Transaction,
+    BankPaymentRequestPayload => BankPaymentRequest,
+    ChainPaymentPayload => ChainPayment,
+    UpdateProfilePayload => UpdateProfile,
+    MintPayload => Mint,
+    BurnPayload => Burn,
+}
+
+impl EventPayload for NewTransactionEventPayload {}
+impl EventPayload for TransactionStatusEventPayload {}
+impl EventPayload for PaymentEventPayload {}
+impl EventPayload for BankPaymentEventPayload {}
+impl EventPayload for ProfileEventPayload {}
+impl EventPayload for NewUserEventPayload {}
+impl EventPayload for KycEventPayload {}
+impl EventPayload for PasswordResetEventPayload {}
+impl EventPayload for VerificationRequestedEventPayload {}
+impl EventPayload for AddressOnboardedEventPayload {}
+impl EventPayload for CustomerNotificationPayload {}
+impl EventPayload for PlatformNotificationPayload {}
+
+#[derive(Debug, Clone)]
+enum MessageType {
+    Instruction(InstructionType),
+    Event(EventType),
+}
+
+#[derive(Debug, Clone)]
+enum KeyStrategy {
+    Random,
+    Explicit(String),
+    Subject {
+        producer: String,
+        subject_id: String,
+    },
+}
+
+/// Builder returned by `MessageBusMessage::instruction` and `MessageBusMessage::event`.
+///
+/// `created_at` defaults to now and `idempotency_key` to a random UUID. `source` and `token`
+/// must be set; `build` reports them as missing otherwise.
+#[derive(Debug, Clone)]
+#[must_use = "call build() to create the message"]
+pub struct MessageBuilder {
+    message_type: MessageType,
+    payload: Payload,
+    source: String,
+    token: String,
+    created_at: DateTime<Utc>,
+    idempotency_key: KeyStrategy,
+    ip_address: Option<String>,
+    schema_version: Option<u32>,
+    parent: Option<MetaData>,
+    correlation_id: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    ttl: Option<Duration>,
+    tenant: Option<String>,
+}
+
+impl MessageBuilder {
+    fn new(message_type: MessageType, payload: Payload) -> Self {
+        Self {
+            message_type,
+            payload,
+            source: String::new(),
+            token: String::new(),
+            created_at: Utc::now(),
+            idempotency_key: KeyStrategy::Random,
+            ip_address: None,
+            schema_version: None,
+            parent: None,
+            correlation_id: None,
+            expires_at: None,
+            ttl: None,
+            tenant: None,
+        }
+    }
+
+    /// Start building an instruction around an already decoded payload, e.g. one read from a
+    /// file. `build` fails if `instruction_type` does not carry the payload.
+    pub fn for_instruction(instruction_type: InstructionType, payload: Payload) -> Self {
+        Self::new(MessageType::Instruction(instruction_type), payload)
+    }
+
+    /// Start building an event around an already decoded payload. `build` fails if `event`
+    /// does not carry the payload.
+    pub fn for_event(event: EventType, payload: Payload) -> Self {
+        Self::new(MessageType::Event(event), payload)
+    }
+
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = token.into();
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    pub fn ip_address(mut self, ip_address: impl Into<String>) -> Self {
+        self.ip_address = Some(ip_address.into());
+        self
+    }
+
+    pub fn schema_version(mut self, schema_version: u32) -> Self {
+        self.schema_version = Some(schema_version);
+        self
+    }
+
+    pub fn correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Link the message to the one that caused it. See `MetaData::caused_by`.
+    pub fn caused_by(mut self, parent: &MetaData) -> Self {
+        self.parent = Some(parent.clone());
+        self
+    }
+
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self.ttl = None;
+        self
+    }
+
+    /// Expire the message `ttl` after `created_at`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self.expires_at = None;
+        self
+    }
+
+    pub fn tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    pub fn idempotency_key(mut self, idempotency_key: impl Into<String>) -> Self {
+        self.idempotency_key = KeyStrategy::Explicit(idempotency_key.into());
+        self
+    }
+
+    /// Derive the key from the message type and a subject, e.g.
+    /// `ledger:deposit_completed:TX-1`, so retries of the same message share a key. For
+    /// time-bucketed keys pass `IdempotencyKey::for_bucket` to `idempotency_key`.
+    pub fn idempotency_key_for(
+        mut self,
+        producer: impl Into<String>,
+        subject_id: impl Into<String>,
+    ) -> Self {
+        self.idempotency_key = KeyStrategy::Subject {
+            producer: producer.into(),
+            subject_id: subject_id.into(),
+        };
+        self
+    }
+
+    pub fn build(self) -> Result<MessageBusMessage, ValidationError> {
+        let idempotency_key = match self.idempotency_key {
+            KeyStrategy::Random => Uuid::new_v4().to_string(),
+            KeyStrategy::Explicit(key) => key,
+            KeyStrategy::Subject {
+                producer,
+                subject_id,
+            } => match &self.message_type {
+                MessageType::Instruction(instruction_type) => IdempotencyKey::for_instruction(
+                    &producer,
+                    instruction_type.clone(),
+                    &subject_id,
+                ),
+                MessageType::Event(event) => {
+                    IdempotencyKey::for_event(&producer, event.clone(), &subject_id)
+                }
+            },
+        };
+
+        let (instruction_type, event) = match self.message_type {
+            MessageType::Instruction(instruction_type) => (Some(instruction_type), None),
+            MessageType::Event(event) => (None, Some(event)),
+        };
+
+        let mut meta_data = MetaData::new(
+            self.source,
+            self.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.token,
+            idempotency_key,
+            instruction_type,
+            event,
+            self.ip_address,
+        )?;
+        meta_data.schema_version = self.schema_version;
+        meta_data.tenant = self.tenant;
+        if let Some(parent) = &self.parent {
+            meta_data = meta_data.caused_by(parent);
+        }
+        if let Some(correlation_id) = self.correlation_id {
+            meta_data.correlation_id = Some(correlation_id);
+        }
+        let expires_at = self
+            .expires_at
+            .or_else(|| self.ttl.map(|ttl| self.created_at + ttl));
+        if let Some(expires_at) = expires_at {
+            meta_data = meta_data.with_expires_at(expires_at);
+        }
+
+        MessageBusMessage::new(meta_data, self.payload)
+    }
+}
+
+impl MessageBusMessage {
+    /// Start building an instruction message. The `InstructionType` comes from the payload type.
+    pub fn instruction<P: InstructionPayload>(payload: P) -> MessageBuilder {
+        MessageBuilder::new(
+            MessageType::Instruction(P::INSTRUCTION_TYPE),
+            payload.into(),
+        )
+    }
+
+    /// Start building an event message. `build` fails if `event` does not carry `P`.
+    pub fn event<P: EventPayload>(event: EventType, payload: P) -> MessageBuilder {
+        MessageBuilder::new(MessageType::Event(event), payload.into())
+    }
+}
@@ -0,0 +1,189 @@
+//! Claim-check offloading of large payloads.
+//!
+//! When a serialized message is larger than the threshold, its `payload` is written to a
+//! `BlobStore` and replaced by the string `claim-check:v1:<key>`, where the key is the SHA-256
+//! of the stored payload. `meta_data` stays in the message, so expiry, auth and routing work
+//! as before, and a signature over the message covers the key and therefore the payload. The
+//! reference is a JSON string, so it fits the `Payload::Raw` branch of the JSON and Avro schemas.
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const CLAIM_CHECK_PREFIX: &str = "claim-check:v1:";
+
+/// Just under Kafka's default 1 MB `message.max.bytes`, leaving room for headers.
+pub const DEFAULT_THRESHOLD_BYTES: usize = 900 * 1024;
+
+const BLOB_FILE_EXTENSION: &str = ".json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClaimCheckError {
+    #[error("blob {0} not found")]
+    NotFound(String),
+    #[error("blob store unavailable: {0}")]
+    Unavailable(String),
+    #[error("blob {0} does not match its key")]
+    DigestMismatch(String),
+    #[error("invalid claim-check reference {0:?}")]
+    InvalidReference(String),
+    #[error("message can't be offloaded: {0}")]
+    InvalidMessage(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Storage for offloaded payloads, keyed by the hex SHA-256 of their contents.
+///
+/// `FileBlobStore` keeps them on the local filesystem; a client for an object store implements
+/// the same two calls.
+pub trait BlobStore: Send + Sync {
+    /// Store `blob` under `key`. Storing a key that already exists overwrites it with the same
+    /// contents.
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        blob: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), ClaimCheckError>>;
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Vec<u8>, ClaimCheckError>>;
+}
+
+/// A blob store writing one `<key>.json` file per payload into a directory, for local
+/// development and services sharing a volume.
+pub struct FileBlobStore {
+    dir: PathBuf,
+}
+
+impl FileBlobStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, ClaimCheckError> {
+        if !is_key(key) {
+            return Err(ClaimCheckError::InvalidReference(key.to_string()));
+        }
+        Ok(self.dir.join(format!("{key}{BLOB_FILE_EXTENSION}")))
+    }
+
+    fn write(&self, key: &str, blob: &[u8]) -> Result<(), ClaimCheckError> {
+        let path = self.path(key)?;
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| ClaimCheckError::Unavailable(e.to_string()))?;
+        // Write then rename, so a reader never sees a partly written blob
+        let partial = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&partial, blob).map_err(|e| ClaimCheckError::Unavailable(e.to_string()))?;
+        std::fs::rename(&partial, &path).map_err(|e| ClaimCheckError::Unavailable(e.to_string()))
+    }
+
+    fn read(&self, key: &str) -> Result<Vec<u8>, ClaimCheckError> {
+        match std::fs::read(self.path(key)?) {
+            Ok(blob) => Ok(blob),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ClaimCheckError::NotFound(key.to_string()))
+            }
+            Err(e) => Err(ClaimCheckError::Unavailable(e.to_string())),
+        }
+    }
+}
+
+impl BlobStore for FileBlobStore {
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        blob: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), ClaimCheckError>> {
+        Box::pin(async move { self.write(key, blob) })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Vec<u8>, ClaimCheckError>> {
+        Box::pin(async move { self.read(key) })
+    }
+}
+
+fn blob_key(blob: &[u8]) -> String {
+    Sha256::digest(blob)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn is_key(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// The blob key a message's payload was offloaded under, if it was.
+pub fn claim_check_key(message: &Value) -> Option<&str> {
+    message
+        .get("payload")?
+        .as_str()?
+        .strip_prefix(CLAIM_CHECK_PREFIX)
+}
+
+/// Offloads payloads of messages over a size threshold and puts them back on the way in.
+pub struct ClaimCheck {
+    store: Arc<dyn BlobStore>,
+    threshold: usize,
+}
+
+impl ClaimCheck {
+    pub fn new(store: Arc<dyn BlobStore>) -> Self {
+        Self {
+            store,
+            threshold: DEFAULT_THRESHOLD_BYTES,
+        }
+    }
+
+    /// Offload payloads of messages larger than `bytes` once serialized.
+    pub fn with_threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+        self
+    }
+
+    /// Replace the payload of a serialized message over the threshold with a claim-check
+    /// reference. Smaller messages are returned unchanged.
+    pub async fn offload(&self, json: &[u8]) -> Result<Vec<u8>, ClaimCheckError> {
+        if json.len() <= self.threshold {
+            return Ok(json.to_vec());
+        }
+        let mut message: Value = serde_json::from_slice(json)?;
+        let payload = message
+            .get_mut("payload")
+            .ok_or_else(|| ClaimCheckError::InvalidMessage("no payload".to_string()))?;
+
+        let blob = serde_json::to_vec(payload)?;
+        let key = blob_key(&blob);
+        self.store.put(&key, &blob).await?;
+        *payload = Value::String(format!("{CLAIM_CHECK_PREFIX}{key}"));
+        Ok(serde_json::to_vec(&message)?)
+    }
+
+    /// Put an offloaded payload back into a serialized message. Messages without a claim-check
+    /// reference are returned unchanged.
+    pub async fn rehydrate(&self, json: &[u8]) -> Result<Vec<u8>, ClaimCheckError> {
+        let prefix = CLAIM_CHECK_PREFIX.as_bytes();
+        if !json.windows(prefix.len()).any(|window| window == prefix) {
+            return Ok(json.to_vec());
+        }
+        let mut message: Value = serde_json::from_slice(json)?;
+        let Some(key) = claim_check_key(&message) else {
+            return Ok(json.to_vec());
+        };
+        if !is_key(key) {
+            return Err(ClaimCheckError::InvalidReference(key.to_string()));
+        }
+
+        let key = key.to_string();
+        let blob = self.store.get(&key).await?;
+        if blob_key(&blob) != key {
+            return Err(ClaimCheckError::DigestMismatch(key));
+        }
+        message["payload"] = serde_json::from_slice(&blob)?;
+        Ok(serde_json::to_vec(&message)?)
+    }
+}
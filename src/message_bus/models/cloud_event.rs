@@ -0,0 +1,337 @@
+//! CloudEvents 1.0 binding for `MessageBusMessage`.
+//!
+//! | `MessageBusMessage`                   | CloudEvent                                          |
+//! |---------------------------------------|-----------------------------------------------------|
+//! | `meta_data.idempotency_key`           | `id`                                                |
+//! | `meta_data.source`                    | `source`                                            |
+//! | `meta_data.event`                     | `type`, as `com.mykobo.event.<EVENT>`               |
+//! | `meta_data.instruction_type`          | `type`, as `com.mykobo.instruction.<INSTRUCTION>`   |
+//! | `meta_data.created_at`                | `time`                                              |
+//! | `payload`                             | `data`, `text/plain` for `Payload::Raw`             |
+//! | every other `meta_data` field         | an extension, e.g. `ip_address` as `ipaddress`      |
+//!
+//! The service token is a credential, so it is only carried, as the `token` extension, when
+//! added with `CloudEvent::with_token`. Every other field has a place, so converting to a
+//! CloudEvent and back gives the same message apart from an empty token.
+//! In Kafka binary mode the attributes travel as `ce_` headers, `datacontenttype` as the
+//! `content-type` header and `data` as the message body.
+
+use super::message::{MessageBusMessage, Payload};
+use super::redaction::REDACTED;
+use super::schema::SCHEMA_REGISTRY;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+pub const SPEC_VERSION: &str = "1.0";
+pub const EVENT_TYPE_PREFIX: &str = "com.mykobo.event.";
+pub const INSTRUCTION_TYPE_PREFIX: &str = "com.mykobo.instruction.";
+
+/// `content-type` of a structured mode CloudEvent.
+pub const STRUCTURED_CONTENT_TYPE: &str = "application/cloudevents+json";
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+pub const TEXT_CONTENT_TYPE: &str = "text/plain";
+
+/// Prefix of attribute headers in Kafka binary mode.
+pub const KAFKA_HEADER_PREFIX: &str = "ce_";
+pub const KAFKA_CONTENT_TYPE_HEADER: &str = "content-type";
+
+/// Extension carrying `meta_data.token`, only set by `CloudEvent::with_token`.
+pub const TOKEN_EXTENSION: &str = "token";
+
+// `meta_data` fields carried as extensions, with their extension names
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("ip_address", "ipaddress"),
+    ("schema_version", "schemaversion"),
+    ("correlation_id", "correlationid"),
+    ("causation_id", "causationid"),
+    ("expires_at", "expiresat"),
+    ("tenant", "tenant"),
+];
+
+// Extensions left out of `Debug` output
+const SECRET_EXTENSIONS: &[&str] = &[TOKEN_EXTENSION, "ipaddress"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum CloudEventError {
+    #[error("missing CloudEvent attribute {0:?}")]
+    MissingAttribute(&'static str),
+    #[error("unsupported CloudEvents specversion {0:?}")]
+    UnsupportedSpecVersion(String),
+    #[error("CloudEvent type {0:?} is not a mykobo event or instruction")]
+    UnknownType(String),
+    #[error("message has neither an event nor an instruction_type to use as the CloudEvent type")]
+    MissingType,
+    #[error("CloudEvent data is not valid for its content type: {0}")]
+    InvalidData(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// A CloudEvent in structured JSON form.
+///
+/// `Debug` output leaves out `data` and the token and IP address extensions.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent {
+    pub specversion: String,
+    pub id: String,
+    pub source: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datacontenttype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
+}
+
+impl fmt::Debug for CloudEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let extensions: BTreeMap<&str, Value> = self
+            .extensions
+            .iter()
+            .map(|(name, value)| {
+                let value = if SECRET_EXTENSIONS.contains(&name.as_str()) {
+                    Value::from(REDACTED)
+                } else {
+                    value.clone()
+                };
+                (name.as_str(), value)
+            })
+            .collect();
+        f.debug_struct("CloudEvent")
+            .field("specversion", &self.specversion)
+            .field("id", &self.id)
+            .field("source", &self.source)
+            .field("type", &self.event_type)
+            .field("time", &self.time)
+            .field("datacontenttype", &self.datacontenttype)
+            .field("data", &self.data.as_ref().map(|_| REDACTED))
+            .field("extensions", &extensions)
+            .finish()
+    }
+}
+
+impl CloudEvent {
+    /// Carry `token`, normally the message's `meta_data.token`, in the `token` extension. Only
+    /// for consumers that need the service token, since it is then sent wherever the event is.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.extensions
+            .insert(TOKEN_EXTENSION.to_string(), Value::String(token.into()));
+        self
+    }
+
+    /// Kafka binary mode headers: each attribute and extension as a `ce_` header, plus
+    /// `content-type`. `data` goes in the body, see `kafka_body`.
+    pub fn kafka_headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![
+            header("specversion", &self.specversion),
+            header("id", &self.id),
+            header("source", &self.source),
+            header("type", &self.event_type),
+        ];
+        if let Some(time) = &self.time {
+            headers.push(header("time", time));
+        }
+        for (name, value) in &self.extensions {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            headers.push(header(name, &value));
+        }
+        if let Some(content_type) = &self.datacontenttype {
+            headers.push((KAFKA_CONTENT_TYPE_HEADER.to_string(), content_type.clone()));
+        }
+        headers
+    }
+
+    /// Kafka binary mode body: `data` as JSON, or as-is for a text content type.
+    pub fn kafka_body(&self) -> Vec<u8> {
+        match (&self.data, self.is_json()) {
+            (None, _) => Vec::new(),
+            (Some(Value::String(text)), false) => text.clone().into_bytes(),
+            (Some(data), _) => data.to_string().into_bytes(),
+        }
+    }
+
+    /// Read a Kafka binary mode CloudEvent.
+    pub fn from_kafka(
+        headers: &HashMap<String, String>,
+        body: &[u8],
+    ) -> Result<Self, CloudEventError> {
+        let attribute = |name: &'static str| {
+            headers
+                .get(&format!("{KAFKA_HEADER_PREFIX}{name}"))
+                .cloned()
+                .ok_or(CloudEventError::MissingAttribute(name))
+        };
+        let datacontenttype = headers.get(KAFKA_CONTENT_TYPE_HEADER).cloned();
+
+        let mut extensions = BTreeMap::new();
+        for (name, value) in headers {
+            let Some(name) = name.strip_prefix(KAFKA_HEADER_PREFIX) else {
+                continue;
+            };
+            if ["specversion", "id", "source", "type", "time"].contains(&name) {
+                continue;
+            }
+            let value = match name {
+                // Integer extension; kept as a string if it isn't one so decoding can report it
+                "schemaversion" => value
+                    .parse::<u32>()
+                    .map(Value::from)
+                    .unwrap_or_else(|_| Value::String(value.clone())),
+                _ => Value::String(value.clone()),
+            };
+            extensions.insert(name.to_string(), value);
+        }
+
+        let mut event = Self {
+            specversion: attribute("specversion")?,
+            id: attribute("id")?,
+            source: attribute("source")?,
+            event_type: attribute("type")?,
+            time: headers.get(&format!("{KAFKA_HEADER_PREFIX}time")).cloned(),
+            datacontenttype,
+            data: None,
+            extensions,
+        };
+        if !body.is_empty() {
+            event.data = Some(if event.is_json() {
+                serde_json::from_slice(body)?
+            } else {
+                Value::String(
+                    String::from_utf8(body.to_vec())
+                        .map_err(|e| CloudEventError::InvalidData(e.to_string()))?,
+                )
+            });
+        }
+        Ok(event)
+    }
+
+    // An absent `datacontenttype` means JSON in the structured format
+    fn is_json(&self) -> bool {
+        self.datacontenttype.as_deref().is_none_or(|content_type| {
+            let media_type = content_type.split(';').next().unwrap_or_default().trim();
+            media_type == JSON_CONTENT_TYPE || media_type.ends_with("+json")
+        })
+    }
+}
+
+fn header(name: &str, value: &str) -> (String, String) {
+    (format!("{KAFKA_HEADER_PREFIX}{name}"), value.to_string())
+}
+
+impl TryFrom<&MessageBusMessage> for CloudEvent {
+    type Error = CloudEventError;
+
+    /// Fails with `CloudEventError::MissingType` if the message has neither an `event` nor an
+    /// `instruction_type`. The token is left out; see `with_token`.
+    fn try_from(message: &MessageBusMessage) -> Result<Self, Self::Error> {
+        let meta_data = &message.meta_data;
+        let event_type = match (&meta_data.event, &meta_data.instruction_type) {
+            (Some(event), _) => format!("{EVENT_TYPE_PREFIX}{event}"),
+            (None, Some(instruction_type)) => {
+                format!("{INSTRUCTION_TYPE_PREFIX}{instruction_type}")
+            }
+            (None, None) => return Err(CloudEventError::MissingType),
+        };
+
+        let meta_data_value = serde_json::to_value(meta_data).unwrap_or(Value::Null);
+        let extensions = EXTENSIONS
+            .iter()
+            .filter_map(|(field, extension)| {
+                meta_data_value
+                    .get(field)
+                    .filter(|value| !value.is_null())
+                    .map(|value| (extension.to_string(), value.clone()))
+            })
+            .collect();
+
+        let (datacontenttype, data) = match &message.payload {
+            Payload::Raw(text) => (TEXT_CONTENT_TYPE, Value::String(text.clone())),
+            payload => (
+                JSON_CONTENT_TYPE,
+                serde_json::to_value(payload).unwrap_or(Value::Null),
+            ),
+        };
+
+        Ok(Self {
+            specversion: SPEC_VERSION.to_string(),
+            id: meta_data.idempotency_key.clone(),
+            source: meta_data.source.clone(),
+            event_type,
+            time: Some(meta_data.created_at.clone()),
+            datacontenttype: Some(datacontenttype.to_string()),
+            data: Some(data),
+            extensions,
+        })
+    }
+}
+
+impl TryFrom<MessageBusMessage> for CloudEvent {
+    type Error = CloudEventError;
+
+    fn try_from(message: MessageBusMessage) -> Result<Self, Self::Error> {
+        Self::try_from(&message)
+    }
+}
+
+impl TryFrom<CloudEvent> for MessageBusMessage {
+    type Error = CloudEventError;
+
+    /// Rebuild the message, decoding `data` the same way as `MessageBusMessage`'s
+    /// `Deserialize` implementation.
+    fn try_from(event: CloudEvent) -> Result<Self, Self::Error> {
+        if event.specversion != SPEC_VERSION {
+            return Err(CloudEventError::UnsupportedSpecVersion(event.specversion));
+        }
+
+        let mut meta_data = Map::new();
+        meta_data.insert("source".to_string(), Value::String(event.source));
+        meta_data.insert(
+            "created_at".to_string(),
+            Value::String(
+                event
+                    .time
+                    .ok_or(CloudEventError::MissingAttribute("time"))?,
+            ),
+        );
+        meta_data.insert("idempotency_key".to_string(), Value::String(event.id));
+        if let Some(event_name) = event.event_type.strip_prefix(EVENT_TYPE_PREFIX) {
+            meta_data.insert("event".to_string(), Value::String(event_name.to_string()));
+        } else if let Some(instruction_type) =
+            event.event_type.strip_prefix(INSTRUCTION_TYPE_PREFIX)
+        {
+            meta_data.insert(
+                "instruction_type".to_string(),
+                Value::String(instruction_type.to_string()),
+            );
+        } else {
+            return Err(CloudEventError::UnknownType(event.event_type));
+        }
+        for (field, extension) in EXTENSIONS {
+            if let Some(value) = event.extensions.get(*extension) {
+                meta_data.insert(field.to_string(), value.clone());
+            }
+        }
+        // `token` is required in `meta_data` but is only carried if added with `with_token`
+        let token = event
+            .extensions
+            .get(TOKEN_EXTENSION)
+            .cloned()
+            .unwrap_or_else(|| Value::String(String::new()));
+        meta_data.insert("token".to_string(), token);
+
+        let payload = event
+            .data
+            .ok_or(CloudEventError::MissingAttribute("data"))?;
+        let message = serde_json::json!({ "meta_data": meta_data, "payload": payload });
+        Ok(MessageBusMessage::from_value(message, &SCHEMA_REGISTRY)?)
+    }
+}
@@ -0,0 +1,187 @@
+//! Strict decoding of `MessageBusMessage`.
+//!
+//! The `Deserialize` implementation is lenient: any JSON string payload becomes `Payload::Raw`
+//! (which `validate` does not type check), fields a payload struct does not know are dropped,
+//! and a message without a type hint falls back to untagged deserialization. `StrictDecoder`
+//! turns each of these into a `DecodeError`. Raw payloads are only accepted for instruction and
+//! event types registered with `allow_raw_instruction` or `allow_raw_event`.
+
+use super::base::{EventType, InstructionType};
+use super::json_schema::payload_schema;
+use super::message::{MessageBusMessage, MetaData, PayloadKind};
+use super::schema::{SchemaError, SchemaRegistry, SCHEMA_REGISTRY};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// The JSON Schema of each payload struct, which knows the fields of nested objects too
+static PAYLOAD_SCHEMAS: Lazy<HashMap<PayloadKind, Value>> = Lazy::new(|| {
+    PayloadKind::ALL
+        .iter()
+        .map(|kind| (*kind, payload_schema(*kind)))
+        .collect()
+});
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+    #[error("{message_type}: raw string payloads are not allowed")]
+    RawPayload { message_type: String },
+    #[error("{payload}: unknown fields: {}", fields.join(", "))]
+    UnknownFields {
+        payload: &'static str,
+        fields: Vec<String>,
+    },
+    #[error("meta_data has neither instruction_type nor event")]
+    MissingTypeHint,
+}
+
+#[derive(Debug, Clone)]
+pub struct StrictDecoder {
+    raw_instructions: HashSet<InstructionType>,
+    raw_events: HashSet<EventType>,
+    schemas: SchemaRegistry,
+}
+
+impl Default for StrictDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StrictDecoder {
+    /// A decoder that rejects raw payloads for every type and upcasts with `SCHEMA_REGISTRY`.
+    pub fn new() -> Self {
+        Self {
+            raw_instructions: HashSet::new(),
+            raw_events: HashSet::new(),
+            schemas: SCHEMA_REGISTRY.clone(),
+        }
+    }
+
+    pub fn allow_raw_instruction(mut self, instruction_type: InstructionType) -> Self {
+        self.raw_instructions.insert(instruction_type);
+        self
+    }
+
+    pub fn allow_raw_event(mut self, event: EventType) -> Self {
+        self.raw_events.insert(event);
+        self
+    }
+
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    pub fn decode_str(&self, json: &str) -> Result<MessageBusMessage, DecodeError> {
+        self.decode_value(serde_json::from_str(json)?)
+    }
+
+    pub fn decode_slice(&self, json: &[u8]) -> Result<MessageBusMessage, DecodeError> {
+        self.decode_value(serde_json::from_slice(json)?)
+    }
+
+    pub fn decode_value(&self, value: Value) -> Result<MessageBusMessage, DecodeError> {
+        MessageBusMessage::decode(value, &self.schemas, self)
+    }
+
+    pub(crate) fn check_raw(&self, meta_data: &MetaData) -> Result<(), DecodeError> {
+        let (allowed, message_type) = match (&meta_data.instruction_type, &meta_data.event) {
+            (Some(instruction_type), _) => (
+                self.raw_instructions.contains(instruction_type),
+                instruction_type.to_string(),
+            ),
+            (None, Some(event)) => (self.raw_events.contains(event), event.to_string()),
+            (None, None) => return Err(DecodeError::MissingTypeHint),
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(DecodeError::RawPayload { message_type })
+        }
+    }
+
+    pub(crate) fn check_fields(
+        &self,
+        kind: PayloadKind,
+        payload: &Value,
+    ) -> Result<(), DecodeError> {
+        let schema = &PAYLOAD_SCHEMAS[&kind];
+        let mut unknown = Vec::new();
+        unknown_fields(schema, &schema["$defs"], payload, "", &mut unknown);
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::UnknownFields {
+                payload: kind.type_name(),
+                fields: unknown,
+            })
+        }
+    }
+}
+
+// Collect the fields of `value` that `schema` doesn't declare, as dotted paths, descending into
+// nested objects and arrays. Values that don't have the shape the schema expects are left for
+// the typed decode to reject with a clearer error.
+fn unknown_fields(
+    schema: &Value,
+    defs: &Value,
+    value: &Value,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let schema = match schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => &defs[name],
+        None => schema,
+    };
+    if let Some(variant) = tagged_variant(schema, value) {
+        return unknown_fields(variant, defs, value, path, unknown);
+    }
+
+    match value {
+        Value::Object(object) => {
+            let Some(properties) = schema["properties"].as_object() else {
+                return;
+            };
+            for (field, field_value) in object {
+                let field_path = format!("{path}{field}");
+                match properties.get(field) {
+                    Some(field_schema) => {
+                        let nested = format!("{field_path}.");
+                        unknown_fields(field_schema, defs, field_value, &nested, unknown)
+                    }
+                    None => unknown.push(field_path),
+                }
+            }
+        }
+        Value::Array(items) if schema["items"].is_object() => {
+            for (index, item) in items.iter().enumerate() {
+                let nested = format!("{path}[{index}].");
+                unknown_fields(&schema["items"], defs, item, &nested, unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+// The `oneOf` variant of an internally tagged enum whose `const` tag matches `value`
+fn tagged_variant<'a>(schema: &'a Value, value: &Value) -> Option<&'a Value> {
+    schema["oneOf"].as_array()?.iter().find(|variant| {
+        variant["properties"].as_object().is_some_and(|properties| {
+            let mut tags = properties
+                .iter()
+                .filter_map(|(field, field_schema)| Some((field, field_schema.get("const")?)))
+                .peekable();
+            tags.peek().is_some() && tags.all(|(field, tag)| value.get(field) == Some(tag))
+        })
+    })
+}
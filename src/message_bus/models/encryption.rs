@@ -0,0 +1,310 @@
+//! Envelope encryption of sensitive payload fields.
+//!
+//! Each encrypted field gets a fresh AES-256-GCM data key, which is itself encrypted ("wrapped")
+//! with a key-encryption key (KEK) identified by a key id. The field keeps its JSON string type
+//! and holds `enc:v1:<key id>:<wrapped data key>:<ciphertext>`, so consumers without the KEK can
+//! still deserialize the message and simply see the encrypted value. The field name and the
+//! message's `meta_data.idempotency_key` are bound to the ciphertext, so an encrypted value can't
+//! be moved to another field or another message.
+//!
+//! Only top-level payload fields holding a string are encrypted. A sensitive field holding an
+//! object or array, such as `AddressOnboardedEventPayload.payload` or a
+//! `CustomerNotificationPayload.data` object, is sent in clear, nested values included.
+
+use super::message::{MetaData, PayloadKind};
+use super::redaction::Sensitivity;
+use super::MessageBusMessage;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload as AeadPayload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+/// Payload fields encrypted by `FieldEncryptor` when they hold a string: the payload's
+/// `Redact::SENSITIVE_FIELDS`. `validate` skips the format checks of encrypted values.
+pub fn encrypted_fields(kind: PayloadKind) -> Vec<&'static str> {
+    kind.sensitive_fields()
+        .iter()
+        .filter(|field| matches!(field.sensitivity, Sensitivity::Pii | Sensitivity::Secret))
+        .map(|field| field.name)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("invalid key {key_id:?}: {reason}")]
+    InvalidKey { key_id: String, reason: String },
+    #[error("{field}: malformed encrypted value")]
+    Malformed { field: String },
+    #[error("{field}: decryption with key {key_id:?} failed")]
+    Decryption { field: String, key_id: String },
+    #[error("message can't be encrypted: {0}")]
+    InvalidMessage(String),
+}
+
+/// Encrypts and decrypts the fields listed by `encrypted_fields`.
+///
+/// New values are encrypted with the active KEK. Keys added with `with_key` are only used to
+/// decrypt, which allows rotating the active KEK while older messages are still retained.
+pub struct FieldEncryptor {
+    active_key_id: String,
+    keys: HashMap<String, [u8; KEY_LENGTH]>,
+}
+
+impl FieldEncryptor {
+    /// Use a 32 byte KEK as the active key.
+    pub fn new(key_id: impl Into<String>, kek: &[u8]) -> Result<Self, EncryptionError> {
+        let key_id = key_id.into();
+        let encryptor = Self {
+            active_key_id: key_id.clone(),
+            keys: HashMap::new(),
+        };
+        encryptor.with_key(key_id, kek)
+    }
+
+    /// Add a KEK that can decrypt but is not used for new values.
+    pub fn with_key(
+        mut self,
+        key_id: impl Into<String>,
+        kek: &[u8],
+    ) -> Result<Self, EncryptionError> {
+        let key_id = key_id.into();
+        let invalid = |reason: &str| EncryptionError::InvalidKey {
+            key_id: key_id.clone(),
+            reason: reason.to_string(),
+        };
+        if key_id.is_empty() || key_id.contains(':') {
+            return Err(invalid("key ids must be non-empty and may not contain ':'"));
+        }
+        let kek = <[u8; KEY_LENGTH]>::try_from(kek)
+            .map_err(|_| invalid("key-encryption keys must be 32 bytes"))?;
+        self.keys.insert(key_id, kek);
+        Ok(self)
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.active_key_id
+    }
+
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Encrypt one field value of the message with `idempotency_key`. Both are bound to the
+    /// ciphertext.
+    pub fn encrypt_field(
+        &self,
+        idempotency_key: &str,
+        field: &str,
+        plaintext: &str,
+    ) -> Result<String, EncryptionError> {
+        let kek = &self.keys[&self.active_key_id];
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let wrapped = seal(kek, &data_key, self.active_key_id.as_bytes())
+            .ok_or_else(|| EncryptionError::InvalidMessage(field.to_string()))?;
+        let ciphertext = seal(
+            &data_key,
+            plaintext.as_bytes(),
+            &field_aad(idempotency_key, field),
+        )
+        .ok_or_else(|| EncryptionError::InvalidMessage(field.to_string()))?;
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{}:{}:{}",
+            self.active_key_id,
+            STANDARD_NO_PAD.encode(wrapped),
+            STANDARD_NO_PAD.encode(ciphertext)
+        ))
+    }
+
+    /// Decrypt one field value of the message with `idempotency_key`. Returns `Ok(None)` when
+    /// the value was encrypted with a KEK this encryptor doesn't hold.
+    pub fn decrypt_field(
+        &self,
+        idempotency_key: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<String>, EncryptionError> {
+        let malformed = || EncryptionError::Malformed {
+            field: field.to_string(),
+        };
+        let mut parts = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(malformed)?
+            .split(':');
+        let (Some(key_id), Some(wrapped), Some(ciphertext), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(malformed());
+        };
+        let Some(kek) = self.keys.get(key_id) else {
+            return Ok(None);
+        };
+        let decode = |part: &str| STANDARD_NO_PAD.decode(part).map_err(|_| malformed());
+        let failed = || EncryptionError::Decryption {
+            field: field.to_string(),
+            key_id: key_id.to_string(),
+        };
+
+        let data_key = open(kek, &decode(wrapped)?, key_id.as_bytes())
+            .and_then(|data_key| <[u8; KEY_LENGTH]>::try_from(data_key).ok())
+            .ok_or_else(failed)?;
+        let plaintext = open(
+            &data_key,
+            &decode(ciphertext)?,
+            &field_aad(idempotency_key, field),
+        )
+        .ok_or_else(failed)?;
+        String::from_utf8(plaintext).map(Some).map_err(|_| failed())
+    }
+
+    /// Encrypt the sensitive fields of a serialized `MessageBusMessage` in place.
+    ///
+    /// The payload kind is taken from `meta_data`. Values that are already encrypted are left
+    /// alone.
+    pub fn encrypt_json(&self, message: &mut Value) -> Result<(), EncryptionError> {
+        let Some(kind) = serde_json::from_value::<MetaData>(message["meta_data"].clone())
+            .ok()
+            .and_then(|meta_data| meta_data.payload_kind())
+        else {
+            return Ok(());
+        };
+        let idempotency_key = idempotency_key(message)?;
+        let Some(payload) = message.get_mut("payload").and_then(Value::as_object_mut) else {
+            return Ok(());
+        };
+        for field in encrypted_fields(kind) {
+            if let Some(Value::String(value)) = payload.get_mut(field) {
+                if !Self::is_encrypted(value) {
+                    *value = self.encrypt_field(&idempotency_key, field, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt every encrypted payload field this encryptor holds the KEK for, in place.
+    pub fn decrypt_json(&self, message: &mut Value) -> Result<(), EncryptionError> {
+        let Some(payload) = message.get("payload").and_then(Value::as_object) else {
+            return Ok(());
+        };
+        let encrypted = payload
+            .values()
+            .any(|value| value.as_str().is_some_and(Self::is_encrypted));
+        if !encrypted {
+            return Ok(());
+        }
+        let idempotency_key = idempotency_key(message)?;
+        let Some(payload) = message.get_mut("payload").and_then(Value::as_object_mut) else {
+            return Ok(());
+        };
+        for (field, value) in payload.iter_mut() {
+            if let Value::String(text) = value {
+                if Self::is_encrypted(text) {
+                    if let Some(plaintext) = self.decrypt_field(&idempotency_key, field, text)? {
+                        *text = plaintext;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `encrypt_json` over serialized bytes, as sent by `EventProducer`.
+    pub fn encrypt_bytes(&self, message: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        self.transform_bytes(message, Self::encrypt_json)
+    }
+
+    /// `decrypt_json` over serialized bytes, as received by `EventConsumer`.
+    pub fn decrypt_bytes(&self, message: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        self.transform_bytes(message, Self::decrypt_json)
+    }
+
+    pub fn encrypt(
+        &self,
+        message: &MessageBusMessage,
+    ) -> Result<MessageBusMessage, EncryptionError> {
+        self.transform_message(message, Self::encrypt_json)
+    }
+
+    pub fn decrypt(
+        &self,
+        message: &MessageBusMessage,
+    ) -> Result<MessageBusMessage, EncryptionError> {
+        self.transform_message(message, Self::decrypt_json)
+    }
+
+    fn transform_bytes(
+        &self,
+        message: &[u8],
+        transform: fn(&Self, &mut Value) -> Result<(), EncryptionError>,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        let invalid = |e: serde_json::Error| EncryptionError::InvalidMessage(e.to_string());
+        let mut value: Value = serde_json::from_slice(message).map_err(invalid)?;
+        transform(self, &mut value)?;
+        serde_json::to_vec(&value).map_err(invalid)
+    }
+
+    fn transform_message(
+        &self,
+        message: &MessageBusMessage,
+        transform: fn(&Self, &mut Value) -> Result<(), EncryptionError>,
+    ) -> Result<MessageBusMessage, EncryptionError> {
+        let invalid = |e: serde_json::Error| EncryptionError::InvalidMessage(e.to_string());
+        let mut value = serde_json::to_value(message).map_err(invalid)?;
+        transform(self, &mut value)?;
+        serde_json::from_value(value).map_err(invalid)
+    }
+}
+
+fn idempotency_key(message: &Value) -> Result<String, EncryptionError> {
+    message
+        .pointer("/meta_data/idempotency_key")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| EncryptionError::InvalidMessage("no meta_data.idempotency_key".to_string()))
+}
+
+// idempotency key || 0 || field name
+fn field_aad(idempotency_key: &str, field: &str) -> Vec<u8> {
+    [idempotency_key.as_bytes(), &[0], field.as_bytes()].concat()
+}
+
+// nonce || AES-256-GCM(key, plaintext, aad)
+fn seal(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new_from_slice(key)
+        .ok()?
+        .encrypt(
+            &nonce,
+            AeadPayload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .ok()?;
+    Some([&nonce[..], &ciphertext].concat())
+}
+
+fn open(key: &[u8], sealed: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    let nonce = Nonce::from(<[u8; NONCE_LENGTH]>::try_from(nonce).ok()?);
+    Aes256Gcm::new_from_slice(key)
+        .ok()?
+        .decrypt(
+            &nonce,
+            AeadPayload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
@@ -1,9 +1,13 @@
-use super::base::{validate_required_fields, TransactionType, ValidationError};
+use super::base::{
+    impl_payload_parsing, validate_required_fields, TransactionType, ValidationError,
+};
+use super::validation::FieldValidator;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Payload for a new transaction event mainly for notification purposes
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct NewTransactionEventPayload {
     pub created_at: String,
     pub kind: TransactionType,
@@ -30,21 +34,14 @@ impl NewTransactionEventPayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        FieldValidator::new("NewTransactionEventPayload")
+            .required_fields(&[
                 ("created_at", &self.created_at),
                 ("reference", &self.reference),
                 ("source", &self.source),
-            ],
-            "NewTransactionEventPayload",
-        )
-    }
-}
-
-impl From<String> for NewTransactionEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize NewTransactionEventPayload from String")
+            ])
+            .timestamp("created_at", &self.created_at)
+            .finish()
     }
 }
 
@@ -56,7 +53,7 @@ impl From<NewTransactionEventPayload> for String {
 }
 
 /// Payload for transaction status update event for notification purposes, this can go to the notification server
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde_with::skip_serializing_none]
 pub struct TransactionStatusEventPayload {
     pub external_reference: Option<String>,
@@ -88,13 +85,6 @@ impl TransactionStatusEventPayload {
     }
 }
 
-impl From<String> for TransactionStatusEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize TransactionStatusEventPayload from String")
-    }
-}
-
 impl From<TransactionStatusEventPayload> for String {
     fn from(val: TransactionStatusEventPayload) -> Self {
         serde_json::to_string(&val)
@@ -104,7 +94,7 @@ impl From<TransactionStatusEventPayload> for String {
 
 /// Payload for notifying the business server of a bank payment event. This is generally used to let the
 /// business server know to create a chain payment for the corresponding bank payment
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde_with::skip_serializing_none]
 pub struct BankPaymentEventPayload {
     pub transaction_id: String,
@@ -143,13 +133,6 @@ impl BankPaymentEventPayload {
     }
 }
 
-impl From<String> for BankPaymentEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize BankPaymentEventPayload from String")
-    }
-}
-
 impl From<BankPaymentEventPayload> for String {
     fn from(val: BankPaymentEventPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize BankPaymentEventPayload to String")
@@ -158,7 +141,7 @@ impl From<BankPaymentEventPayload> for String {
 
 /// Payload for bank payment event
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct PaymentEventPayload {
     pub external_reference: String,
     pub reference: Option<String>,
@@ -192,12 +175,6 @@ impl PaymentEventPayload {
     }
 }
 
-impl From<String> for PaymentEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize PaymentEventPayload from String")
-    }
-}
-
 impl From<PaymentEventPayload> for String {
     fn from(val: PaymentEventPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize PaymentEventPayload to String")
@@ -205,7 +182,7 @@ impl From<PaymentEventPayload> for String {
 }
 
 /// Payload for new profile event
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct ProfileEventPayload {
     pub title: String,
     pub identifier: String,
@@ -230,12 +207,6 @@ impl ProfileEventPayload {
     }
 }
 
-impl From<String> for ProfileEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize ProfileEventPayload from String")
-    }
-}
-
 impl From<ProfileEventPayload> for String {
     fn from(val: ProfileEventPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize ProfileEventPayload to String")
@@ -243,7 +214,7 @@ impl From<ProfileEventPayload> for String {
 }
 
 /// Payload for new user event
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct NewUserEventPayload {
     pub title: String,
     pub identifier: String,
@@ -268,12 +239,6 @@ impl NewUserEventPayload {
     }
 }
 
-impl From<String> for NewUserEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize NewUserEventPayload from String")
-    }
-}
-
 impl From<NewUserEventPayload> for String {
     fn from(val: NewUserEventPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize NewUserEventPayload to String")
@@ -282,7 +247,7 @@ impl From<NewUserEventPayload> for String {
 
 /// Payload for KYC event
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct KycEventPayload {
     pub title: String,
     pub identifier: String,
@@ -330,12 +295,6 @@ impl KycEventPayload {
     }
 }
 
-impl From<String> for KycEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize KycEventPayload from String")
-    }
-}
-
 impl From<KycEventPayload> for String {
     fn from(val: KycEventPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize KycEventPayload to String")
@@ -343,7 +302,7 @@ impl From<KycEventPayload> for String {
 }
 
 /// Payload for password reset event
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct PasswordResetEventPayload {
     pub to: String,
     pub subject: String,
@@ -363,21 +322,14 @@ impl PasswordResetEventPayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        FieldValidator::new("PasswordResetEventPayload")
+            .required_fields(&[
                 ("to", &self.to),
                 ("subject", &self.subject),
                 ("password", &self.password),
-            ],
-            "PasswordResetEventPayload",
-        )
-    }
-}
-
-impl From<String> for PasswordResetEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize PasswordResetEventPayload from String")
+            ])
+            .email("to", &self.to)
+            .finish()
     }
 }
 
@@ -389,7 +341,7 @@ impl From<PasswordResetEventPayload> for String {
 }
 
 /// Payload for verification requested event
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct VerificationRequestedEventPayload {
     pub to: String,
     pub subject: String,
@@ -407,17 +359,10 @@ impl VerificationRequestedEventPayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[("to", &self.to), ("subject", &self.subject)],
-            "VerificationRequestedEventPayload",
-        )
-    }
-}
-
-impl From<String> for VerificationRequestedEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize VerificationRequestedEventPayload from String")
+        FieldValidator::new("VerificationRequestedEventPayload")
+            .required_fields(&[("to", &self.to), ("subject", &self.subject)])
+            .email("to", &self.to)
+            .finish()
     }
 }
 
@@ -429,7 +374,7 @@ impl From<VerificationRequestedEventPayload> for String {
 }
 
 /// Payload for address onboarded event
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct AddressOnboardedEventPayload {
     pub email: String,
     pub payload: HashMap<String, String>,
@@ -446,14 +391,10 @@ impl AddressOnboardedEventPayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(&[("email", &self.email)], "AddressOnboardedEventPayload")
-    }
-}
-
-impl From<String> for AddressOnboardedEventPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize AddressOnboardedEventPayload from String")
+        FieldValidator::new("AddressOnboardedEventPayload")
+            .required_fields(&[("email", &self.email)])
+            .email("email", &self.email)
+            .finish()
     }
 }
 
@@ -464,3 +405,15 @@ impl From<AddressOnboardedEventPayload> for String {
     }
 }
 
+impl_payload_parsing!(
+    NewTransactionEventPayload,
+    TransactionStatusEventPayload,
+    BankPaymentEventPayload,
+    PaymentEventPayload,
+    ProfileEventPayload,
+    NewUserEventPayload,
+    KycEventPayload,
+    PasswordResetEventPayload,
+    VerificationRequestedEventPayload,
+    AddressOnboardedEventPayload,
+);
@@ -1,20 +1,37 @@
-use super::base::{validate_required_fields, PaymentDirection, TransactionType, ValidationError};
+use super::base::{impl_payload_parsing, PaymentDirection, TransactionType, ValidationError};
+use super::validation::{parse_money, FieldValidator};
+use crate::models::money::Money;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Payload for payment instructions
-#[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(try_from = "PaymentPayloadJson", into = "PaymentPayloadJson")]
 pub struct PaymentPayload {
     pub external_reference: String,
     pub payer_name: Option<String>,
-    pub currency: String,
-    pub value: String,
+    /// Sent as the `value` and `currency` strings.
+    pub value: Money,
     pub source: String,
     pub direction: PaymentDirection,
     pub reference: String,
     pub bank_account_number: Option<String>,
 }
 
+/// Payload for payment instructions
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct PaymentPayloadJson {
+    external_reference: String,
+    payer_name: Option<String>,
+    currency: String,
+    value: String,
+    source: String,
+    direction: PaymentDirection,
+    reference: String,
+    bank_account_number: Option<String>,
+}
+
 impl PaymentPayload {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -27,38 +44,70 @@ impl PaymentPayload {
         payer_name: Option<String>,
         bank_account_number: Option<String>,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
-            external_reference: external_reference.clone(),
+        let payload = PaymentPayloadJson {
+            external_reference,
             payer_name,
-            currency: currency.clone(),
-            value: value.clone(),
-            source: source.clone(),
+            currency,
+            value,
+            source,
             direction,
-            reference: reference.clone(),
+            reference,
             bank_account_number,
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        PaymentPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl PaymentPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("PaymentPayload")
+            .required_fields(&[
                 ("external_reference", &self.external_reference),
                 ("currency", &self.currency),
                 ("value", &self.value),
                 ("source", &self.source),
                 ("reference", &self.reference),
-            ],
-            "PaymentPayload",
-        )
+            ])
+            .positive_amount("value", &self.value)
+            .currency("currency", &self.currency)
+            .finish()
     }
 }
 
-impl From<String> for PaymentPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize PaymentPayload from String")
+impl TryFrom<PaymentPayloadJson> for PaymentPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: PaymentPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: parse_money("PaymentPayload", "value", &json.value, &json.currency)?,
+            external_reference: json.external_reference,
+            payer_name: json.payer_name,
+            source: json.source,
+            direction: json.direction,
+            reference: json.reference,
+            bank_account_number: json.bank_account_number,
+        })
+    }
+}
+
+impl From<PaymentPayload> for PaymentPayloadJson {
+    fn from(payload: PaymentPayload) -> Self {
+        Self {
+            external_reference: payload.external_reference,
+            payer_name: payload.payer_name,
+            currency: payload.value.currency.to_string(),
+            value: payload.value.amount_string(),
+            source: payload.source,
+            direction: payload.direction,
+            reference: payload.reference,
+            bank_account_number: payload.bank_account_number,
+        }
     }
 }
 
@@ -69,7 +118,7 @@ impl From<PaymentPayload> for String {
 }
 
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct ChainPaymentPayload {
     pub chain: String,
     pub hash: String,
@@ -99,23 +148,17 @@ impl ChainPaymentPayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        FieldValidator::new("ChainPaymentPayload")
+            .required_fields(&[
                 ("chain", &self.chain),
                 ("hash", &self.hash),
                 ("reference", &self.reference),
                 ("status", &self.status),
-            ],
-            "ChainPaymentPayload",
-        )
+            ])
+            .chain("chain", &self.chain)
+            .finish()
     }
 }
-impl From<String> for ChainPaymentPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize ChainPaymentPayload from String")
-    }
-}
-
 impl From<ChainPaymentPayload> for String {
     fn from(val: ChainPaymentPayload) -> Self {
         serde_json::to_string(&val).expect("Failed to serialize ChainPaymentPayload to String")
@@ -123,7 +166,7 @@ impl From<ChainPaymentPayload> for String {
 }
 
 /// Payload for status update instructions
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct StatusUpdatePayload {
     pub reference: String,
     pub status: String,
@@ -152,16 +195,9 @@ impl StatusUpdatePayload {
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[("reference", &self.reference), ("status", &self.status)],
-            "StatusUpdatePayload",
-        )
-    }
-}
-
-impl From<String> for StatusUpdatePayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize StatusUpdatePayload from String")
+        FieldValidator::new("StatusUpdatePayload")
+            .required_fields(&[("reference", &self.reference), ("status", &self.status)])
+            .finish()
     }
 }
 
@@ -172,15 +208,26 @@ impl From<StatusUpdatePayload> for String {
 }
 
 /// Payload for correction instructions
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(try_from = "CorrectionPayloadJson", into = "CorrectionPayloadJson")]
 pub struct CorrectionPayload {
     pub reference: String,
-    pub value: String,
+    /// Sent as the `value` and `currency` strings. Negative for a debit.
+    pub value: Money,
     pub message: String,
-    pub currency: String,
     pub source: String,
 }
 
+/// Payload for correction instructions
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct CorrectionPayloadJson {
+    reference: String,
+    value: String,
+    message: String,
+    currency: String,
+    source: String,
+}
+
 impl CorrectionPayload {
     pub fn new(
         reference: String,
@@ -189,35 +236,61 @@ impl CorrectionPayload {
         currency: String,
         source: String,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
-            reference: reference.clone(),
-            value: value.clone(),
-            message: message.clone(),
-            currency: currency.clone(),
-            source: source.clone(),
+        let payload = CorrectionPayloadJson {
+            reference,
+            value,
+            message,
+            currency,
+            source,
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        CorrectionPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl CorrectionPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("CorrectionPayload")
+            .required_fields(&[
                 ("reference", &self.reference),
                 ("value", &self.value),
                 ("message", &self.message),
                 ("currency", &self.currency),
                 ("source", &self.source),
-            ],
-            "CorrectionPayload",
-        )
+            ])
+            .decimal("value", &self.value)
+            .currency("currency", &self.currency)
+            .finish()
     }
 }
 
-impl From<String> for CorrectionPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize CorrectionPayload from String")
+impl TryFrom<CorrectionPayloadJson> for CorrectionPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: CorrectionPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: parse_money("CorrectionPayload", "value", &json.value, &json.currency)?,
+            reference: json.reference,
+            message: json.message,
+            source: json.source,
+        })
+    }
+}
+
+impl From<CorrectionPayload> for CorrectionPayloadJson {
+    fn from(payload: CorrectionPayload) -> Self {
+        Self {
+            reference: payload.reference,
+            value: payload.value.amount_string(),
+            message: payload.message,
+            currency: payload.value.currency.to_string(),
+            source: payload.source,
+        }
     }
 }
 
@@ -228,8 +301,8 @@ impl From<CorrectionPayload> for String {
 }
 
 /// Payload for transaction instructions
-#[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(try_from = "TransactionPayloadJson", into = "TransactionPayloadJson")]
 pub struct TransactionPayload {
     pub external_reference: String,
     pub source: String,
@@ -238,14 +311,35 @@ pub struct TransactionPayload {
     pub last_name: String,
     pub transaction_type: TransactionType,
     pub status: String,
-    pub incoming_currency: String,
     pub outgoing_currency: String,
-    pub value: String,
-    pub fee: String,
+    /// Sent as the `value` and `incoming_currency` strings.
+    pub value: Money,
+    /// Charged in the incoming currency. Only the amount is sent, as `fee`, so it is read in and
+    /// written as `value`'s currency.
+    pub fee: Money,
     pub payer: Option<String>,
     pub payee: Option<String>,
 }
 
+/// Payload for transaction instructions
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct TransactionPayloadJson {
+    external_reference: String,
+    source: String,
+    reference: String,
+    first_name: String,
+    last_name: String,
+    transaction_type: TransactionType,
+    status: String,
+    incoming_currency: String,
+    outgoing_currency: String,
+    value: String,
+    fee: String,
+    payer: Option<String>,
+    payee: Option<String>,
+}
+
 impl TransactionPayload {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -263,46 +357,48 @@ impl TransactionPayload {
         payer: Option<String>,
         payee: Option<String>,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
-            external_reference: external_reference.clone(),
-            source: source.clone(),
-            reference: reference.clone(),
-            first_name: first_name.clone(),
-            last_name: last_name.clone(),
+        let payload = TransactionPayloadJson {
+            external_reference,
+            source,
+            reference,
+            first_name,
+            last_name,
             transaction_type,
-            status: status.clone(),
-            incoming_currency: incoming_currency.clone(),
-            outgoing_currency: outgoing_currency.clone(),
-            value: value.clone(),
-            fee: fee.clone(),
-            payer: payer.clone(),
-            payee: payee.clone(),
+            status,
+            incoming_currency,
+            outgoing_currency,
+            value,
+            fee,
+            payer,
+            payee,
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
+        TransactionPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl TransactionPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let mut validator = FieldValidator::new("TransactionPayload");
+
         // Validate transaction type specific requirements
         match self.transaction_type {
             TransactionType::Deposit if self.payer.is_none() => {
-                return Err(ValidationError {
-                    class_name: "TransactionPayload".to_string(),
-                    fields: vec!["payer (required for DEPOSIT transactions)".to_string()],
-                });
+                validator.missing("payer", "required for DEPOSIT transactions");
             }
             TransactionType::Withdraw if self.payee.is_none() => {
-                return Err(ValidationError {
-                    class_name: "TransactionPayload".to_string(),
-                    fields: vec!["payee (required for WITHDRAW transactions)".to_string()],
-                });
+                validator.missing("payee", "required for WITHDRAW transactions");
             }
             _ => {}
         }
 
-        validate_required_fields(
-            &[
+        validator
+            .required_fields(&[
                 ("external_reference", &self.external_reference),
                 ("source", &self.source),
                 ("reference", &self.reference),
@@ -313,15 +409,54 @@ impl TransactionPayload {
                 ("outgoing_currency", &self.outgoing_currency),
                 ("value", &self.value),
                 ("fee", &self.fee),
-            ],
-            "TransactionPayload",
-        )
-    }
-}
-
-impl From<String> for TransactionPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize TransactionPayload from String")
+            ])
+            .positive_amount("value", &self.value)
+            .non_negative_amount("fee", &self.fee)
+            .currency("incoming_currency", &self.incoming_currency)
+            .currency("outgoing_currency", &self.outgoing_currency)
+            .finish()
+    }
+}
+
+impl TryFrom<TransactionPayloadJson> for TransactionPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: TransactionPayloadJson) -> Result<Self, Self::Error> {
+        let currency = &json.incoming_currency;
+        Ok(Self {
+            value: parse_money("TransactionPayload", "value", &json.value, currency)?,
+            fee: parse_money("TransactionPayload", "fee", &json.fee, currency)?,
+            external_reference: json.external_reference,
+            source: json.source,
+            reference: json.reference,
+            first_name: json.first_name,
+            last_name: json.last_name,
+            transaction_type: json.transaction_type,
+            status: json.status,
+            outgoing_currency: json.outgoing_currency,
+            payer: json.payer,
+            payee: json.payee,
+        })
+    }
+}
+
+impl From<TransactionPayload> for TransactionPayloadJson {
+    fn from(payload: TransactionPayload) -> Self {
+        Self {
+            external_reference: payload.external_reference,
+            source: payload.source,
+            reference: payload.reference,
+            first_name: payload.first_name,
+            last_name: payload.last_name,
+            transaction_type: payload.transaction_type,
+            status: payload.status,
+            incoming_currency: payload.value.currency.to_string(),
+            outgoing_currency: payload.outgoing_currency,
+            value: payload.value.amount_string(),
+            fee: payload.fee.amount_string(),
+            payer: payload.payer,
+            payee: payload.payee,
+        }
     }
 }
 
@@ -331,16 +466,29 @@ impl From<TransactionPayload> for String {
     }
 }
 
-#[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(
+    try_from = "BankPaymentRequestPayloadJson",
+    into = "BankPaymentRequestPayloadJson"
+)]
 pub struct BankPaymentRequestPayload {
     pub reference: String,
-    pub value: String,
-    pub currency: String,
+    /// Sent as the `value` and `currency` strings.
+    pub value: Money,
     pub profile_id: String,
     pub message: Option<String>,
 }
 
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct BankPaymentRequestPayloadJson {
+    reference: String,
+    value: String,
+    currency: String,
+    profile_id: String,
+    message: Option<String>,
+}
+
 impl BankPaymentRequestPayload {
     pub fn new(
         reference: String,
@@ -349,35 +497,65 @@ impl BankPaymentRequestPayload {
         profile_id: String,
         message: Option<String>,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
-            reference: reference.clone(),
-            value: value.clone(),
-            currency: currency.clone(),
-            profile_id: profile_id.clone(),
+        let payload = BankPaymentRequestPayloadJson {
+            reference,
+            value,
+            currency,
+            profile_id,
             message,
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        BankPaymentRequestPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl BankPaymentRequestPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("BankPaymentRequestPayload")
+            .required_fields(&[
                 ("reference", &self.reference),
                 ("value", &self.value),
                 ("currency", &self.currency),
                 ("profile_id", &self.profile_id),
-            ],
-            "BankPaymentRequestPayload",
-        )
+            ])
+            .positive_amount("value", &self.value)
+            .currency("currency", &self.currency)
+            .finish()
     }
 }
 
-impl From<String> for BankPaymentRequestPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize BankPaymentRequestPayload from String")
+impl TryFrom<BankPaymentRequestPayloadJson> for BankPaymentRequestPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: BankPaymentRequestPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: parse_money(
+                "BankPaymentRequestPayload",
+                "value",
+                &json.value,
+                &json.currency,
+            )?,
+            reference: json.reference,
+            profile_id: json.profile_id,
+            message: json.message,
+        })
+    }
+}
+
+impl From<BankPaymentRequestPayload> for BankPaymentRequestPayloadJson {
+    fn from(payload: BankPaymentRequestPayload) -> Self {
+        Self {
+            reference: payload.reference,
+            value: payload.value.amount_string(),
+            currency: payload.value.currency.to_string(),
+            profile_id: payload.profile_id,
+            message: payload.message,
+        }
     }
 }
 
@@ -390,7 +568,7 @@ impl From<BankPaymentRequestPayload> for String {
 
 /// Payload for profile update instructions
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct UpdateProfilePayload {
     pub profile_id: String,
     pub address_line_1: Option<String>,
@@ -431,12 +609,13 @@ impl UpdateProfilePayload {
             deleted_at,
         }
     }
-}
 
-impl From<String> for UpdateProfilePayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value)
-            .expect("Failed to deserialize UpdateProfilePayload from String")
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("UpdateProfilePayload")
+            .required_fields(&[("profile_id", &self.profile_id)])
+            .optional_timestamp("suspended_at", self.suspended_at.as_deref())
+            .optional_timestamp("deleted_at", self.deleted_at.as_deref())
+            .finish()
     }
 }
 
@@ -447,16 +626,27 @@ impl From<UpdateProfilePayload> for String {
 }
 
 /// Payload for mint instructions
-#[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(try_from = "MintPayloadJson", into = "MintPayloadJson")]
 pub struct MintPayload {
-    pub value: String,
-    pub currency: String,
+    /// Sent as the `value` and `currency` strings.
+    pub value: Money,
     pub reference: String,
     pub chain: String,
     pub message: Option<String>,
 }
 
+/// Payload for mint instructions
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct MintPayloadJson {
+    value: String,
+    currency: String,
+    reference: String,
+    chain: String,
+    message: Option<String>,
+}
+
 impl MintPayload {
     pub fn new(
         value: String,
@@ -465,7 +655,7 @@ impl MintPayload {
         chain: String,
         message: Option<String>,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
+        let payload = MintPayloadJson {
             value,
             currency,
             reference,
@@ -474,25 +664,52 @@ impl MintPayload {
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        MintPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl MintPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("MintPayload")
+            .required_fields(&[
                 ("value", &self.value),
                 ("currency", &self.currency),
                 ("reference", &self.reference),
                 ("chain", &self.chain),
-            ],
-            "MintPayload",
-        )
+            ])
+            .positive_amount("value", &self.value)
+            .currency("currency", &self.currency)
+            .chain("chain", &self.chain)
+            .finish()
     }
 }
 
-impl From<String> for MintPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize MintPayload from String")
+impl TryFrom<MintPayloadJson> for MintPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: MintPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: parse_money("MintPayload", "value", &json.value, &json.currency)?,
+            reference: json.reference,
+            chain: json.chain,
+            message: json.message,
+        })
+    }
+}
+
+impl From<MintPayload> for MintPayloadJson {
+    fn from(payload: MintPayload) -> Self {
+        Self {
+            value: payload.value.amount_string(),
+            currency: payload.value.currency.to_string(),
+            reference: payload.reference,
+            chain: payload.chain,
+            message: payload.message,
+        }
     }
 }
 
@@ -503,16 +720,27 @@ impl From<MintPayload> for String {
 }
 
 /// Payload for burn instructions
-#[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(try_from = "BurnPayloadJson", into = "BurnPayloadJson")]
 pub struct BurnPayload {
-    pub value: String,
-    pub currency: String,
+    /// Sent as the `value` and `currency` strings.
+    pub value: Money,
     pub reference: String,
     pub chain: String,
     pub message: Option<String>,
 }
 
+/// Payload for burn instructions
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+struct BurnPayloadJson {
+    value: String,
+    currency: String,
+    reference: String,
+    chain: String,
+    message: Option<String>,
+}
+
 impl BurnPayload {
     pub fn new(
         value: String,
@@ -521,7 +749,7 @@ impl BurnPayload {
         chain: String,
         message: Option<String>,
     ) -> Result<Self, ValidationError> {
-        let payload = Self {
+        let payload = BurnPayloadJson {
             value,
             currency,
             reference,
@@ -530,25 +758,52 @@ impl BurnPayload {
         };
 
         payload.validate()?;
-        Ok(payload)
+        payload.try_into()
     }
 
     pub fn validate(&self) -> Result<(), ValidationError> {
-        validate_required_fields(
-            &[
+        BurnPayloadJson::from(self.clone()).validate()
+    }
+}
+
+impl BurnPayloadJson {
+    fn validate(&self) -> Result<(), ValidationError> {
+        FieldValidator::new("BurnPayload")
+            .required_fields(&[
                 ("value", &self.value),
                 ("currency", &self.currency),
                 ("reference", &self.reference),
                 ("chain", &self.chain),
-            ],
-            "BurnPayload",
-        )
+            ])
+            .positive_amount("value", &self.value)
+            .currency("currency", &self.currency)
+            .chain("chain", &self.chain)
+            .finish()
     }
 }
 
-impl From<String> for BurnPayload {
-    fn from(value: String) -> Self {
-        serde_json::from_str(&value).expect("Failed to deserialize BurnPayload from String")
+impl TryFrom<BurnPayloadJson> for BurnPayload {
+    type Error = ValidationError;
+
+    fn try_from(json: BurnPayloadJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: parse_money("BurnPayload", "value", &json.value, &json.currency)?,
+            reference: json.reference,
+            chain: json.chain,
+            message: json.message,
+        })
+    }
+}
+
+impl From<BurnPayload> for BurnPayloadJson {
+    fn from(payload: BurnPayload) -> Self {
+        Self {
+            value: payload.value.amount_string(),
+            currency: payload.value.currency.to_string(),
+            reference: payload.reference,
+            chain: payload.chain,
+            message: payload.message,
+        }
     }
 }
 
@@ -557,3 +812,15 @@ impl From<BurnPayload> for String {
         serde_json::to_string(&val).expect("Failed to serialize BurnPayload to String")
     }
 }
+
+impl_payload_parsing!(
+    PaymentPayload,
+    ChainPaymentPayload,
+    StatusUpdatePayload,
+    CorrectionPayload,
+    TransactionPayload,
+    BankPaymentRequestPayload,
+    UpdateProfilePayload,
+    MintPayload,
+    BurnPayload,
+);
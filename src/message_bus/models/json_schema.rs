@@ -0,0 +1,148 @@
+//! JSON Schema documents generated from the message bus types.
+//!
+//! `mykobo_py` and the TypeScript services validate against these instead of copying the
+//! payload shapes by hand. The envelope schema also carries the instruction/event to payload
+//! pairing enforced by `MessageBusMessage::validate`. Regenerate the committed copies in
+//! `schemas/` with `cargo run --bin export_schemas`.
+
+use super::avro::{avro_schema, AVRO_SCHEMA_FILE_EXTENSION};
+use super::base::{EventType, InstructionType};
+use super::event::*;
+use super::instruction::*;
+use super::message::{MessageBusMessage, MetaData, PayloadKind};
+use super::notification::{CustomerNotificationPayload, PlatformNotificationPayload};
+use schemars::{schema_for, JsonSchema};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const SCHEMA_FILE_EXTENSION: &str = ".schema.json";
+
+fn root_schema<T: JsonSchema>() -> Value {
+    schema_for!(T).to_value()
+}
+
+/// Schema for a single payload struct.
+pub fn payload_schema(kind: PayloadKind) -> Value {
+    match kind {
+        PayloadKind::Payment => root_schema::<PaymentPayload>(),
+        PayloadKind::StatusUpdate => root_schema::<StatusUpdatePayload>(),
+        PayloadKind::Correction => root_schema::<CorrectionPayload>(),
+        PayloadKind::Transaction => root_schema::<TransactionPayload>(),
+        PayloadKind::BankPaymentRequest => root_schema::<BankPaymentRequestPayload>(),
+        PayloadKind::ChainPayment => root_schema::<ChainPaymentPayload>(),
+        PayloadKind::UpdateProfile => root_schema::<UpdateProfilePayload>(),
+        PayloadKind::Mint => root_schema::<MintPayload>(),
+        PayloadKind::Burn => root_schema::<BurnPayload>(),
+        PayloadKind::NewTransaction => root_schema::<NewTransactionEventPayload>(),
+        PayloadKind::TransactionStatus => root_schema::<TransactionStatusEventPayload>(),
+        PayloadKind::PaymentEvent => root_schema::<PaymentEventPayload>(),
+        PayloadKind::BankPayment => root_schema::<BankPaymentEventPayload>(),
+        PayloadKind::Profile => root_schema::<ProfileEventPayload>(),
+        PayloadKind::NewUser => root_schema::<NewUserEventPayload>(),
+        PayloadKind::Kyc => root_schema::<KycEventPayload>(),
+        PayloadKind::PasswordReset => root_schema::<PasswordResetEventPayload>(),
+        PayloadKind::VerificationRequested => root_schema::<VerificationRequestedEventPayload>(),
+        PayloadKind::AddressOnboarded => root_schema::<AddressOnboardedEventPayload>(),
+        PayloadKind::CustomerNotification => root_schema::<CustomerNotificationPayload>(),
+        PayloadKind::PlatformNotification => root_schema::<PlatformNotificationPayload>(),
+    }
+}
+
+pub fn metadata_schema() -> Value {
+    root_schema::<MetaData>()
+}
+
+/// Schema for `MessageBusMessage`, with one `if`/`then` rule per payload kind restricting
+/// `payload` to that struct (or a raw string) for the matching `instruction_type` or `event`.
+pub fn envelope_schema() -> Value {
+    let mut schema = root_schema::<MessageBusMessage>();
+
+    let rules: Vec<Value> = PayloadKind::ALL
+        .iter()
+        .map(|kind| {
+            let instruction_types: Vec<&InstructionType> = InstructionType::ALL
+                .iter()
+                .filter(|t| PayloadKind::for_instruction(t) == Some(*kind))
+                .collect();
+            let events: Vec<&EventType> = EventType::ALL
+                .iter()
+                .filter(|e| PayloadKind::for_event(e) == Some(*kind))
+                .collect();
+
+            let (field, values) = if instruction_types.is_empty() {
+                ("event", json!(events))
+            } else {
+                ("instruction_type", json!(instruction_types))
+            };
+
+            json!({
+                "if": {
+                    "properties": {
+                        "meta_data": {
+                            "properties": { field: { "enum": values } },
+                            "required": [field]
+                        }
+                    }
+                },
+                "then": {
+                    "properties": {
+                        "payload": {
+                            "anyOf": [
+                                { "$ref": format!("#/$defs/{}", kind.type_name()) },
+                                { "type": "string" }
+                            ]
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+
+    schema["allOf"] = Value::Array(rules);
+    schema
+}
+
+/// Every schema document keyed by file name, e.g. `PaymentPayload.schema.json`, plus the Avro
+/// schema `MessageBusMessage.avsc`.
+pub fn export() -> BTreeMap<String, Value> {
+    let mut schemas = BTreeMap::new();
+    schemas.insert(
+        format!("MessageBusMessage{SCHEMA_FILE_EXTENSION}"),
+        envelope_schema(),
+    );
+    schemas.insert(
+        format!("MessageBusMessage{AVRO_SCHEMA_FILE_EXTENSION}"),
+        avro_schema(),
+    );
+    schemas.insert(
+        format!("MetaData{SCHEMA_FILE_EXTENSION}"),
+        metadata_schema(),
+    );
+    for kind in PayloadKind::ALL {
+        schemas.insert(
+            format!("{}{SCHEMA_FILE_EXTENSION}", kind.type_name()),
+            payload_schema(*kind),
+        );
+    }
+    schemas
+}
+
+/// Pretty printed schema with a trailing newline, as written to disk.
+pub fn to_file_contents(schema: &Value) -> String {
+    let mut contents = serde_json::to_string_pretty(schema).expect("schema is valid JSON");
+    contents.push('\n');
+    contents
+}
+
+/// Write every schema document into `dir`, creating it if needed. Returns the paths written.
+pub fn write_schemas(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (file_name, schema) in export() {
+        let path = dir.join(file_name);
+        std::fs::write(&path, to_file_contents(&schema))?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -1,15 +1,22 @@
-use super::base::{validate_required_fields, EventType, InstructionType, ValidationError};
+use super::base::{EventType, InstructionType, ValidationError};
+use super::decode::{DecodeError, StrictDecoder};
 use super::event::*;
 use super::instruction::*;
 use super::notification::{CustomerNotificationPayload, PlatformNotificationPayload};
-use chrono::{SecondsFormat, Utc};
-use serde::{Deserialize, Serialize};
+use super::schema::{SchemaError, SchemaRegistry, DEFAULT_SCHEMA_VERSION, SCHEMA_REGISTRY};
+use super::validation::FieldValidator;
+use chrono::{DateTime, SecondsFormat, Utc};
+use schemars::JsonSchema;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{DeserializeSeed, Error as _, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::{Display, Formatter};
 use uuid::Uuid;
 
 /// Metadata for message bus messages
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[schemars(extend("oneOf" = [{ "required": ["instruction_type"] }, { "required": ["event"] }]))]
 pub struct MetaData {
     pub source: String,
     pub created_at: String,
@@ -18,6 +25,17 @@ pub struct MetaData {
     pub instruction_type: Option<InstructionType>,
     pub event: Option<EventType>,
     pub ip_address: Option<String>,
+    /// Schema version of the payload. Absent means `DEFAULT_SCHEMA_VERSION`.
+    pub schema_version: Option<u32>,
+    /// Shared by every message in a flow, starting with the `idempotency_key` of the first.
+    pub correlation_id: Option<String>,
+    /// `idempotency_key` of the message that caused this one.
+    pub causation_id: Option<String>,
+    /// RFC 3339 time after which consumers drop the message unprocessed.
+    pub expires_at: Option<String>,
+    /// Client domain the message was sent on behalf of.
+    #[serde(alias = "client_domain")]
+    pub tenant: Option<String>,
 }
 
 impl MetaData {
@@ -38,6 +56,11 @@ impl MetaData {
             instruction_type,
             event,
             ip_address,
+            schema_version: None,
+            correlation_id: None,
+            causation_id: None,
+            expires_at: None,
+            tenant: None,
         };
 
         metadata.validate()?;
@@ -46,15 +69,15 @@ impl MetaData {
 
     pub fn validate(&self) -> Result<(), ValidationError> {
         // Validate required base fields
-        validate_required_fields(
-            &[
+        FieldValidator::new("MetaData")
+            .required_fields(&[
                 ("source", &self.source),
                 ("created_at", &self.created_at),
                 ("token", &self.token),
                 ("idempotency_key", &self.idempotency_key),
-            ],
-            "MetaData",
-        )?;
+            ])
+            .optional_timestamp("expires_at", self.expires_at.as_deref())
+            .finish()?;
 
         // Ensure at least one of instruction_type or event is provided
         if self.instruction_type.is_none() && self.event.is_none() {
@@ -74,16 +97,94 @@ impl MetaData {
 
         Ok(())
     }
+
+    /// The payload type implied by `instruction_type` or `event`, or `None` when the type is
+    /// missing or unknown.
+    pub fn payload_kind(&self) -> Option<PayloadKind> {
+        match (&self.instruction_type, &self.event) {
+            (Some(instruction_type), _) => PayloadKind::for_instruction(instruction_type),
+            (None, Some(event)) => PayloadKind::for_event(event),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether `instruction_type` or `event` is a type this version of the crate doesn't know,
+    /// e.g. one added by a newer producer. Such messages decode with a `Payload::Unknown`
+    /// payload, or `Payload::Raw` if the payload is a string.
+    pub fn has_unknown_type(&self) -> bool {
+        self.instruction_type
+            .as_ref()
+            .is_some_and(InstructionType::is_unknown)
+            || self.event.as_ref().is_some_and(EventType::is_unknown)
+    }
+
+    pub fn with_schema_version(mut self, schema_version: u32) -> Self {
+        self.schema_version = Some(schema_version);
+        self
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    pub fn with_causation_id(mut self, causation_id: impl Into<String>) -> Self {
+        self.causation_id = Some(causation_id.into());
+        self
+    }
+
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Mark this message as caused by `parent`: it joins the parent's correlation (or starts
+    /// one from the parent's `idempotency_key`), records the parent as its cause and inherits
+    /// the tenant unless one is already set. Expiry is not inherited.
+    pub fn caused_by(mut self, parent: &MetaData) -> Self {
+        self.correlation_id = Some(
+            parent
+                .correlation_id
+                .clone()
+                .unwrap_or_else(|| parent.idempotency_key.clone()),
+        );
+        self.causation_id = Some(parent.idempotency_key.clone());
+        if self.tenant.is_none() {
+            self.tenant = parent.tenant.clone();
+        }
+        self
+    }
+
+    /// `expires_at` parsed, or `None` when absent or not RFC 3339.
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+            .as_deref()
+            .and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+            .map(|expires_at| expires_at.with_timezone(&Utc))
+    }
+
+    /// Whether the message has expired at `now`. Messages without a readable `expires_at`
+    /// never expire.
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expiry().is_some_and(|expires_at| expires_at <= now)
+    }
 }
 
 /// Enum containing all possible payload types
 ///
-/// Each variant implements `From<String>` for easy conversion from JSON strings:
+/// Each payload struct implements `FromStr` and `TryFrom<&str>`, which deserialize the JSON
+/// and run the payload's `validate()`:
 ///
 /// # Examples
 ///
 /// ```
 /// use mykobo_rs::message_bus::models::instruction::PaymentPayload;
+/// use mykobo_rs::models::Currency;
 ///
 /// let json = r#"{
 ///     "external_reference": "P123",
@@ -94,10 +195,10 @@ impl MetaData {
 ///     "reference": "REF123"
 /// }"#;
 ///
-/// let payload: PaymentPayload = json.to_string().into();
-/// assert_eq!(payload.currency, "EUR");
+/// let payload: PaymentPayload = json.parse().unwrap();
+/// assert_eq!(payload.value.currency, Currency::EUR);
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(untagged)]
 pub enum Payload {
     // Instruction payloads
@@ -129,6 +230,10 @@ pub enum Payload {
 
     // Generic payload
     Raw(String),
+    /// The JSON payload of a message whose type this version of the crate doesn't know.
+    #[serde(skip_deserializing)]
+    #[schemars(skip)]
+    Unknown(serde_json::Value),
 }
 
 impl Display for Payload {
@@ -137,19 +242,264 @@ impl Display for Payload {
     }
 }
 
+impl Payload {
+    /// The kind of a typed payload, or `None` for `Payload::Raw` and `Payload::Unknown`.
+    pub fn kind(&self) -> Option<PayloadKind> {
+        Some(match self {
+            Payload::Payment(_) => PayloadKind::Payment,
+            Payload::StatusUpdate(_) => PayloadKind::StatusUpdate,
+            Payload::Correction(_) => PayloadKind::Correction,
+            Payload::Transaction(_) => PayloadKind::Transaction,
+            Payload::BankPaymentRequest(_) => PayloadKind::BankPaymentRequest,
+            Payload::ChainPayment(_) => PayloadKind::ChainPayment,
+            Payload::UpdateProfile(_) => PayloadKind::UpdateProfile,
+            Payload::Mint(_) => PayloadKind::Mint,
+            Payload::Burn(_) => PayloadKind::Burn,
+            Payload::NewTransaction(_) => PayloadKind::NewTransaction,
+            Payload::TransactionStatus(_) => PayloadKind::TransactionStatus,
+            Payload::PaymentEvent(_) => PayloadKind::PaymentEvent,
+            Payload::BankPayment(_) => PayloadKind::BankPayment,
+            Payload::Profile(_) => PayloadKind::Profile,
+            Payload::NewUser(_) => PayloadKind::NewUser,
+            Payload::Kyc(_) => PayloadKind::Kyc,
+            Payload::PasswordReset(_) => PayloadKind::PasswordReset,
+            Payload::VerificationRequested(_) => PayloadKind::VerificationRequested,
+            Payload::AddressOnboarded(_) => PayloadKind::AddressOnboarded,
+            Payload::CustomerNotification(_) => PayloadKind::CustomerNotification,
+            Payload::PlatformNotification(_) => PayloadKind::PlatformNotification,
+            Payload::Raw(_) | Payload::Unknown(_) => return None,
+        })
+    }
+}
+
+/// The typed variants of `Payload`, without their contents.
+///
+/// Used to pick the payload struct implied by a message's `instruction_type` or `event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PayloadKind {
+    // Instruction payloads
+    Payment,
+    StatusUpdate,
+    Correction,
+    Transaction,
+    BankPaymentRequest,
+    ChainPayment,
+    UpdateProfile,
+    Mint,
+    Burn,
+
+    // Event payloads
+    NewTransaction,
+    TransactionStatus,
+    PaymentEvent,
+    BankPayment,
+    Profile,
+    NewUser,
+    Kyc,
+    PasswordReset,
+    VerificationRequested,
+    AddressOnboarded,
+
+    // Notification payloads
+    CustomerNotification,
+    PlatformNotification,
+}
+
+impl PayloadKind {
+    pub const ALL: &'static [PayloadKind] = &[
+        PayloadKind::Payment,
+        PayloadKind::StatusUpdate,
+        PayloadKind::Correction,
+        PayloadKind::Transaction,
+        PayloadKind::BankPaymentRequest,
+        PayloadKind::ChainPayment,
+        PayloadKind::UpdateProfile,
+        PayloadKind::Mint,
+        PayloadKind::Burn,
+        PayloadKind::NewTransaction,
+        PayloadKind::TransactionStatus,
+        PayloadKind::PaymentEvent,
+        PayloadKind::BankPayment,
+        PayloadKind::Profile,
+        PayloadKind::NewUser,
+        PayloadKind::Kyc,
+        PayloadKind::PasswordReset,
+        PayloadKind::VerificationRequested,
+        PayloadKind::AddressOnboarded,
+        PayloadKind::CustomerNotification,
+        PayloadKind::PlatformNotification,
+    ];
+
+    /// Name of the payload struct, e.g. `TransactionPayload`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PayloadKind::Payment => "PaymentPayload",
+            PayloadKind::StatusUpdate => "StatusUpdatePayload",
+            PayloadKind::Correction => "CorrectionPayload",
+            PayloadKind::Transaction => "TransactionPayload",
+            PayloadKind::BankPaymentRequest => "BankPaymentRequestPayload",
+            PayloadKind::ChainPayment => "ChainPaymentPayload",
+            PayloadKind::UpdateProfile => "UpdateProfilePayload",
+            PayloadKind::Mint => "MintPayload",
+            PayloadKind::Burn => "BurnPayload",
+            PayloadKind::NewTransaction => "NewTransactionEventPayload",
+            PayloadKind::TransactionStatus => "TransactionStatusEventPayload",
+            PayloadKind::PaymentEvent => "PaymentEventPayload",
+            PayloadKind::BankPayment => "BankPaymentEventPayload",
+            PayloadKind::Profile => "ProfileEventPayload",
+            PayloadKind::NewUser => "NewUserEventPayload",
+            PayloadKind::Kyc => "KycEventPayload",
+            PayloadKind::PasswordReset => "PasswordResetEventPayload",
+            PayloadKind::VerificationRequested => "VerificationRequestedEventPayload",
+            PayloadKind::AddressOnboarded => "AddressOnboardedEventPayload",
+            PayloadKind::CustomerNotification => "CustomerNotificationPayload",
+            PayloadKind::PlatformNotification => "PlatformNotificationPayload",
+        }
+    }
+
+    /// `None` for `InstructionType::Unknown`.
+    pub fn for_instruction(instruction_type: &InstructionType) -> Option<Self> {
+        Some(match instruction_type {
+            InstructionType::Payment => PayloadKind::Payment,
+            InstructionType::StatusUpdate => PayloadKind::StatusUpdate,
+            InstructionType::Correction => PayloadKind::Correction,
+            InstructionType::Transaction => PayloadKind::Transaction,
+            InstructionType::BankPaymentRequest => PayloadKind::BankPaymentRequest,
+            InstructionType::ChainPayment => PayloadKind::ChainPayment,
+            InstructionType::UpdateProfile => PayloadKind::UpdateProfile,
+            InstructionType::Mint => PayloadKind::Mint,
+            InstructionType::Burn => PayloadKind::Burn,
+            InstructionType::Unknown(_) => return None,
+        })
+    }
+
+    /// `None` for `EventType::Unknown`.
+    pub fn for_event(event: &EventType) -> Option<Self> {
+        Some(match event {
+            EventType::NewTransaction => PayloadKind::NewTransaction,
+            EventType::TransactionStatusUpdate => PayloadKind::TransactionStatus,
+            EventType::Payment => PayloadKind::PaymentEvent,
+            EventType::BankPayment => PayloadKind::BankPayment,
+            EventType::NewProfile => PayloadKind::Profile,
+            EventType::NewUser => PayloadKind::NewUser,
+            EventType::KycEvent => PayloadKind::Kyc,
+            EventType::PasswordResetRequested => PayloadKind::PasswordReset,
+            EventType::VerificationRequested => PayloadKind::VerificationRequested,
+            EventType::AddressOnboarded => PayloadKind::AddressOnboarded,
+            EventType::RelayInitiated
+            | EventType::RelayCompleted
+            | EventType::RelayOnboarded
+            | EventType::MintCompleted
+            | EventType::BurnCompleted
+            | EventType::MintHeld
+            | EventType::BurnHeld
+            | EventType::DepositInitiated
+            | EventType::DepositCompleted
+            | EventType::DepositFailed
+            | EventType::WithdrawInitiated
+            | EventType::WithdrawCompleted
+            | EventType::WithdrawFailed
+            | EventType::CustomerFundsReceived => PayloadKind::CustomerNotification,
+            EventType::RelayStuckDepositing
+            | EventType::RelayStuckBridging
+            | EventType::RelayStuckForwarding
+            | EventType::RelayFailed
+            | EventType::CircleApi5xxBurst
+            | EventType::WebhookReprocessorBacklog
+            | EventType::MintHeldAlert
+            | EventType::BurnHeldAlert
+            | EventType::CustomerNotifyFailed
+            | EventType::MintInfo
+            | EventType::BurnInfo
+            | EventType::BankPaymentBalanceInsufficientAlert
+            | EventType::BankPaymentExecutionFailedAlert
+            | EventType::BcbWebhookProcessingFailedAlert
+            | EventType::BeneficiaryCreationFailedAlert
+            | EventType::TransactionFailedAlert
+            | EventType::TransactionHeldAlert
+            | EventType::TransactionFundedInfo
+            | EventType::BankPaymentReceivedInfo
+            | EventType::BankPaymentSentInfo
+            | EventType::OnchainPaymentReceivedInfo
+            | EventType::OnchainPaymentSentInfo
+            | EventType::TransactionApprovedInfo
+            | EventType::TransactionFulfilledInfo => PayloadKind::PlatformNotification,
+            EventType::Unknown(_) => return None,
+        })
+    }
+
+    /// Deserialize a JSON payload as this kind's payload struct.
+    pub fn decode(&self, value: serde_json::Value) -> Result<Payload, serde_json::Error> {
+        self.deserialize_payload(value)
+    }
+
+    /// Deserialize this kind's payload struct straight from `deserializer`.
+    pub fn deserialize_payload<'de, D>(&self, deserializer: D) -> Result<Payload, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let d = deserializer;
+        Ok(match self {
+            PayloadKind::Payment => Payload::Payment(Deserialize::deserialize(d)?),
+            PayloadKind::StatusUpdate => Payload::StatusUpdate(Deserialize::deserialize(d)?),
+            PayloadKind::Correction => Payload::Correction(Deserialize::deserialize(d)?),
+            PayloadKind::Transaction => Payload::Transaction(Deserialize::deserialize(d)?),
+            PayloadKind::BankPaymentRequest => {
+                Payload::BankPaymentRequest(Deserialize::deserialize(d)?)
+            }
+            PayloadKind::ChainPayment => Payload::ChainPayment(Deserialize::deserialize(d)?),
+            PayloadKind::UpdateProfile => Payload::UpdateProfile(Deserialize::deserialize(d)?),
+            PayloadKind::Mint => Payload::Mint(Deserialize::deserialize(d)?),
+            PayloadKind::Burn => Payload::Burn(Deserialize::deserialize(d)?),
+            PayloadKind::NewTransaction => Payload::NewTransaction(Deserialize::deserialize(d)?),
+            PayloadKind::TransactionStatus => {
+                Payload::TransactionStatus(Deserialize::deserialize(d)?)
+            }
+            PayloadKind::PaymentEvent => Payload::PaymentEvent(Deserialize::deserialize(d)?),
+            PayloadKind::BankPayment => Payload::BankPayment(Deserialize::deserialize(d)?),
+            PayloadKind::Profile => Payload::Profile(Deserialize::deserialize(d)?),
+            PayloadKind::NewUser => Payload::NewUser(Deserialize::deserialize(d)?),
+            PayloadKind::Kyc => Payload::Kyc(Deserialize::deserialize(d)?),
+            PayloadKind::PasswordReset => Payload::PasswordReset(Deserialize::deserialize(d)?),
+            PayloadKind::VerificationRequested => {
+                Payload::VerificationRequested(Deserialize::deserialize(d)?)
+            }
+            PayloadKind::AddressOnboarded => {
+                Payload::AddressOnboarded(Deserialize::deserialize(d)?)
+            }
+            PayloadKind::CustomerNotification => {
+                Payload::CustomerNotification(Deserialize::deserialize(d)?)
+            }
+            PayloadKind::PlatformNotification => {
+                Payload::PlatformNotification(Deserialize::deserialize(d)?)
+            }
+        })
+    }
+}
+
 /// Complete message bus message structure
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq)]
 pub struct MessageBusMessage {
     pub meta_data: MetaData,
     pub payload: Payload,
 }
 
 impl MessageBusMessage {
+    /// Build a message, stamping `schema_version` from `SCHEMA_REGISTRY` when the payload's
+    /// current version is past `DEFAULT_SCHEMA_VERSION`. Version 1 messages omit the field so
+    /// they stay byte-identical to messages written before versioning existed.
     pub fn new(meta_data: MetaData, payload: Payload) -> Result<Self, ValidationError> {
-        let message = Self {
-            meta_data: meta_data.clone(),
-            payload: payload.clone(),
-        };
+        let mut meta_data = meta_data;
+        if meta_data.schema_version.is_none() {
+            if let Some(kind) = payload.kind() {
+                let current_version = SCHEMA_REGISTRY.current_version(kind);
+                if current_version > DEFAULT_SCHEMA_VERSION {
+                    meta_data.schema_version = Some(current_version);
+                }
+            }
+        }
+
+        let message = Self { meta_data, payload };
 
         message.validate()?;
         Ok(message)
@@ -164,82 +514,34 @@ impl MessageBusMessage {
         }
 
         // Validate that the payload type matches the instruction_type or event
-        if let Some(instruction_type) = &self.meta_data.instruction_type {
-            match (instruction_type, &self.payload) {
-                (InstructionType::Payment, Payload::Payment(_)) => Ok(()),
-                (InstructionType::StatusUpdate, Payload::StatusUpdate(_)) => Ok(()),
-                (InstructionType::Correction, Payload::Correction(_)) => Ok(()),
-                (InstructionType::Transaction, Payload::Transaction(_)) => Ok(()),
-                (InstructionType::BankPaymentRequest, Payload::BankPaymentRequest(_)) => Ok(()),
-                (InstructionType::ChainPayment, Payload::ChainPayment(_)) => Ok(()),
-                (InstructionType::UpdateProfile, Payload::UpdateProfile(_)) => Ok(()),
-                (InstructionType::Mint, Payload::Mint(_)) => Ok(()),
-                (InstructionType::Burn, Payload::Burn(_)) => Ok(()),
-                _ => Err(ValidationError {
+        let message_type = match (&self.meta_data.instruction_type, &self.meta_data.event) {
+            (Some(instruction_type), _) => instruction_type.to_string(),
+            (None, Some(event)) => event.to_string(),
+            (None, None) => {
+                return Err(ValidationError {
                     class_name: "MessageBusMessage".to_string(),
-                    fields: vec![format!(
-                        "message type {}: {} requires matching payload type",
-                        instruction_type, instruction_type
-                    )],
-                }),
+                    fields: vec!["either instruction_type or event must be provided".to_string()],
+                })
             }
-        } else if let Some(event) = &self.meta_data.event {
-            match (event, &self.payload) {
-                (EventType::NewTransaction, Payload::NewTransaction(_)) => Ok(()),
-                (EventType::TransactionStatusUpdate, Payload::TransactionStatus(_)) => Ok(()),
-                (EventType::Payment, Payload::PaymentEvent(_)) => Ok(()),
-                (EventType::BankPayment, Payload::BankPayment(_)) => Ok(()),
-                (EventType::NewProfile, Payload::Profile(_)) => Ok(()),
-                (EventType::NewUser, Payload::NewUser(_)) => Ok(()),
-                (EventType::KycEvent, Payload::Kyc(_)) => Ok(()),
-                (EventType::PasswordResetRequested, Payload::PasswordReset(_)) => Ok(()),
-                (EventType::VerificationRequested, Payload::VerificationRequested(_)) => Ok(()),
-                (EventType::AddressOnboarded, Payload::AddressOnboarded(_)) => Ok(()),
-                (
-                    EventType::RelayInitiated
-                    | EventType::RelayCompleted
-                    | EventType::RelayOnboarded
-                    | EventType::MintCompleted
-                    | EventType::BurnCompleted
-                    | EventType::MintHeld
-                    | EventType::BurnHeld,
-                    Payload::CustomerNotification(_),
-                ) => Ok(()),
-                (
-                    EventType::RelayStuckDepositing
-                    | EventType::RelayStuckBridging
-                    | EventType::RelayStuckForwarding
-                    | EventType::RelayFailed
-                    | EventType::CircleApi5xxBurst
-                    | EventType::WebhookReprocessorBacklog
-                    | EventType::MintHeldAlert
-                    | EventType::BurnHeldAlert
-                    | EventType::CustomerNotifyFailed
-                    | EventType::MintInfo
-                    | EventType::BurnInfo
-                    | EventType::TransactionFailedAlert
-                    | EventType::TransactionHeldAlert,
-                    Payload::PlatformNotification(_),
-                ) => Ok(()),
-                _ => Err(ValidationError {
-                    class_name: "MessageBusMessage".to_string(),
-                    fields: vec![format!(
-                        "message type {}: {} requires matching payload type",
-                        event, event
-                    )],
-                }),
-            }
-        } else {
-            Err(ValidationError {
+        };
+
+        if self.payload.kind() != self.meta_data.payload_kind() {
+            return Err(ValidationError {
                 class_name: "MessageBusMessage".to_string(),
-                fields: vec!["either instruction_type or event must be provided".to_string()],
-            })
-        }?;
+                fields: vec![format!(
+                    "message type {}: {} requires matching payload type",
+                    message_type, message_type
+                )],
+            });
+        }
 
         Ok(())
     }
 
     /// Convenience function to create a complete MessageBusMessage
+    ///
+    /// `MessageBusMessage::instruction` and `MessageBusMessage::event` build the same message
+    /// with the message type taken from the payload.
     pub fn create(
         source: String,
         payload: Payload,
@@ -277,156 +579,278 @@ impl From<MessageBusMessage> for String {
 impl<'de> Deserialize<'de> for MessageBusMessage {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        D: serde::Deserializer<'de>,
+        D: Deserializer<'de>,
     {
-        use serde::de::Error;
+        deserializer.deserialize_struct(
+            "MessageBusMessage",
+            MESSAGE_FIELDS,
+            MessageVisitor {
+                schemas: &SCHEMA_REGISTRY,
+            },
+        )
+    }
+}
 
-        // Deserialize into a generic JSON Value first
-        let mut value = serde_json::Value::deserialize(deserializer)?;
+impl MessageBusMessage {
+    /// Decode a message from JSON, upcasting payloads written with an older schema version
+    /// using the upcasters in `schemas`.
+    ///
+    /// The `Deserialize` implementation does the same with the built-in `SCHEMA_REGISTRY`.
+    pub fn from_value(
+        value: serde_json::Value,
+        schemas: &SchemaRegistry,
+    ) -> Result<Self, serde_json::Error> {
+        value.deserialize_struct(
+            "MessageBusMessage",
+            MESSAGE_FIELDS,
+            MessageVisitor { schemas },
+        )
+    }
+
+    /// `from_value` straight from JSON bytes, without building a `serde_json::Value` first.
+    pub fn from_slice(json: &[u8], schemas: &SchemaRegistry) -> Result<Self, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_slice(json);
+        let message = deserializer.deserialize_struct(
+            "MessageBusMessage",
+            MESSAGE_FIELDS,
+            MessageVisitor { schemas },
+        )?;
+        deserializer.end()?;
+        Ok(message)
+    }
+
+    /// Decoding path for `StrictDecoder`, which needs the payload as a `Value` to check its
+    /// fields.
+    pub(crate) fn decode(
+        mut value: serde_json::Value,
+        schemas: &SchemaRegistry,
+        strict: &StrictDecoder,
+    ) -> Result<Self, DecodeError> {
+        use serde::de::Error;
 
         // Extract metadata to determine payload type
-        let meta_data: MetaData = serde_json::from_value(
+        let mut meta_data: MetaData = serde_json::from_value(
             value
-                .get("meta_data")
-                .ok_or_else(|| D::Error::missing_field("meta_data"))?
-                .clone(),
-        )
-        .map_err(D::Error::custom)?;
+                .get_mut("meta_data")
+                .ok_or_else(|| serde_json::Error::missing_field("meta_data"))?
+                .take(),
+        )?;
 
         // Get the payload JSON value
         let payload_value = value
             .get_mut("payload")
-            .ok_or_else(|| D::Error::missing_field("payload"))?
+            .ok_or_else(|| serde_json::Error::missing_field("payload"))?
             .take();
 
-        // Deserialize payload based on instruction_type or event
-        let payload: Payload = if let Some(instruction_type) = &meta_data.instruction_type {
-            // First check if it's a raw string payload
-            if let Ok(raw_string) = serde_json::from_value::<String>(payload_value.clone()) {
-                Payload::Raw(raw_string)
-            } else {
-                // Otherwise deserialize according to instruction type
-                match instruction_type {
-                    InstructionType::Payment => Payload::Payment(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::StatusUpdate => Payload::StatusUpdate(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::Correction => Payload::Correction(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::Transaction => Payload::Transaction(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::BankPaymentRequest => Payload::BankPaymentRequest(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::ChainPayment => Payload::ChainPayment(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::UpdateProfile => Payload::UpdateProfile(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::Mint => Payload::Mint(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    InstructionType::Burn => Payload::Burn(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
+        // A JSON string is always a raw payload, whatever the type hint says
+        if let serde_json::Value::String(raw_string) = payload_value {
+            strict.check_raw(&meta_data)?;
+            return Ok(MessageBusMessage {
+                meta_data,
+                payload: Payload::Raw(raw_string),
+            });
+        }
+
+        let payload = match meta_data.payload_kind() {
+            Some(kind) => {
+                let (payload_value, schema_version) =
+                    schemas.upcast(kind, meta_data.schema_version, payload_value)?;
+                meta_data.schema_version = schema_version;
+                strict.check_fields(kind, &payload_value)?;
+                kind.decode(payload_value)?
+            }
+            // A type added by a newer producer keeps its payload as raw JSON
+            None if meta_data.has_unknown_type() => {
+                strict.check_raw(&meta_data)?;
+                match payload_value {
+                    serde_json::Value::String(raw_string) => Payload::Raw(raw_string),
+                    value => Payload::Unknown(value),
                 }
             }
-        } else if let Some(event) = &meta_data.event {
-            // First check if it's a raw string payload
-            if let Ok(raw_string) = serde_json::from_value::<String>(payload_value.clone()) {
-                Payload::Raw(raw_string)
-            } else {
-                // Otherwise deserialize according to event type
-                match event {
-                    EventType::NewTransaction => Payload::NewTransaction(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::TransactionStatusUpdate => Payload::TransactionStatus(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::Payment => Payload::PaymentEvent(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::BankPayment => Payload::BankPayment(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::NewProfile => Payload::Profile(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::NewUser => Payload::NewUser(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::KycEvent => Payload::Kyc(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::PasswordResetRequested => Payload::PasswordReset(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::VerificationRequested => Payload::VerificationRequested(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::AddressOnboarded => Payload::AddressOnboarded(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::RelayInitiated
-                    | EventType::RelayCompleted
-                    | EventType::RelayOnboarded
-                    | EventType::MintCompleted
-                    | EventType::BurnCompleted
-                    | EventType::MintHeld
-                    | EventType::BurnHeld
-                    | EventType::DepositInitiated
-                    | EventType::DepositCompleted
-                    | EventType::DepositFailed
-                    | EventType::WithdrawInitiated
-                    | EventType::WithdrawCompleted
-                    | EventType::WithdrawFailed
-                    | EventType::CustomerFundsReceived => Payload::CustomerNotification(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
-                    EventType::RelayStuckDepositing
-                    | EventType::RelayStuckBridging
-                    | EventType::RelayStuckForwarding
-                    | EventType::RelayFailed
-                    | EventType::CircleApi5xxBurst
-                    | EventType::WebhookReprocessorBacklog
-                    | EventType::MintHeldAlert
-                    | EventType::BurnHeldAlert
-                    | EventType::CustomerNotifyFailed
-                    | EventType::MintInfo
-                    | EventType::BurnInfo
-                    | EventType::BankPaymentBalanceInsufficientAlert
-                    | EventType::BankPaymentExecutionFailedAlert
-                    | EventType::BcbWebhookProcessingFailedAlert
-                    | EventType::BeneficiaryCreationFailedAlert
-                    | EventType::TransactionFailedAlert
-                    | EventType::TransactionHeldAlert
-                    | EventType::TransactionFundedInfo
-                    | EventType::BankPaymentReceivedInfo
-                    | EventType::BankPaymentSentInfo
-                    | EventType::OnchainPaymentReceivedInfo
-                    | EventType::OnchainPaymentSentInfo
-                    | EventType::TransactionApprovedInfo
-                    | EventType::TransactionFulfilledInfo => Payload::PlatformNotification(
-                        serde_json::from_value(payload_value).map_err(D::Error::custom)?,
-                    ),
+            None => return Err(DecodeError::MissingTypeHint),
+        };
+
+        Ok(MessageBusMessage { meta_data, payload })
+    }
+
+    /// Upcast the payload of a message's JSON in place with the upcasters in `schemas`,
+    /// recording the new `schema_version` in its `meta_data`.
+    ///
+    /// Lets messages decoded through `Deserialize`, which only knows the built-in
+    /// `SCHEMA_REGISTRY`, see payloads migrated by upcasters registered elsewhere. JSON whose
+    /// metadata can't be read is left for deserialization to reject.
+    pub fn upcast_json(
+        value: &mut serde_json::Value,
+        schemas: &SchemaRegistry,
+    ) -> Result<(), SchemaError> {
+        let Some(meta_data) = value
+            .get("meta_data")
+            .and_then(|meta_data| MetaData::deserialize(meta_data).ok())
+        else {
+            return Ok(());
+        };
+        let Some(kind) = meta_data.payload_kind() else {
+            return Ok(());
+        };
+        if !schemas.needs_upcast(kind, meta_data.schema_version) {
+            return Ok(());
+        }
+        let Some(payload) = value
+            .get_mut("payload")
+            .filter(|payload| !payload.is_string())
+        else {
+            return Ok(());
+        };
+
+        let (upcast, schema_version) =
+            schemas.upcast(kind, meta_data.schema_version, payload.take())?;
+        *payload = upcast;
+        value["meta_data"]["schema_version"] = schema_version.into();
+        Ok(())
+    }
+}
+
+const MESSAGE_FIELDS: &[&str] = &["meta_data", "payload"];
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum MessageField {
+    MetaData,
+    Payload,
+    #[serde(other)]
+    Other,
+}
+
+// Reads `meta_data` and then deserializes `payload` straight into the struct it names, in a
+// single pass over the input. A `payload` that comes before `meta_data` is buffered.
+struct MessageVisitor<'a> {
+    schemas: &'a SchemaRegistry,
+}
+
+impl<'de> Visitor<'de> for MessageVisitor<'_> {
+    type Value = MessageBusMessage;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a message with meta_data and payload")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<MessageBusMessage, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut meta_data: Option<MetaData> = None;
+        let mut payload: Option<Payload> = None;
+        let mut buffered: Option<serde_json::Value> = None;
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                MessageField::MetaData if meta_data.is_some() => {
+                    return Err(A::Error::duplicate_field("meta_data"));
+                }
+                MessageField::MetaData => meta_data = Some(map.next_value()?),
+                MessageField::Payload if payload.is_some() || buffered.is_some() => {
+                    return Err(A::Error::duplicate_field("payload"));
+                }
+                MessageField::Payload => match meta_data.as_mut() {
+                    Some(meta_data) => {
+                        payload = Some(map.next_value_seed(PayloadSeed {
+                            meta_data,
+                            schemas: self.schemas,
+                        })?)
+                    }
+                    None => buffered = Some(map.next_value()?),
+                },
+                MessageField::Other => {
+                    map.next_value::<IgnoredAny>()?;
                 }
             }
-        } else {
-            // Try to deserialize as Raw payload if no type hint
-            if let Ok(raw_string) = serde_json::from_value::<String>(payload_value.clone()) {
-                Payload::Raw(raw_string)
-            } else {
-                // Fall back to untagged deserialization
-                serde_json::from_value(payload_value).map_err(D::Error::custom)?
+        }
+
+        let mut meta_data = meta_data.ok_or_else(|| A::Error::missing_field("meta_data"))?;
+        let payload = match (payload, buffered) {
+            (Some(payload), _) => payload,
+            (None, Some(value)) => PayloadSeed {
+                meta_data: &mut meta_data,
+                schemas: self.schemas,
             }
+            .deserialize(value)
+            .map_err(A::Error::custom)?,
+            (None, None) => return Err(A::Error::missing_field("payload")),
         };
 
         Ok(MessageBusMessage { meta_data, payload })
     }
 }
+
+// Picks the payload struct from `meta_data`. Only payloads that need upcasting or have an
+// unknown type go through a `serde_json::Value`.
+struct PayloadSeed<'a> {
+    meta_data: &'a mut MetaData,
+    schemas: &'a SchemaRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for PayloadSeed<'_> {
+    type Value = Payload;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Payload, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let schema_version = self.meta_data.schema_version;
+        match self.meta_data.payload_kind() {
+            Some(kind) if !self.schemas.needs_upcast(kind, schema_version) => {
+                deserializer.deserialize_any(TypedPayloadVisitor { kind })
+            }
+            Some(kind) => {
+                // A JSON string is always a raw payload, whatever the type hint says
+                let value = match serde_json::Value::deserialize(deserializer)? {
+                    serde_json::Value::String(raw_string) => return Ok(Payload::Raw(raw_string)),
+                    value => value,
+                };
+                let (value, schema_version) = self
+                    .schemas
+                    .upcast(kind, schema_version, value)
+                    .map_err(D::Error::custom)?;
+                self.meta_data.schema_version = schema_version;
+                kind.decode(value).map_err(D::Error::custom)
+            }
+            // A type added by a newer producer keeps its payload as raw JSON
+            None if self.meta_data.has_unknown_type() => {
+                Ok(match serde_json::Value::deserialize(deserializer)? {
+                    serde_json::Value::String(raw_string) => Payload::Raw(raw_string),
+                    value => Payload::Unknown(value),
+                })
+            }
+            // Fall back to untagged deserialization if no type hint
+            None => Payload::deserialize(deserializer),
+        }
+    }
+}
+
+struct TypedPayloadVisitor {
+    kind: PayloadKind,
+}
+
+impl<'de> Visitor<'de> for TypedPayloadVisitor {
+    type Value = Payload;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a {} or a raw string", self.kind.type_name())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, raw_string: &str) -> Result<Payload, E> {
+        Ok(Payload::Raw(raw_string.to_string()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, raw_string: String) -> Result<Payload, E> {
+        Ok(Payload::Raw(raw_string))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Payload, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.kind
+            .deserialize_payload(MapAccessDeserializer::new(map))
+    }
+}
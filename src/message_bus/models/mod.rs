@@ -1,14 +1,34 @@
+pub mod avro;
 pub mod base;
+pub mod builder;
+pub mod claim_check;
+pub mod cloud_event;
+pub mod decode;
+pub mod encryption;
 pub mod event;
 pub mod instruction;
+pub mod json_schema;
 pub mod message;
 pub mod notification;
+pub mod redaction;
+pub mod schema;
+pub mod validation;
+pub mod wire;
 
 // Re-export commonly used types
-pub use base::{EventType, InstructionType, TransactionType, ValidationError};
+pub use base::{EventType, InstructionType, PayloadParseError, TransactionType, ValidationError};
+pub use builder::{EventPayload, InstructionPayload, MessageBuilder, TypedPayload};
+pub use claim_check::{BlobStore, ClaimCheck, ClaimCheckError, FileBlobStore};
+pub use cloud_event::{CloudEvent, CloudEventError};
+pub use decode::{DecodeError, StrictDecoder};
+pub use encryption::{EncryptionError, FieldEncryptor};
 pub use event::*;
 pub use instruction::*;
-pub use message::{MessageBusMessage, MetaData, Payload};
+pub use message::{MessageBusMessage, MetaData, Payload, PayloadKind};
 pub use notification::{
     CustomerNotificationPayload, NotificationSubject, PlatformNotificationPayload, Severity,
 };
+pub use redaction::{Redact, SensitiveField, Sensitivity, Unredacted, REDACTED};
+pub use schema::{SchemaError, SchemaRegistry, DEFAULT_SCHEMA_VERSION, SCHEMA_REGISTRY};
+pub use validation::FieldValidator;
+pub use wire::{AvroCodec, FileSchemaRegistry, SchemaRegistryClient, WireFormat};
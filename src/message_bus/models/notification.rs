@@ -3,13 +3,14 @@
 /// Mirrors `mykobo_py.message_bus.models.notification`.
 /// Severity, Subjects, and NotificationPayloads are registered against the
 /// notification EventType variants in the Payload enum (message.rs).
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Importance gradient for PlatformNotifications.
 ///
 /// Serializes as lowercase strings: `"info"`, `"warning"`, `"critical"`.
 /// Ordered: Info < Warning < Critical.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Info,
@@ -21,7 +22,7 @@ pub enum Severity {
 ///
 /// Serialized with an internal `type` tag (lowercase variant name).
 /// Field order within each variant matches the Python declaration order.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum NotificationSubject {
     Relay {
@@ -41,7 +42,7 @@ pub enum NotificationSubject {
 ///
 /// Carries a typed subject reference and a fully-rendered template-data dict.
 /// Field order: `subject` then `data` (matching Python declaration order).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct CustomerNotificationPayload {
     pub subject: NotificationSubject,
     pub data: serde_json::Value,
@@ -54,7 +55,7 @@ pub struct CustomerNotificationPayload {
 /// Field order: `severity`, `data`, `subject` (matching Python declaration order).
 /// `subject: None` is omitted from JSON (mirrors Python `exclude_none=True`).
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PlatformNotificationPayload {
     pub severity: Severity,
     pub data: serde_json::Value,
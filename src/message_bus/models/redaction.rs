@@ -0,0 +1,252 @@
+//! Redaction of secrets and personal data in `Debug` and log output.
+//!
+//! Each payload type lists its sensitive fields in the tables below. Types with sensitive
+//! fields get a `Debug` implementation that prints `"[REDACTED]"` in their place, so messages
+//! can be logged with `{:?}` or `{}` without leaking tokens, passwords, names or emails.
+//! Call `unredacted()` to opt in to the full content.
+
+use super::event::*;
+use super::instruction::*;
+use super::message::{MessageBusMessage, MetaData, Payload, PayloadKind};
+use super::notification::{CustomerNotificationPayload, PlatformNotificationPayload};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sensitivity {
+    /// Credentials such as tokens and passwords.
+    Secret,
+    /// Personal data such as names, emails, addresses and account numbers.
+    Pii,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensitiveField {
+    pub name: &'static str,
+    pub sensitivity: Sensitivity,
+}
+
+/// A type whose serialized form can be logged with its sensitive fields masked.
+pub trait Redact: Serialize {
+    /// Sensitive fields by serialized name.
+    const SENSITIVE_FIELDS: &'static [SensitiveField];
+
+    /// The serialized form with every non-null sensitive field replaced by `REDACTED`.
+    fn redacted_value(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            for field in Self::SENSITIVE_FIELDS {
+                if let Some(v) = map.get_mut(field.name).filter(|v| !v.is_null()) {
+                    *v = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        value
+    }
+
+    /// Opt in to printing the full, unredacted content.
+    fn unredacted(&self) -> Unredacted<'_, Self>
+    where
+        Self: Sized,
+    {
+        Unredacted(self)
+    }
+}
+
+/// Prints the wrapped value as JSON without redaction. Returned by `Redact::unredacted`.
+pub struct Unredacted<'a, T>(&'a T);
+
+impl<T: Serialize> fmt::Display for Unredacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self.0).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl<T: Serialize> fmt::Debug for Unredacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Writes a JSON value as-is inside a `debug_struct`
+struct JsonDebug<'a>(&'a Value);
+
+impl fmt::Debug for JsonDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub(crate) fn debug_redacted(name: &str, value: &Value, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut debug = f.debug_struct(name);
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            debug.field(key, &JsonDebug(value));
+        }
+    }
+    debug.finish()
+}
+
+macro_rules! sensitive_fields {
+    ($($type:ident { $($field:ident: $sensitivity:ident),* $(,)? }),+ $(,)?) => {
+        $(
+            impl Redact for $type {
+                const SENSITIVE_FIELDS: &'static [SensitiveField] = &[
+                    $(SensitiveField {
+                        name: stringify!($field),
+                        sensitivity: Sensitivity::$sensitivity,
+                    }),*
+                ];
+            }
+        )+
+    };
+}
+
+macro_rules! redacted_debug {
+    ($($type:ident),+ $(,)?) => {
+        $(
+            impl fmt::Debug for $type {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    debug_redacted(stringify!($type), &self.redacted_value(), f)
+                }
+            }
+        )+
+    };
+}
+
+sensitive_fields! {
+    MetaData { token: Secret, ip_address: Pii },
+    PaymentPayload { payer_name: Pii, bank_account_number: Pii },
+    StatusUpdatePayload {},
+    CorrectionPayload {},
+    TransactionPayload { first_name: Pii, last_name: Pii, payer: Pii, payee: Pii },
+    BankPaymentRequestPayload {},
+    ChainPaymentPayload {},
+    UpdateProfilePayload {
+        address_line_1: Pii,
+        address_line_2: Pii,
+        bank_account_number: Pii,
+        bank_number: Pii,
+        tax_id: Pii,
+    },
+    MintPayload {},
+    BurnPayload {},
+    NewTransactionEventPayload {},
+    TransactionStatusEventPayload {},
+    PaymentEventPayload {},
+    BankPaymentEventPayload {},
+    ProfileEventPayload {},
+    NewUserEventPayload {},
+    KycEventPayload {},
+    PasswordResetEventPayload { to: Pii, password: Secret },
+    VerificationRequestedEventPayload { to: Pii },
+    AddressOnboardedEventPayload { email: Pii, payload: Pii },
+    CustomerNotificationPayload { data: Pii },
+    PlatformNotificationPayload {},
+}
+
+redacted_debug! {
+    MetaData,
+    PaymentPayload,
+    TransactionPayload,
+    UpdateProfilePayload,
+    PasswordResetEventPayload,
+    VerificationRequestedEventPayload,
+    AddressOnboardedEventPayload,
+    CustomerNotificationPayload,
+}
+
+// The sensitive fields of the struct a `Payload` variant wraps
+fn fields_of<T: Redact>(_variant: fn(T) -> Payload) -> &'static [SensitiveField] {
+    T::SENSITIVE_FIELDS
+}
+
+macro_rules! payload_redaction {
+    ($($variant:ident),+ $(,)?) => {
+        impl Redact for Payload {
+            /// Fields depend on the variant; see `redacted_value`.
+            const SENSITIVE_FIELDS: &'static [SensitiveField] = &[];
+
+            /// Redacts the variant's sensitive fields. `Raw` and `Unknown` payloads can't be
+            /// inspected, so they are redacted entirely.
+            fn redacted_value(&self) -> Value {
+                match self {
+                    $(Payload::$variant(payload) => payload.redacted_value(),)+
+                    Payload::Raw(_) | Payload::Unknown(_) => Value::String(REDACTED.to_string()),
+                }
+            }
+        }
+
+        impl PayloadKind {
+            /// The `Redact::SENSITIVE_FIELDS` of this kind's payload struct.
+            pub fn sensitive_fields(&self) -> &'static [SensitiveField] {
+                match self {
+                    $(PayloadKind::$variant => fields_of(Payload::$variant),)+
+                }
+            }
+        }
+
+        impl fmt::Debug for Payload {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(Payload::$variant(payload) => {
+                        f.debug_tuple(stringify!($variant)).field(payload).finish()
+                    })+
+                    Payload::Raw(_) => f.debug_tuple("Raw").field(&REDACTED).finish(),
+                    Payload::Unknown(_) => f.debug_tuple("Unknown").field(&REDACTED).finish(),
+                }
+            }
+        }
+    };
+}
+
+payload_redaction! {
+    Payment,
+    StatusUpdate,
+    Correction,
+    Transaction,
+    BankPaymentRequest,
+    ChainPayment,
+    UpdateProfile,
+    Mint,
+    Burn,
+    NewTransaction,
+    TransactionStatus,
+    PaymentEvent,
+    BankPayment,
+    Profile,
+    NewUser,
+    Kyc,
+    PasswordReset,
+    VerificationRequested,
+    AddressOnboarded,
+    CustomerNotification,
+    PlatformNotification,
+}
+
+impl Redact for MessageBusMessage {
+    const SENSITIVE_FIELDS: &'static [SensitiveField] = &[];
+
+    fn redacted_value(&self) -> Value {
+        serde_json::json!({
+            "meta_data": self.meta_data.redacted_value(),
+            "payload": self.payload.redacted_value(),
+        })
+    }
+}
+
+/// Untyped JSON has no field tables to go by, so anything but `null` is redacted whole.
+impl Redact for Value {
+    const SENSITIVE_FIELDS: &'static [SensitiveField] = &[];
+
+    fn redacted_value(&self) -> Value {
+        match self {
+            Value::Null => Value::Null,
+            _ => Value::String(REDACTED.to_string()),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Payload schema versions and the upcasters that migrate older payload JSON.
+//!
+//! A message's `meta_data.schema_version` records the version of the payload shape it was
+//! written with; a missing version means `DEFAULT_SCHEMA_VERSION`. When a payload struct
+//! changes shape, bump its version here and register an upcaster from the previous version.
+//! Decoding then runs every upcaster from the message's version up to the current one before
+//! the payload is deserialised into its struct.
+
+use super::message::PayloadKind;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+pub const DEFAULT_SCHEMA_VERSION: u32 = 1;
+
+/// Migrates a payload from one schema version to the next.
+pub type Upcaster = fn(Value) -> Result<Value, String>;
+
+/// Schema versions of the payloads defined in this crate.
+pub static SCHEMA_REGISTRY: Lazy<SchemaRegistry> = Lazy::new(SchemaRegistry::new);
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SchemaError {
+    #[error("{payload}: schema version must be at least {DEFAULT_SCHEMA_VERSION}, got {version}")]
+    InvalidVersion { payload: &'static str, version: u32 },
+    #[error("{payload}: no upcaster registered from schema version {from_version}")]
+    MissingUpcaster {
+        payload: &'static str,
+        from_version: u32,
+    },
+    #[error("{payload}: upcasting from schema version {from_version} failed: {reason}")]
+    Upcast {
+        payload: &'static str,
+        from_version: u32,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct PayloadSchema {
+    current_version: u32,
+    // Keyed by the version each upcaster migrates from
+    upcasters: BTreeMap<u32, Upcaster>,
+}
+
+impl Default for PayloadSchema {
+    fn default() -> Self {
+        Self {
+            current_version: DEFAULT_SCHEMA_VERSION,
+            upcasters: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaRegistry {
+    schemas: HashMap<PayloadKind, PayloadSchema>,
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchemaRegistry {
+    /// A registry with every payload at `DEFAULT_SCHEMA_VERSION` and no upcasters.
+    pub fn new() -> Self {
+        Self {
+            schemas: PayloadKind::ALL
+                .iter()
+                .map(|kind| (*kind, PayloadSchema::default()))
+                .collect(),
+        }
+    }
+
+    /// Set the current schema version of a payload.
+    pub fn register(&mut self, kind: PayloadKind, current_version: u32) -> Result<(), SchemaError> {
+        if current_version < DEFAULT_SCHEMA_VERSION {
+            return Err(SchemaError::InvalidVersion {
+                payload: kind.type_name(),
+                version: current_version,
+            });
+        }
+        self.schemas.entry(kind).or_default().current_version = current_version;
+        Ok(())
+    }
+
+    /// Register the upcaster migrating a payload from `from_version` to `from_version + 1`.
+    pub fn register_upcaster(
+        &mut self,
+        kind: PayloadKind,
+        from_version: u32,
+        upcaster: Upcaster,
+    ) -> Result<(), SchemaError> {
+        if from_version < DEFAULT_SCHEMA_VERSION {
+            return Err(SchemaError::InvalidVersion {
+                payload: kind.type_name(),
+                version: from_version,
+            });
+        }
+        self.schemas
+            .entry(kind)
+            .or_default()
+            .upcasters
+            .insert(from_version, upcaster);
+        Ok(())
+    }
+
+    pub fn current_version(&self, kind: PayloadKind) -> u32 {
+        self.schemas
+            .get(&kind)
+            .map(|schema| schema.current_version)
+            .unwrap_or(DEFAULT_SCHEMA_VERSION)
+    }
+
+    /// Check that every payload has an upcaster for each version below its current one.
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        for kind in PayloadKind::ALL {
+            let Some(schema) = self.schemas.get(kind) else {
+                continue;
+            };
+            for from_version in DEFAULT_SCHEMA_VERSION..schema.current_version {
+                if !schema.upcasters.contains_key(&from_version) {
+                    return Err(SchemaError::MissingUpcaster {
+                        payload: kind.type_name(),
+                        from_version,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a `kind` payload written at `version` is older than its current schema version.
+    pub fn needs_upcast(&self, kind: PayloadKind, version: Option<u32>) -> bool {
+        version.unwrap_or(DEFAULT_SCHEMA_VERSION) < self.current_version(kind)
+    }
+
+    /// Migrate `payload` from `version` to the current schema version of `kind`.
+    ///
+    /// Returns the migrated payload and the version to record in metadata: the current version
+    /// if any upcaster ran, otherwise `version` unchanged. Payloads already at or past the
+    /// current version are returned as they are.
+    pub fn upcast(
+        &self,
+        kind: PayloadKind,
+        version: Option<u32>,
+        payload: Value,
+    ) -> Result<(Value, Option<u32>), SchemaError> {
+        let from_version = version.unwrap_or(DEFAULT_SCHEMA_VERSION);
+        let Some(schema) = self.schemas.get(&kind) else {
+            return Ok((payload, version));
+        };
+        if from_version >= schema.current_version {
+            return Ok((payload, version));
+        }
+
+        let mut payload = payload;
+        for step in from_version..schema.current_version {
+            let upcaster = schema
+                .upcasters
+                .get(&step)
+                .ok_or(SchemaError::MissingUpcaster {
+                    payload: kind.type_name(),
+                    from_version: step,
+                })?;
+            payload = upcaster(payload).map_err(|reason| SchemaError::Upcast {
+                payload: kind.type_name(),
+                from_version: step,
+                reason,
+            })?;
+        }
+
+        Ok((payload, Some(schema.current_version)))
+    }
+}
@@ -0,0 +1,208 @@
+//! Field level checks shared by the payload `validate()` methods.
+//!
+//! `FieldValidator` collects every violation for a payload so that `ValidationError.fields`
+//! reports them all at once. Missing required fields are listed by name, as
+//! `validate_required_fields` does, or as `"<field> (<reason>)"` when only required in some
+//! cases; other violations read `"<field>: <reason>"`.
+//!
+//! Format checks skip values encrypted by `FieldEncryptor`, so a message whose sensitive fields
+//! are still encrypted validates.
+
+use super::base::ValidationError;
+use super::encryption::FieldEncryptor;
+use crate::models::money::{parse_amount, Currency, Money, MoneyError};
+use bigdecimal::{BigDecimal, Zero};
+use chrono::DateTime;
+
+pub use crate::models::money::{CRYPTO_ASSETS, ISO_4217_CURRENCIES};
+
+/// Chains the platform settles on. Compared case-insensitively.
+pub const KNOWN_CHAINS: &[&str] = &[
+    "stellar",
+    "solana",
+    "ethereum",
+    "base",
+    "polygon",
+    "arbitrum",
+    "optimism",
+    "avalanche",
+];
+
+pub fn is_known_currency(code: &str) -> bool {
+    Currency::from_code(code).is_some()
+}
+
+pub fn is_known_chain(chain: &str) -> bool {
+    KNOWN_CHAINS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(chain))
+}
+
+/// A deliberately loose check: one `@`, a non-empty local part and a dotted domain, no spaces.
+pub fn is_plausible_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+pub struct FieldValidator {
+    class_name: &'static str,
+    fields: Vec<String>,
+}
+
+impl FieldValidator {
+    pub fn new(class_name: &'static str) -> Self {
+        Self {
+            class_name,
+            fields: Vec::new(),
+        }
+    }
+
+    fn violation(&mut self, field: &str, reason: impl std::fmt::Display) {
+        self.fields.push(format!("{field}: {reason}"));
+    }
+
+    /// Record a violation that is not tied to a single well-formed check.
+    pub fn invalid(&mut self, field: &str, reason: &str) -> &mut Self {
+        self.violation(field, reason);
+        self
+    }
+
+    /// Non-empty after trimming. Later checks skip empty values so they are reported once.
+    pub fn required(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            self.fields.push(field.to_string());
+        }
+        self
+    }
+
+    /// A field missing where `reason` requires it, e.g. a payer on a deposit.
+    pub fn missing(&mut self, field: &str, reason: &str) -> &mut Self {
+        self.fields.push(format!("{field} ({reason})"));
+        self
+    }
+
+    /// `required` for each `(name, value)` pair, in order.
+    pub fn required_fields(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        for (field, value) in fields {
+            self.required(field, value);
+        }
+        self
+    }
+
+    /// A well-formed decimal greater than zero.
+    pub fn positive_amount(&mut self, field: &str, value: &str) -> &mut Self {
+        match parse_decimal(value) {
+            Some(Ok(amount)) if amount <= BigDecimal::zero() => {
+                self.violation(field, format!("must be greater than zero, got {value:?}"))
+            }
+            Some(Err(_)) => self.violation(field, format!("must be a decimal, got {value:?}")),
+            _ => {}
+        }
+        self
+    }
+
+    /// A well-formed decimal that is zero or more, e.g. a fee.
+    pub fn non_negative_amount(&mut self, field: &str, value: &str) -> &mut Self {
+        match parse_decimal(value) {
+            Some(Ok(amount)) if amount < BigDecimal::zero() => {
+                self.violation(field, format!("must not be negative, got {value:?}"))
+            }
+            Some(Err(_)) => self.violation(field, format!("must be a decimal, got {value:?}")),
+            _ => {}
+        }
+        self
+    }
+
+    /// A well-formed decimal of either sign, e.g. a correction.
+    pub fn decimal(&mut self, field: &str, value: &str) -> &mut Self {
+        if let Some(Err(_)) = parse_decimal(value) {
+            self.violation(field, format!("must be a decimal, got {value:?}"));
+        }
+        self
+    }
+
+    /// An ISO 4217 code or a known crypto asset, in upper case.
+    pub fn currency(&mut self, field: &str, value: &str) -> &mut Self {
+        if is_checkable(value) && !is_known_currency(value) {
+            self.violation(
+                field,
+                format!("must be an ISO 4217 code or known crypto asset, got {value:?}"),
+            );
+        }
+        self
+    }
+
+    pub fn chain(&mut self, field: &str, value: &str) -> &mut Self {
+        if is_checkable(value) && !is_known_chain(value) {
+            self.violation(field, format!("must be a known chain, got {value:?}"));
+        }
+        self
+    }
+
+    pub fn timestamp(&mut self, field: &str, value: &str) -> &mut Self {
+        if is_checkable(value) && DateTime::parse_from_rfc3339(value).is_err() {
+            self.violation(
+                field,
+                format!("must be an RFC 3339 timestamp, got {value:?}"),
+            );
+        }
+        self
+    }
+
+    pub fn optional_timestamp(&mut self, field: &str, value: Option<&str>) -> &mut Self {
+        match value {
+            Some(value) => self.timestamp(field, value),
+            None => self,
+        }
+    }
+
+    pub fn email(&mut self, field: &str, value: &str) -> &mut Self {
+        if is_checkable(value) && !is_plausible_email(value) {
+            self.violation(field, format!("must be an email address, got {value:?}"));
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Result<(), ValidationError> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        Err(ValidationError {
+            class_name: self.class_name.to_string(),
+            fields: std::mem::take(&mut self.fields),
+        })
+    }
+}
+
+/// Parse the amount and currency strings of a payload into its `Money` field, reporting a
+/// malformed one against `field`.
+pub fn parse_money(
+    class_name: &'static str,
+    field: &str,
+    amount: &str,
+    currency: &str,
+) -> Result<Money, ValidationError> {
+    Money::parse(amount, currency).map_err(|e| ValidationError {
+        class_name: class_name.to_string(),
+        fields: vec![format!("{field}: {e}")],
+    })
+}
+
+// Empty values are reported by `required`, encrypted ones can't be read
+fn is_checkable(value: &str) -> bool {
+    !value.trim().is_empty() && !FieldEncryptor::is_encrypted(value)
+}
+
+// None for values that aren't checkable
+fn parse_decimal(value: &str) -> Option<Result<BigDecimal, MoneyError>> {
+    if !is_checkable(value) {
+        return None;
+    }
+    Some(parse_amount(value))
+}
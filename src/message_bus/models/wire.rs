@@ -0,0 +1,290 @@
+//! Wire formats of the message bus and the schema registry used by the Avro format.
+//!
+//! Producers set the `content-type` header to the format they wrote, and consumers decode
+//! by it; messages without the header are JSON. Avro messages use the Confluent framing: a
+//! zero magic byte, the writer schema's registry id as a big-endian `u32`, then the Avro datum.
+
+use super::avro::{avro_schema, decode_datum, encode_datum, AvroError};
+pub use super::cloud_event::JSON_CONTENT_TYPE;
+use super::cloud_event::KAFKA_CONTENT_TYPE_HEADER;
+use apache_avro::Schema;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const CONTENT_TYPE_HEADER: &str = KAFKA_CONTENT_TYPE_HEADER;
+pub const AVRO_CONTENT_TYPE: &str = "application/avro";
+
+/// Registry subject the message schema is registered under.
+pub const AVRO_SUBJECT: &str = "com.mykobo.bus.MessageBusMessage";
+
+const MAGIC_BYTE: u8 = 0;
+const HEADER_LENGTH: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    Avro,
+}
+
+impl WireFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WireFormat::Json => JSON_CONTENT_TYPE,
+            WireFormat::Avro => AVRO_CONTENT_TYPE,
+        }
+    }
+
+    /// The format named by a message's `content-type` header, JSON if there is none.
+    pub fn from_headers(headers: &HashMap<String, String>) -> Result<Self, WireError> {
+        match headers.get(CONTENT_TYPE_HEADER).map(String::as_str) {
+            None | Some(JSON_CONTENT_TYPE) => Ok(WireFormat::Json),
+            Some(AVRO_CONTENT_TYPE) => Ok(WireFormat::Avro),
+            Some(other) => Err(WireError::UnsupportedContentType(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    #[error("schema id {0} is not registered")]
+    NotFound(u32),
+    #[error("schema registry unavailable: {0}")]
+    Unavailable(String),
+    #[error("invalid schema: {0}")]
+    InvalidSchema(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WireError {
+    #[error("unsupported content type {0:?}")]
+    UnsupportedContentType(String),
+    #[error("malformed Avro message: {0}")]
+    Framing(String),
+    #[error(transparent)]
+    Avro(#[from] AvroError),
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Client of a schema registry holding the Avro schemas messages are written with.
+///
+/// `FileSchemaRegistry` is a local stand-in; a client for a hosted registry implements the
+/// same two calls.
+pub trait SchemaRegistryClient: Send + Sync {
+    /// Register `schema` under `subject` and return its id. Registering a schema that is
+    /// already registered under the subject returns the existing id.
+    fn register<'a>(
+        &'a self,
+        subject: &'a str,
+        schema: &'a str,
+    ) -> BoxFuture<'a, Result<u32, RegistryError>>;
+
+    /// The schema registered with `id`.
+    fn schema(&self, id: u32) -> BoxFuture<'_, Result<String, RegistryError>>;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    schemas: Vec<RegisteredSchema>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegisteredSchema {
+    id: u32,
+    subject: String,
+    version: u32,
+    schema: String,
+}
+
+/// A schema registry kept in a single JSON file, for local development and tests.
+///
+/// Ids and per-subject versions count up from 1. The file is created on the first
+/// registration. It is not safe to share between processes.
+pub struct FileSchemaRegistry {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSchemaRegistry {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<RegistryFile, RegistryError> {
+        match std::fs::read(&self.path) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .map_err(|e| RegistryError::Unavailable(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(RegistryFile::default()),
+            Err(e) => Err(RegistryError::Unavailable(e.to_string())),
+        }
+    }
+
+    fn register_schema(&self, subject: &str, schema: &str) -> Result<u32, RegistryError> {
+        let canonical = canonical_form(schema)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = self.read()?;
+
+        for registered in &file.schemas {
+            if registered.subject == subject
+                && canonical_form(&registered.schema).ok().as_ref() == Some(&canonical)
+            {
+                return Ok(registered.id);
+            }
+        }
+
+        let id = file.schemas.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        let version = file
+            .schemas
+            .iter()
+            .filter(|s| s.subject == subject)
+            .map(|s| s.version)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        file.schemas.push(RegisteredSchema {
+            id,
+            subject: subject.to_string(),
+            version,
+            schema: schema.to_string(),
+        });
+
+        let contents = serde_json::to_vec_pretty(&file)
+            .map_err(|e| RegistryError::Unavailable(e.to_string()))?;
+        std::fs::write(&self.path, contents)
+            .map_err(|e| RegistryError::Unavailable(e.to_string()))?;
+        Ok(id)
+    }
+
+    fn find_schema(&self, id: u32) -> Result<String, RegistryError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.read()?
+            .schemas
+            .into_iter()
+            .find(|s| s.id == id)
+            .map(|s| s.schema)
+            .ok_or(RegistryError::NotFound(id))
+    }
+}
+
+impl SchemaRegistryClient for FileSchemaRegistry {
+    fn register<'a>(
+        &'a self,
+        subject: &'a str,
+        schema: &'a str,
+    ) -> BoxFuture<'a, Result<u32, RegistryError>> {
+        Box::pin(async move { self.register_schema(subject, schema) })
+    }
+
+    fn schema(&self, id: u32) -> BoxFuture<'_, Result<String, RegistryError>> {
+        Box::pin(async move { self.find_schema(id) })
+    }
+}
+
+fn canonical_form(schema: &str) -> Result<String, RegistryError> {
+    Schema::parse_str(schema)
+        .map(|schema| schema.canonical_form())
+        .map_err(|e| RegistryError::InvalidSchema(e.to_string()))
+}
+
+/// Converts messages between their JSON form and framed Avro.
+///
+/// The message schema is registered on the first `encode`. Writer schemas fetched while
+/// decoding are cached by id, so the registry is asked once per schema.
+pub struct AvroCodec {
+    registry: Arc<dyn SchemaRegistryClient>,
+    subject: String,
+    schema_id: Mutex<Option<u32>>,
+    writer_schemas: Mutex<HashMap<u32, Arc<Schema>>>,
+}
+
+impl AvroCodec {
+    pub fn new(registry: Arc<dyn SchemaRegistryClient>) -> Self {
+        Self {
+            registry,
+            subject: AVRO_SUBJECT.to_string(),
+            schema_id: Mutex::new(None),
+            writer_schemas: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register the message schema under `subject` instead of `AVRO_SUBJECT`.
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
+    /// The registry id of the message schema, registering it if needed.
+    pub async fn schema_id(&self) -> Result<u32, WireError> {
+        if let Some(id) = *self.schema_id.lock().unwrap_or_else(|e| e.into_inner()) {
+            return Ok(id);
+        }
+        let schema = avro_schema().to_string();
+        let id = self.registry.register(&self.subject, &schema).await?;
+        *self.schema_id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id);
+        Ok(id)
+    }
+
+    /// Encode a JSON message as framed Avro.
+    pub async fn encode(&self, json: &[u8]) -> Result<Vec<u8>, WireError> {
+        let message: Value = serde_json::from_slice(json)?;
+        let datum = encode_datum(&message)?;
+        let id = self.schema_id().await?;
+
+        let mut framed = Vec::with_capacity(HEADER_LENGTH + datum.len());
+        framed.push(MAGIC_BYTE);
+        framed.extend_from_slice(&id.to_be_bytes());
+        framed.extend_from_slice(&datum);
+        Ok(framed)
+    }
+
+    /// Decode framed Avro into a JSON message.
+    pub async fn decode(&self, framed: &[u8]) -> Result<Vec<u8>, WireError> {
+        if framed.len() < HEADER_LENGTH || framed[0] != MAGIC_BYTE {
+            return Err(WireError::Framing(
+                "missing magic byte and schema id".to_string(),
+            ));
+        }
+        let id = u32::from_be_bytes([framed[1], framed[2], framed[3], framed[4]]);
+        let writer = self.writer_schema(id).await?;
+        let message = decode_datum(&framed[HEADER_LENGTH..], &writer)?;
+        Ok(serde_json::to_vec(&message)?)
+    }
+
+    /// Bring a message of either format into its JSON form.
+    pub async fn to_json(&self, format: WireFormat, payload: &[u8]) -> Result<Vec<u8>, WireError> {
+        match format {
+            WireFormat::Json => Ok(payload.to_vec()),
+            WireFormat::Avro => self.decode(payload).await,
+        }
+    }
+
+    async fn writer_schema(&self, id: u32) -> Result<Arc<Schema>, WireError> {
+        if let Some(schema) = self
+            .writer_schemas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&id)
+        {
+            return Ok(schema.clone());
+        }
+        let schema = self.registry.schema(id).await?;
+        let schema = Arc::new(
+            Schema::parse_str(&schema).map_err(|e| RegistryError::InvalidSchema(e.to_string()))?,
+        );
+        self.writer_schemas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, schema.clone());
+        Ok(schema)
+    }
+}
@@ -88,6 +88,45 @@ pub enum KafkaError {
 
     #[error("Connection timeout: {0}")]
     Timeout(String),
+
+    #[error("Failed to subscribe to topics: {0}")]
+    Subscription(String),
+
+    #[error("Failed to reposition consumer: {0}")]
+    Seek(String),
+
+    #[error("Topic administration failed: {0}")]
+    Admin(String),
+
+    #[error("Topic configuration drift: {0}")]
+    TopicDrift(String),
+
+    #[error("Metrics error: {0}")]
+    Metrics(String),
+
+    #[error("Message rejected by strict decoding: {0}")]
+    StrictDecoding(String),
+
+    #[error("Message payload could not be upcast: {0}")]
+    Upcast(String),
+
+    #[error("Message could not be authorised: {0}")]
+    Authorisation(String),
+
+    #[error("Message signature rejected: {0}")]
+    Signature(String),
+
+    #[error("Message field encryption failed: {0}")]
+    Encryption(String),
+
+    #[error("Message wire encoding failed: {0}")]
+    Encoding(String),
+
+    #[error("Message claim check failed: {0}")]
+    ClaimCheck(String),
+
+    #[error("Message could not be routed: {0}")]
+    Routing(String),
 }
 
 pub type KafkaResult<T> = Result<T, KafkaError>;
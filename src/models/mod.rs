@@ -1,9 +1,11 @@
 pub mod error;
+pub mod money;
 
 use serde::Serialize;
 
 // Re-export commonly used error types
 pub use error::{KafkaError, KafkaResult, MykoboStatusCode, ServiceError};
+pub use money::{Currency, Money, MoneyError, RoundingMode};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AuthError {
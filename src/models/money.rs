@@ -0,0 +1,273 @@
+//! Monetary amounts shared by the message bus, anchor and ledger models.
+//!
+//! Payloads keep carrying amounts as decimal strings on the wire and parse them into `Money`
+//! fields, the form services should do arithmetic and rounding with. `amount_string` is what
+//! they write back.
+
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, Signed, Zero};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+pub use bigdecimal::RoundingMode;
+
+/// Crypto assets accepted wherever a currency is expected.
+pub const CRYPTO_ASSETS: &[&str] = &["USDC", "EURC", "USDT", "XLM", "SOL", "ETH", "BTC"];
+
+/// Active ISO 4217 currency codes.
+pub const ISO_4217_CURRENCIES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD",
+    "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ",
+    "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD", "JOD",
+    "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR",
+    "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR",
+    "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN",
+    "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR",
+    "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS",
+    "VES", "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWL",
+];
+
+// ISO 4217 currencies without the usual two minor units
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &[
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "VND", "VUV",
+    "XAF", "XOF", "XPF",
+];
+const THREE_DECIMAL_CURRENCIES: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+// Precision of the on-chain asset
+const CRYPTO_MINOR_UNITS: &[(&str, u32)] = &[
+    ("USDC", 6),
+    ("EURC", 6),
+    ("USDT", 6),
+    ("XLM", 7),
+    ("SOL", 9),
+    ("ETH", 18),
+    ("BTC", 8),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MoneyError {
+    #[error("invalid amount {0:?}, expected a plain decimal such as \"100.00\"")]
+    InvalidAmount(String),
+    #[error("unknown currency {0:?}")]
+    UnknownCurrency(String),
+    #[error("currency mismatch: {left} and {right}")]
+    CurrencyMismatch { left: Currency, right: Currency },
+    #[error("division by zero")]
+    DivisionByZero,
+}
+
+/// An ISO 4217 currency or a supported crypto asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: &'static str,
+    minor_units: u32,
+}
+
+impl Currency {
+    pub const EUR: Currency = Currency::define("EUR", 2);
+    pub const USD: Currency = Currency::define("USD", 2);
+    pub const GBP: Currency = Currency::define("GBP", 2);
+    pub const USDC: Currency = Currency::define("USDC", 6);
+    pub const EURC: Currency = Currency::define("EURC", 6);
+
+    const fn define(code: &'static str, minor_units: u32) -> Self {
+        Self { code, minor_units }
+    }
+
+    /// Look up an upper case currency code, e.g. `EUR` or `USDC`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        if let Some(code) = ISO_4217_CURRENCIES.iter().find(|c| **c == code) {
+            let minor_units = if ZERO_DECIMAL_CURRENCIES.contains(code) {
+                0
+            } else if THREE_DECIMAL_CURRENCIES.contains(code) {
+                3
+            } else {
+                2
+            };
+            return Some(Self::define(code, minor_units));
+        }
+        CRYPTO_MINOR_UNITS
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(code, minor_units)| Self::define(code, *minor_units))
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Number of decimal places amounts in this currency are rounded to.
+    pub fn minor_units(&self) -> u32 {
+        self.minor_units
+    }
+
+    pub fn is_crypto(&self) -> bool {
+        CRYPTO_ASSETS.contains(&self.code)
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code)
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::from_code(code).ok_or_else(|| MoneyError::UnknownCurrency(code.to_string()))
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code)
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(D::Error::custom)
+    }
+}
+
+/// An amount in a currency. Arithmetic is only defined between amounts in the same currency.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub amount: BigDecimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: BigDecimal, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(BigDecimal::zero(), currency)
+    }
+
+    /// Parse the `value` and `currency` strings carried by the payloads.
+    pub fn parse(amount: &str, currency: &str) -> Result<Self, MoneyError> {
+        Ok(Self::new(parse_amount(amount)?, currency.parse()?))
+    }
+
+    /// Build an amount from an integer count of minor units, e.g. cents.
+    pub fn from_minor_units(units: i64, currency: Currency) -> Self {
+        Self::new(
+            BigDecimal::new(BigInt::from(units), currency.minor_units as i64),
+            currency,
+        )
+    }
+
+    /// The amount as an integer count of minor units, rounding any extra precision with `mode`.
+    pub fn to_minor_units(&self, mode: RoundingMode) -> BigInt {
+        let (units, _) = self.round(mode).amount.into_bigint_and_exponent();
+        units
+    }
+
+    /// Round to the currency's minor units.
+    pub fn round(&self, mode: RoundingMode) -> Self {
+        self.round_to(self.currency.minor_units, mode)
+    }
+
+    /// Round to an explicit number of decimal places, e.g. for display.
+    pub fn round_to(&self, decimal_places: u32, mode: RoundingMode) -> Self {
+        Self::new(
+            self.amount.with_scale_round(decimal_places as i64, mode),
+            self.currency,
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.amount.is_positive()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_negative()
+    }
+
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.same_currency(other)?;
+        Ok(Self::new(&self.amount + &other.amount, self.currency))
+    }
+
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.same_currency(other)?;
+        Ok(Self::new(&self.amount - &other.amount, self.currency))
+    }
+
+    /// Multiply by a rate or quantity. The result is not rounded.
+    pub fn mul(&self, factor: &BigDecimal) -> Money {
+        Self::new(&self.amount * factor, self.currency)
+    }
+
+    /// Divide by a rate or quantity, rounding the result to the currency's minor units.
+    pub fn checked_div(
+        &self,
+        divisor: &BigDecimal,
+        mode: RoundingMode,
+    ) -> Result<Money, MoneyError> {
+        if divisor.is_zero() {
+            return Err(MoneyError::DivisionByZero);
+        }
+        Ok(Self::new(&self.amount / divisor, self.currency).round(mode))
+    }
+
+    /// Compare two amounts, failing if the currencies differ.
+    pub fn checked_cmp(&self, other: &Money) -> Result<Ordering, MoneyError> {
+        self.same_currency(other)?;
+        Ok(self.amount.cmp(&other.amount))
+    }
+
+    /// The amount as a plain decimal string, as carried in payload `value` fields.
+    pub fn amount_string(&self) -> String {
+        self.amount.to_plain_string()
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(MoneyError::CurrencyMismatch {
+                left: self.currency,
+                right: other.currency,
+            })
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount_string(), self.currency)
+    }
+}
+
+/// Parse a plain decimal string such as `"100.00"` or `"-1.5"`.
+///
+/// `BigDecimal` alone also accepts exponents (`"1e3"`); amounts on the wire never use them,
+/// so they are rejected along with surrounding whitespace.
+pub fn parse_amount(value: &str) -> Result<BigDecimal, MoneyError> {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .enumerate()
+            .all(|(i, c)| c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+')));
+    if !plain {
+        return Err(MoneyError::InvalidAmount(value.to_string()));
+    }
+    BigDecimal::from_str(value).map_err(|_| MoneyError::InvalidAmount(value.to_string()))
+}
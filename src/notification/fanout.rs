@@ -0,0 +1,220 @@
+//! Registry-driven notification fan-out.
+//!
+//! `NotificationFanOut` turns domain events into the notification events the notification
+//! registry says they fire, so producers don't each hand-code the rules in `registry.yaml`.
+//! Audience and severity come from the registry; each notification is caused by the domain
+//! event and keyed with `IdempotencyKey::for_event` on the domain event's idempotency key, so
+//! redelivered domain events produce the same idempotency keys and consumers drop the
+//! duplicates, while distinct domain events about the same subject don't collide.
+
+use crate::message_bus::kafka::models::IncomingMessage;
+use crate::message_bus::kafka::routing::RoutedProducer;
+use crate::message_bus::models::{
+    CustomerNotificationPayload, EventType, MessageBuilder, MessageBusMessage, NotificationSubject,
+    Payload, PlatformNotificationPayload, Severity, ValidationError,
+};
+use crate::models::error::KafkaError;
+use crate::notification::IdempotencyKey;
+use crate::notification_contract::{self, Audience, Entry, Registry, REGISTRY};
+use log::{info, warn};
+use serde_json::Value;
+use tokio::sync::mpsc::Receiver;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FanOutError {
+    #[error("{0} payload has no `reference` or `identifier` to use as the notification subject")]
+    NoSubject(String),
+    #[error("{0} is not a notification in the registry")]
+    NotANotification(String),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+}
+
+/// What a fan-out run did with the messages it received.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FanOutReport {
+    /// Domain events evaluated against the registry.
+    pub domain_events: u64,
+    /// Notifications published.
+    pub published: u64,
+    /// Instructions and notification events, which fire nothing.
+    pub skipped: u64,
+    /// Domain events whose notifications could not be built, so none were published.
+    pub failed: u64,
+}
+
+/// Builds and publishes the notifications fired by domain events.
+///
+/// The subject of each notification is taken from the domain payload: its `reference` makes
+/// a transaction subject and its `identifier` a profile subject. The domain payload becomes the
+/// notification's `data`.
+pub struct NotificationFanOut {
+    producer: String,
+    token: String,
+    registry: Registry,
+}
+
+impl NotificationFanOut {
+    /// `producer` names the service in the notifications' `source` and idempotency keys, e.g.
+    /// `ledger`; `token` is the service token they carry.
+    pub fn new(producer: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            producer: producer.into(),
+            token: token.into(),
+            registry: REGISTRY.clone(),
+        }
+    }
+
+    /// Evaluate `registry` instead of the built-in `REGISTRY`.
+    pub fn with_registry(mut self, registry: Registry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Whether `message` is a domain event, i.e. one the registry may fan out.
+    pub fn is_domain_event(&self, message: &MessageBusMessage) -> bool {
+        message.meta_data.event.as_ref().is_some_and(|event| {
+            matches!(self.registry.entries.get(event), Some(Entry::Domain { .. }))
+        })
+    }
+
+    /// The notifications `message` fires, in registry order. Empty for anything but a domain
+    /// event, and for domain events none of whose rules match.
+    pub fn fan_out(
+        &self,
+        message: &MessageBusMessage,
+    ) -> Result<Vec<MessageBusMessage>, FanOutError> {
+        let Some(event) = message.meta_data.event.clone() else {
+            return Ok(Vec::new());
+        };
+        let payload = serde_json::to_value(&message.payload)?;
+        let fired = self.registry.notifications_for(event.clone(), &payload);
+        if fired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let subject = subject_of(&payload)
+            .ok_or_else(|| FanOutError::NoSubject(event.as_str().to_string()))?;
+        fired
+            .into_iter()
+            .map(|notification| {
+                let payload = self.payload_for(&notification, &subject, &payload)?;
+                let key = IdempotencyKey::for_event(
+                    &self.producer,
+                    notification.clone(),
+                    &message.meta_data.idempotency_key,
+                );
+                Ok(MessageBuilder::for_event(notification, payload)
+                    .source(&self.producer)
+                    .token(&self.token)
+                    .caused_by(&message.meta_data)
+                    .idempotency_key(key)
+                    .build()?)
+            })
+            .collect()
+    }
+
+    fn payload_for(
+        &self,
+        notification: &EventType,
+        subject: &NotificationSubject,
+        data: &Value,
+    ) -> Result<Payload, FanOutError> {
+        let not_a_notification = || FanOutError::NotANotification(notification.as_str().into());
+        let audience = self
+            .registry
+            .audience_of(notification.clone())
+            .ok_or_else(not_a_notification)?;
+        if audience == Audience::Customer {
+            return Ok(Payload::CustomerNotification(CustomerNotificationPayload {
+                subject: subject.clone(),
+                data: data.clone(),
+            }));
+        }
+
+        let severity = match self
+            .registry
+            .severity_of(notification.clone())
+            .ok_or_else(not_a_notification)?
+        {
+            notification_contract::Severity::Info => Severity::Info,
+            notification_contract::Severity::Warning => Severity::Warning,
+            notification_contract::Severity::Critical => Severity::Critical,
+        };
+        Ok(Payload::PlatformNotification(PlatformNotificationPayload {
+            severity,
+            data: data.clone(),
+            subject: Some(platform_subject(subject)),
+        }))
+    }
+
+    /// Fan out `message` and publish its notifications through `producer`. Returns how many
+    /// were published.
+    pub async fn publish(
+        &self,
+        message: &MessageBusMessage,
+        producer: &RoutedProducer,
+    ) -> Result<usize, FanOutError> {
+        let notifications = self.fan_out(message)?;
+        for notification in &notifications {
+            producer.send(notification).await?;
+        }
+        Ok(notifications.len())
+    }
+
+    /// Fan out everything received on `receiver` until its senders are dropped, e.g. when the
+    /// consumer feeding it stops. Domain events whose notifications can't be built are logged
+    /// and counted; a failed send stops the run.
+    pub async fn run(
+        &self,
+        mut receiver: Receiver<IncomingMessage<MessageBusMessage>>,
+        producer: &RoutedProducer,
+    ) -> Result<FanOutReport, FanOutError> {
+        let mut report = FanOutReport::default();
+        while let Some(incoming) = receiver.recv().await {
+            let message = incoming.payload;
+            if !self.is_domain_event(&message) {
+                report.skipped += 1;
+                continue;
+            }
+            report.domain_events += 1;
+            match self.publish(&message, producer).await {
+                Ok(published) => report.published += published as u64,
+                Err(FanOutError::Kafka(e)) => return Err(FanOutError::Kafka(e)),
+                Err(e) => {
+                    warn!("Not fanning out {}: {e}", message.meta_data.idempotency_key);
+                    report.failed += 1;
+                }
+            }
+        }
+        info!("Notification fan-out finished: {report:?}");
+        Ok(report)
+    }
+}
+
+fn subject_of(payload: &Value) -> Option<NotificationSubject> {
+    let field = |name: &str| {
+        payload
+            .get(name)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(reference) = field("reference") {
+        return Some(NotificationSubject::Transaction { reference });
+    }
+    field("identifier").map(|user_id| NotificationSubject::Profile { user_id })
+}
+
+/// The free-form platform subject, in the `relay:abc-123` style.
+fn platform_subject(subject: &NotificationSubject) -> String {
+    match subject {
+        NotificationSubject::Relay { id, .. } => format!("relay:{id}"),
+        NotificationSubject::Transaction { reference } => format!("transaction:{reference}"),
+        NotificationSubject::Profile { user_id } => format!("profile:{user_id}"),
+    }
+}
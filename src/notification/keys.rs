@@ -1,4 +1,4 @@
-use crate::message_bus::models::base::EventType;
+use crate::message_bus::models::base::{EventType, InstructionType};
 use chrono::{DateTime, Duration, TimeZone, Utc};
 
 pub struct IdempotencyKey;
@@ -14,6 +14,18 @@ impl IdempotencyKey {
         format!("{}:{}:{}", producer, event_lower, subject_id)
     }
 
+    /// Build a deterministic idempotency key for an instruction with a subject.
+    ///
+    /// Format: `{producer}:{instruction_lower}:{subject_id}`
+    pub fn for_instruction(
+        producer: &str,
+        instruction_type: InstructionType,
+        subject_id: &str,
+    ) -> String {
+        let instruction_lower = instruction_type.to_string().to_lowercase();
+        format!("{}:{}:{}", producer, instruction_lower, subject_id)
+    }
+
     /// Build a deterministic idempotency key for an event bucketed by time window.
     ///
     /// This is useful for rate-limiting or batching notifications that occur multiple times
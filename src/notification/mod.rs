@@ -1,5 +1,7 @@
 pub mod keys;
 pub mod events;
+pub mod fanout;
 
 pub use keys::IdempotencyKey;
 pub use events::NOTIFICATION_EVENTS;
+pub use fanout::{FanOutError, FanOutReport, NotificationFanOut};
@@ -32,7 +32,7 @@ pub enum VariantKind { Domain, Notification }
 #[serde(rename_all = "lowercase")]
 pub enum Audience { Customer, Platform }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity { Info, Warning, Critical }
 
@@ -77,7 +77,7 @@ impl Registry {
             let event = EventType::try_from(name.as_str()).map_err(|_| {
                 RegistryError::Msg(format!("YAML key {name:?} does not resolve to an EventType variant"))
             })?;
-            parsed.insert(event, parse_entry(&event, raw_entry)?);
+            parsed.insert(event.clone(), parse_entry(&event, raw_entry)?);
         }
 
         // Phase 2: enum coverage.
@@ -142,7 +142,7 @@ impl Registry {
                         None => true,
                         Some(p) => p.matches(payload).unwrap_or(false),
                     };
-                    if matches { out.extend(rule.fires.iter().copied()); }
+                    if matches { out.extend(rule.fires.iter().cloned()); }
                 }
                 out
             }
@@ -247,7 +247,7 @@ fn parse_notification(event: &EventType, raw: &RawEntry) -> Result<Entry, Regist
     Ok(Entry::Notification { audience, severity })
 }
 
-fn event_str(e: &EventType) -> &'static str { e.as_str() }
+fn event_str(e: &EventType) -> &str { e.as_str() }
 
 pub static REGISTRY: Lazy<Registry> = Lazy::new(|| {
     const YAML: &str = include_str!("registry.yaml");
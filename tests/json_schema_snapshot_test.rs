@@ -0,0 +1,69 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use mykobo_rs::message_bus::models::json_schema::{export, to_file_contents};
+use mykobo_rs::message_bus::PayloadKind;
+use serde_json::Value;
+
+const REGENERATE: &str = "Run `cargo run --bin export_schemas` and commit the result.";
+
+#[test]
+fn schemas_match_committed_snapshots() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+
+    for (file_name, schema) in export() {
+        let expected = std::fs::read_to_string(dir.join(&file_name))
+            .unwrap_or_else(|_| panic!("{file_name} is missing from schemas/. {REGENERATE}"));
+        // Compared as JSON values, so a schemars release that only reorders object keys
+        // doesn't fail the snapshot
+        let expected: Value = serde_json::from_str(&expected)
+            .unwrap_or_else(|e| panic!("{file_name} is not valid JSON: {e}. {REGENERATE}"));
+        let generated: Value = serde_json::from_str(&to_file_contents(&schema)).unwrap();
+        assert_eq!(
+            generated, expected,
+            "{file_name} no longer matches the committed schema. {REGENERATE}"
+        );
+    }
+}
+
+#[test]
+fn no_stale_schemas_are_committed() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    let generated: BTreeSet<String> = export().into_keys().collect();
+    let committed: BTreeSet<String> = std::fs::read_dir(&dir)
+        .expect("read schemas/")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+
+    assert_eq!(committed, generated, "{REGENERATE}");
+}
+
+#[test]
+fn envelope_pairs_every_payload_kind() {
+    let schemas = export();
+    let envelope = &schemas["MessageBusMessage.schema.json"];
+    let rules = envelope["allOf"].as_array().unwrap();
+    assert_eq!(rules.len(), PayloadKind::ALL.len());
+
+    let definitions = envelope["$defs"].as_object().unwrap();
+    for kind in PayloadKind::ALL {
+        assert!(
+            definitions.contains_key(kind.type_name()),
+            "{} missing from $defs",
+            kind.type_name()
+        );
+    }
+
+    let deposit_rule = rules
+        .iter()
+        .find(|rule| {
+            rule["if"]["properties"]["meta_data"]["properties"]["event"]["enum"]
+                .as_array()
+                .is_some_and(|events| events.contains(&"DEPOSIT_INITIATED".into()))
+        })
+        .unwrap();
+    assert_eq!(
+        deposit_rule["then"]["properties"]["payload"]["anyOf"][0]["$ref"],
+        "#/$defs/CustomerNotificationPayload"
+    );
+}
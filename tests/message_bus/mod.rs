@@ -1,7 +1,23 @@
+mod test_archive;
+mod test_auth_gate;
 mod test_base_models;
+mod test_claim_check;
+mod test_cloud_events;
 mod test_event_models;
+mod test_field_encryption;
 mod test_instruction_models;
+mod test_kafka_admin;
 mod test_kafka_clients;
+mod test_message_builder;
 mod test_message_bus_message_deserialisation;
 mod test_message_models;
 mod test_message_serialisation;
+mod test_redaction;
+mod test_routing;
+mod test_schema_versioning;
+mod test_semantic_validation;
+mod test_signing;
+mod test_strict_decoding;
+mod test_wire_encoding;
+#[cfg(feature = "metrics")]
+mod test_metrics;
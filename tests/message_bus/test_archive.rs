@@ -0,0 +1,281 @@
+use chrono::{TimeZone, Utc};
+use mykobo_rs::message_bus::kafka::archive::{
+    archive_files, read_archives, ArchiveError, ArchiveReader, ArchiveWriter, ArchivedMessage,
+    ReplayFilter, Replayer,
+};
+use mykobo_rs::message_bus::kafka::models::IncomingMessage;
+use mykobo_rs::message_bus::kafka::producer::EventProducer;
+use mykobo_rs::message_bus::models::encryption::FieldEncryptor;
+use mykobo_rs::message_bus::models::event::PasswordResetEventPayload;
+use mykobo_rs::message_bus::{EventType, InstructionType, MessageBusMessage, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serial_test::serial;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Value {
+    serde_json::from_slice(&std::fs::read(format!("tests/fixtures/message_bus/{name}")).unwrap())
+        .unwrap()
+}
+
+fn archived(name: &str, offset: i64) -> ArchivedMessage {
+    ArchivedMessage::from(
+        IncomingMessage::new(
+            HashMap::from([("source".to_string(), "banking".to_string())]),
+            fixture(name),
+        )
+        .with_key(format!("key-{offset}"))
+        .with_position("mykobo.instructions", 2, offset),
+    )
+}
+
+fn archive_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("bus-archive-{}", uuid::Uuid::new_v4()))
+}
+
+fn replayer() -> Replayer {
+    std::env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+    Replayer::new(EventProducer::new("localhost:9092", 5, "mykobo.replay").unwrap())
+}
+
+#[test]
+fn test_archive_rotates_and_reads_back_in_order() {
+    for compressed in [false, true] {
+        let dir = archive_dir();
+        let mut writer = ArchiveWriter::new(&dir, "instructions").with_max_records(2);
+        if compressed {
+            writer = writer.with_compression();
+        }
+        let messages: Vec<ArchivedMessage> = (0..5)
+            .map(|offset| archived("payment.json", offset))
+            .collect();
+        for message in &messages {
+            writer.write(message).unwrap();
+        }
+        let files = writer.finish().unwrap();
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(archive_files(&dir).unwrap(), files);
+        assert_eq!(
+            files
+                .iter()
+                .all(|f| f.to_string_lossy().ends_with(".jsonl.gz")),
+            compressed
+        );
+        let read: Vec<ArchivedMessage> = read_archives(&files).map(Result::unwrap).collect();
+        assert_eq!(read, messages);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_archive_drains_consumer_channel() {
+    let dir = archive_dir();
+    let (sender, receiver) = tokio::sync::mpsc::channel(10);
+    let archiving = tokio::spawn(ArchiveWriter::new(&dir, "events").archive(receiver));
+
+    sender
+        .send(
+            IncomingMessage::new(HashMap::new(), fixture("raw.json")).with_position(
+                "mykobo.events",
+                0,
+                7,
+            ),
+        )
+        .await
+        .unwrap();
+    drop(sender);
+
+    let files = archiving.await.unwrap().unwrap();
+    let read: Vec<ArchivedMessage> = ArchiveReader::open(&files[0])
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].offset, 7);
+    assert_eq!(read[0].key, None);
+    assert_eq!(read[0].message, fixture("raw.json"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_corrupt_line_reports_position() {
+    let dir = archive_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("broken.jsonl");
+    let line = serde_json::to_string(&archived("payment.json", 1)).unwrap();
+    std::fs::write(&path, format!("{line}\n\n{{not json\n")).unwrap();
+
+    let mut reader = ArchiveReader::open(&path).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    assert!(matches!(
+        reader.next(),
+        Some(Err(ArchiveError::Record { line: 3, .. }))
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_replay_filter() {
+    let payment = archived("payment.json", 1);
+    let transaction = archived("transaction.json", 2);
+    let matches = |filter: &ReplayFilter, archived: &ArchivedMessage| {
+        filter.matches(archived, &archived.decode().unwrap())
+    };
+
+    let filter = ReplayFilter::new().instruction_type(InstructionType::Payment);
+    assert!(matches(&filter, &payment));
+    assert!(!matches(&filter, &transaction));
+
+    let filter = ReplayFilter::new()
+        .instruction_type(InstructionType::Payment)
+        .event(EventType::NewTransaction)
+        .source("TRANSACTION_SERVICE");
+    assert!(!matches(&filter, &payment));
+    assert!(!matches(&filter, &transaction));
+
+    let filter = ReplayFilter::new()
+        .topic("mykobo.instructions")
+        .created_from(Utc.with_ymd_and_hms(2026, 5, 30, 12, 0, 0).unwrap())
+        .created_until(Utc.with_ymd_and_hms(2026, 5, 30, 12, 0, 1).unwrap());
+    assert!(matches(&filter, &payment));
+    assert!(!matches(
+        &ReplayFilter::new().topic("mykobo.events"),
+        &payment
+    ));
+    assert!(!matches(
+        &ReplayFilter::new().created_until(Utc.with_ymd_and_hms(2026, 5, 30, 12, 0, 0).unwrap()),
+        &payment
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_replay_preserves_keys_and_rewrites_on_request() {
+    let payment = archived("payment.json", 1);
+
+    let (key, message) = replayer().prepare(&payment).unwrap().unwrap();
+    assert_eq!(key, "key-1");
+    assert_eq!(message, payment.decode().unwrap());
+
+    let (key, message) = replayer()
+        .with_new_idempotency_keys()
+        .with_replay_id("incident-42")
+        .with_new_created_at()
+        .prepare(&payment)
+        .unwrap()
+        .unwrap();
+    assert_eq!(key, "key-1");
+    assert_eq!(
+        message.meta_data.idempotency_key,
+        "banking:payment:P763763453G:replay:incident-42"
+    );
+    assert_ne!(message.meta_data.created_at, "2026-05-30T12:00:00Z");
+    assert_eq!(message.payload, payment.decode().unwrap().payload);
+}
+
+fn password_reset(idempotency_key: &str) -> MessageBusMessage {
+    MessageBusMessage::event(
+        EventType::PasswordResetRequested,
+        PasswordResetEventPayload::new(
+            "user@example.com".to_string(),
+            "Reset your password".to_string(),
+            "hunter2".to_string(),
+        )
+        .unwrap(),
+    )
+    .source("IDENTITY_SERVICE")
+    .token("service.token")
+    .idempotency_key(idempotency_key)
+    .build()
+    .unwrap()
+}
+
+fn password(message: &MessageBusMessage) -> &str {
+    match &message.payload {
+        Payload::PasswordReset(payload) => &payload.password,
+        other => panic!("unexpected payload {other:?}"),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_encrypted_fields_stay_encrypted_through_archive_and_replay() {
+    let encryptor = || FieldEncryptor::new("kek-2024", &[7; 32]).unwrap();
+    let dir = archive_dir();
+
+    // A consumer with a FieldEncryptor forwards the message decrypted
+    let decrypted = IncomingMessage::new(
+        HashMap::new(),
+        serde_json::to_value(password_reset("key-1")).unwrap(),
+    );
+    let mut writer = ArchiveWriter::new(&dir, "events").with_field_encryptor(encryptor());
+    writer.write(&ArchivedMessage::from(decrypted)).unwrap();
+    let files = writer.finish().unwrap();
+    let archived = read_archives(&files).next().unwrap().unwrap();
+    assert!(!archived.message.to_string().contains("hunter2"));
+    assert_eq!(
+        password(&encryptor().decrypt(&archived.decode().unwrap()).unwrap()),
+        "hunter2"
+    );
+
+    // Archived ciphertext is bound to key-1, so new keys need the KEK to re-encrypt
+    assert!(matches!(
+        replayer().with_new_idempotency_keys().prepare(&archived),
+        Err(ArchiveError::EncryptedFields(key)) if key == "key-1"
+    ));
+    let other_kek = FieldEncryptor::new("kek-2025", &[9; 32]).unwrap();
+    assert!(matches!(
+        replayer()
+            .with_new_idempotency_keys()
+            .with_field_encryptor(other_kek)
+            .prepare(&archived),
+        Err(ArchiveError::EncryptedFields(_))
+    ));
+
+    let (_, message) = replayer()
+        .with_new_idempotency_keys()
+        .with_replay_id("incident-42")
+        .with_field_encryptor(encryptor())
+        .prepare(&archived)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        message.meta_data.idempotency_key,
+        "key-1:replay:incident-42"
+    );
+    assert!(FieldEncryptor::is_encrypted(password(&message)));
+    assert_eq!(password(&encryptor().decrypt(&message).unwrap()), "hunter2");
+
+    // Keeping the original keys needs no KEK
+    let (_, message) = replayer().prepare(&archived).unwrap().unwrap();
+    assert_eq!(message, archived.decode().unwrap());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_replay_skips_filtered_and_invalid_messages() {
+    let mut invalid = archived("payment.json", 3);
+    invalid.message["meta_data"]
+        .as_object_mut()
+        .unwrap()
+        .remove("source");
+    let replayer = replayer().with_filter(ReplayFilter::new().event(EventType::NewTransaction));
+
+    assert_eq!(
+        replayer.prepare(&archived("payment.json", 1)).unwrap(),
+        None
+    );
+    assert!(replayer.prepare(&invalid).is_err());
+
+    let report = replayer
+        .replay([Ok(archived("transaction.json", 2)), Ok(invalid)])
+        .await
+        .unwrap();
+    assert_eq!(
+        (report.replayed, report.filtered, report.invalid),
+        (0, 1, 1)
+    );
+}
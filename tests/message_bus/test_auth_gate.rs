@@ -0,0 +1,287 @@
+use jsonwebtoken::{encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use mykobo_rs::identity::IdentityServiceClient;
+use mykobo_rs::message_bus::kafka::auth::{AuthGate, AuthorisationError, TokenVerifier};
+use mykobo_rs::message_bus::kafka::consumer::EventConsumer;
+use mykobo_rs::message_bus::InstructionType;
+use pretty_assertions::assert_eq;
+use rdkafka::mocking::MockCluster;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::read_file;
+
+const SECRET: &[u8] = b"bus-signing-secret";
+
+fn token_with_scopes(scopes: &[&str], secret: &[u8]) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+    let claims = json!({
+        "sub": "urn:svc:ledger",
+        "iat": now,
+        "exp": now + 3600,
+        "aud": "Service",
+        "scope": scopes,
+    });
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret),
+    )
+    .unwrap()
+}
+
+fn message(instruction_type: &str, token: &str) -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "meta_data": {
+            "source": "LEDGER_SERVICE",
+            "created_at": "2024-05-01T12:00:00Z",
+            "token": token,
+            "idempotency_key": "key-1",
+            "instruction_type": instruction_type
+        },
+        "payload": {}
+    }))
+    .unwrap()
+}
+
+fn jwt_gate() -> AuthGate {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_audience(&["Service"]);
+    let (quarantine, _) = mpsc::channel(1);
+    AuthGate::new(
+        TokenVerifier::jwt(DecodingKey::from_secret(SECRET), validation),
+        quarantine,
+    )
+    .require_scope(InstructionType::Mint, "ledger:mint")
+    .require_scope(InstructionType::Burn, "ledger:burn")
+}
+
+#[tokio::test]
+async fn test_jwt_gate_checks_required_scope() {
+    let gate = jwt_gate();
+    let token = token_with_scopes(&["ledger:mint"], SECRET);
+
+    assert_eq!(gate.authorise(&message("MINT", &token)).await, Ok(()));
+    assert_eq!(
+        gate.authorise(&message("BURN", &token)).await,
+        Err(AuthorisationError::MissingScope("ledger:burn".to_string()))
+    );
+    assert_eq!(gate.required_scope(&InstructionType::Payment), None);
+    assert_eq!(gate.authorise(&message("PAYMENT", "")).await, Ok(()));
+}
+
+#[tokio::test]
+async fn test_jwt_gate_rejects_missing_and_forged_tokens() {
+    let gate = jwt_gate();
+    let forged = token_with_scopes(&["ledger:mint"], b"someone-elses-secret");
+
+    assert_eq!(
+        gate.authorise(&message("MINT", "")).await,
+        Err(AuthorisationError::MissingToken)
+    );
+    assert!(matches!(
+        gate.authorise(&message("MINT", &forged)).await,
+        Err(AuthorisationError::InvalidToken(_))
+    ));
+    assert!(matches!(
+        gate.authorise(&message("MINT", "not.a.jwt")).await,
+        Err(AuthorisationError::InvalidToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_gate_rejects_unreadable_messages() {
+    let gate = jwt_gate();
+
+    assert_eq!(
+        gate.authorise(br#"{"meta_data": {"event": "NEW_USER"}}"#)
+            .await,
+        Ok(())
+    );
+
+    let mut numeric_token: serde_json::Value =
+        serde_json::from_slice(&message("MINT", "")).unwrap();
+    numeric_token["meta_data"]["token"] = json!(42);
+    let mut object_token = numeric_token.clone();
+    object_token["meta_data"]["token"] = json!({ "scope": ["ledger:mint"] });
+    let unreadable = [
+        b"not json".to_vec(),
+        br#"{"payload": {}}"#.to_vec(),
+        serde_json::to_vec(&numeric_token).unwrap(),
+        serde_json::to_vec(&object_token).unwrap(),
+    ];
+    for payload in unreadable {
+        assert!(matches!(
+            gate.authorise(&payload).await,
+            Err(AuthorisationError::UnreadableMetaData(_))
+        ));
+    }
+
+    // A gate without required scopes checks nothing
+    let (quarantine, _) = mpsc::channel(1);
+    let open = AuthGate::new(
+        TokenVerifier::jwt(
+            DecodingKey::from_secret(SECRET),
+            Validation::new(Algorithm::HS256),
+        ),
+        quarantine,
+    );
+    assert_eq!(open.authorise(b"not json").await, Ok(()));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_identity_gate_uses_check_scope() {
+    let mock_server = MockServer::start().await;
+
+    env::set_var("IDENTITY_ACCESS_KEY", "TEST_ACCESS_KEY");
+    env::set_var("IDENTITY_SECRET_KEY", "TEST_SECRET_KEY");
+    env::set_var("IDENTITY_SERVICE_HOST", mock_server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/authenticate"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(read_file("tests/stubs/authenticate.json")),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/authorise/scope"))
+        .and(body_partial_json(
+            json!({"token": "granted", "scope": "ledger:mint"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"authorised": true, "message": "ok"})),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/authorise/scope"))
+        .and(body_partial_json(json!({"token": "denied"})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"authorised": false, "message": "missing scope"})),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/authorise/scope"))
+        .and(body_partial_json(json!({"token": "outage"})))
+        .respond_with(ResponseTemplate::new(503).set_body_json(json!({"error": "down"})))
+        .mount(&mock_server)
+        .await;
+
+    let (quarantine, _) = mpsc::channel(1);
+    let gate = AuthGate::new(
+        TokenVerifier::identity(IdentityServiceClient::new(0)),
+        quarantine,
+    )
+    .require_scope(InstructionType::Mint, "ledger:mint");
+
+    assert_eq!(gate.authorise(&message("MINT", "granted")).await, Ok(()));
+    assert_eq!(
+        gate.authorise(&message("MINT", "denied")).await,
+        Err(AuthorisationError::MissingScope("ledger:mint".to_string()))
+    );
+    assert!(matches!(
+        gate.authorise(&message("MINT", "outage")).await,
+        Err(AuthorisationError::Unavailable(_))
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_consumer_redelivers_messages_the_identity_service_could_not_check() {
+    let mock_server = MockServer::start().await;
+    env::set_var("IDENTITY_ACCESS_KEY", "TEST_ACCESS_KEY");
+    env::set_var("IDENTITY_SECRET_KEY", "TEST_SECRET_KEY");
+    env::set_var("IDENTITY_SERVICE_HOST", mock_server.uri());
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+    env::remove_var("KAFKA_API_KEY");
+    env::remove_var("KAFKA_API_SECRET");
+
+    Mock::given(method("POST"))
+        .and(path("/authenticate"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(read_file("tests/stubs/authenticate.json")),
+        )
+        .mount(&mock_server)
+        .await;
+    // The first check hits an outage, the redelivered message is authorised
+    Mock::given(method("POST"))
+        .and(path("/authorise/scope"))
+        .respond_with(ResponseTemplate::new(503).set_body_json(json!({"error": "down"})))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/authorise/scope"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"authorised": true, "message": "ok"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let cluster = MockCluster::new(1).unwrap();
+    cluster.create_topic("mykobo.instructions", 1, 1).unwrap();
+    let brokers = cluster.bootstrap_servers();
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", &brokers)
+        .create()
+        .unwrap();
+    producer
+        .send(
+            FutureRecord::<str, _>::to("mykobo.instructions").payload(&message("MINT", "granted")),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+    let (quarantine, _) = mpsc::channel(1);
+    let gate = AuthGate::new(
+        TokenVerifier::identity(IdentityServiceClient::new(0)),
+        quarantine,
+    )
+    .require_scope(InstructionType::Mint, "ledger:mint");
+    let (tx, mut rx) = mpsc::channel(1);
+    let consumer = EventConsumer::<Value>::new(
+        &brokers,
+        "auth-gate-test",
+        "auth-gate-test",
+        3,
+        &["mykobo.instructions"],
+        tx,
+    )
+    .unwrap()
+    .with_auth_gate(gate);
+    let consuming = tokio::spawn(async move { consumer.start().await });
+
+    let received = tokio::time::timeout(Duration::from_secs(30), rx.recv())
+        .await
+        .expect("the deferred message was not redelivered")
+        .unwrap();
+    consuming.abort();
+
+    assert_eq!(received.offset, 0);
+    assert_eq!(received.payload["meta_data"]["instruction_type"], "MINT");
+    let scope_checks = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/authorise/scope")
+        .count();
+    assert_eq!(scope_checks, 2);
+}
@@ -1,4 +1,6 @@
-use mykobo_rs::message_bus::models::base::{validate_required_fields, PaymentDirection};
+use mykobo_rs::message_bus::models::base::{
+    validate_required_fields, PayloadParseError, PaymentDirection,
+};
 use mykobo_rs::message_bus::{EventType, InstructionType, TransactionType};
 
 #[test]
@@ -71,12 +73,56 @@ fn test_payment_direction_from_string_invalid() {
     let _direction: PaymentDirection = "\"INVALID\"".to_string().into();
 }
 
+#[test]
+fn test_payment_direction_parse() {
+    assert_eq!(
+        "outbound".parse::<PaymentDirection>().unwrap(),
+        PaymentDirection::Outbound
+    );
+    assert_eq!(
+        PaymentDirection::try_from("\"Both\"").unwrap(),
+        PaymentDirection::Both
+    );
+}
+
+#[test]
+fn test_payment_direction_parse_invalid_returns_error() {
+    let error = "sideways".parse::<PaymentDirection>().unwrap_err();
+    assert!(matches!(
+        error,
+        PayloadParseError::Json {
+            class_name: "PaymentDirection",
+            ..
+        }
+    ));
+}
+
 #[test]
 fn test_event_type_display() {
     assert_eq!(EventType::NewTransaction.to_string(), "NEW_TRANSACTION");
     assert_eq!(EventType::KycEvent.to_string(), "KYC_EVENT");
 }
 
+#[test]
+fn test_unknown_types_round_trip() {
+    let event: EventType = serde_json::from_str("\"LOAN_DISBURSED\"").unwrap();
+    let instruction: InstructionType = serde_json::from_str("\"REFUND\"").unwrap();
+
+    assert_eq!(event, EventType::Unknown("LOAN_DISBURSED".to_string()));
+    assert_eq!(instruction, InstructionType::Unknown("REFUND".to_string()));
+    assert!(event.is_unknown() && instruction.is_unknown());
+    assert_eq!(event.as_str(), "LOAN_DISBURSED");
+    assert_eq!(event.to_string(), "LOAN_DISBURSED");
+    assert_eq!(instruction.to_string(), "REFUND");
+    assert_eq!(serde_json::to_string(&event).unwrap(), "\"LOAN_DISBURSED\"");
+    assert_eq!(serde_json::to_string(&instruction).unwrap(), "\"REFUND\"");
+
+    let known: EventType = serde_json::from_str("\"KYC_EVENT\"").unwrap();
+    assert_eq!(known, EventType::KycEvent);
+    assert!(!known.is_unknown());
+    assert!(EventType::try_from("LOAN_DISBURSED").is_err());
+}
+
 #[test]
 fn test_validate_required_fields_success() {
     let fields = vec![("source", "BANKING_SERVICE"), ("token", "test.token")];
@@ -0,0 +1,116 @@
+use mykobo_rs::message_bus::models::avro::{decode_datum, encode_datum, AVRO_SCHEMA};
+use mykobo_rs::message_bus::models::claim_check::{
+    claim_check_key, ClaimCheck, ClaimCheckError, FileBlobStore, CLAIM_CHECK_PREFIX,
+};
+use mykobo_rs::message_bus::{MessageBusMessage, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const THRESHOLD: usize = 4 * 1024;
+
+fn blob_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("claim-check-{}", uuid::Uuid::new_v4()))
+}
+
+fn claim_check(dir: &PathBuf) -> ClaimCheck {
+    ClaimCheck::new(Arc::new(FileBlobStore::new(dir))).with_threshold(THRESHOLD)
+}
+
+fn notification(data_size: usize) -> Vec<u8> {
+    let mut message: Value = serde_json::from_slice(
+        &std::fs::read("tests/fixtures/notification/customer_relay_completed.json").unwrap(),
+    )
+    .unwrap();
+    message["payload"]["data"]["receipt"] = json!("x".repeat(data_size));
+    serde_json::to_vec(&message).unwrap()
+}
+
+#[tokio::test]
+async fn test_small_messages_are_sent_as_is() {
+    let dir = blob_dir();
+    let claim_check = claim_check(&dir);
+    let json = notification(10);
+
+    assert_eq!(claim_check.offload(&json).await.unwrap(), json);
+    assert_eq!(claim_check.rehydrate(&json).await.unwrap(), json);
+    assert!(!dir.exists());
+}
+
+#[tokio::test]
+async fn test_large_payload_round_trip() {
+    let dir = blob_dir();
+    let json = notification(THRESHOLD * 4);
+
+    let sent = claim_check(&dir).offload(&json).await.unwrap();
+    assert!(sent.len() < THRESHOLD);
+    let sent_message: Value = serde_json::from_slice(&sent).unwrap();
+    let original: Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(sent_message["meta_data"], original["meta_data"]);
+    let key = claim_check_key(&sent_message).unwrap();
+    assert!(dir.join(format!("{key}.json")).exists());
+
+    // A consumer with its own store client on the same directory
+    let received = claim_check(&dir).rehydrate(&sent).await.unwrap();
+    assert_eq!(
+        serde_json::from_slice::<Value>(&received).unwrap(),
+        original
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_reference_is_a_raw_payload_in_json_and_avro() {
+    let dir = blob_dir();
+    let sent = claim_check(&dir)
+        .offload(&notification(THRESHOLD * 2))
+        .await
+        .unwrap();
+
+    let message: MessageBusMessage = serde_json::from_slice(&sent).unwrap();
+    assert!(matches!(
+        &message.payload,
+        Payload::Raw(reference) if reference.starts_with(CLAIM_CHECK_PREFIX)
+    ));
+
+    let value: Value = serde_json::from_slice(&sent).unwrap();
+    let decoded = decode_datum(&encode_datum(&value).unwrap(), &AVRO_SCHEMA).unwrap();
+    assert_eq!(decoded["payload"], value["payload"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_missing_or_altered_blobs_are_rejected() {
+    let dir = blob_dir();
+    let claim_check = claim_check(&dir);
+    let sent = claim_check
+        .offload(&notification(THRESHOLD * 2))
+        .await
+        .unwrap();
+    let key = claim_check_key(&serde_json::from_slice(&sent).unwrap())
+        .unwrap()
+        .to_string();
+
+    std::fs::write(dir.join(format!("{key}.json")), br#"{"data":{}}"#).unwrap();
+    assert!(matches!(
+        claim_check.rehydrate(&sent).await,
+        Err(ClaimCheckError::DigestMismatch(k)) if k == key
+    ));
+
+    std::fs::remove_file(dir.join(format!("{key}.json"))).unwrap();
+    assert!(matches!(
+        claim_check.rehydrate(&sent).await,
+        Err(ClaimCheckError::NotFound(k)) if k == key
+    ));
+
+    let mut forged: Value = serde_json::from_slice(&sent).unwrap();
+    forged["payload"] = json!(format!("{CLAIM_CHECK_PREFIX}../../etc/passwd"));
+    assert!(matches!(
+        claim_check
+            .rehydrate(&serde_json::to_vec(&forged).unwrap())
+            .await,
+        Err(ClaimCheckError::InvalidReference(_))
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
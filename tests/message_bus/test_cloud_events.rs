@@ -0,0 +1,204 @@
+use mykobo_rs::message_bus::models::cloud_event::{
+    CloudEvent, CloudEventError, JSON_CONTENT_TYPE, TEXT_CONTENT_TYPE, TOKEN_EXTENSION,
+};
+use mykobo_rs::message_bus::{MessageBusMessage, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn payment_message() -> MessageBusMessage {
+    serde_json::from_value(json!({
+        "meta_data": {
+            "source": "BANKING_SERVICE",
+            "created_at": "2024-05-01T12:00:00Z",
+            "token": "service.token",
+            "idempotency_key": "key-1",
+            "instruction_type": "PAYMENT",
+            "ip_address": "10.0.0.1",
+            "schema_version": 1,
+            "correlation_id": "corr-1",
+            "causation_id": "cause-1",
+            "expires_at": "2024-05-02T12:00:00Z",
+            "tenant": "acme"
+        },
+        "payload": {
+            "external_reference": "ext-1",
+            "payer_name": "Jane Doe",
+            "currency": "EUR",
+            "value": "100.00",
+            "source": "BANK",
+            "direction": "INBOUND",
+            "reference": "ref-1",
+            "bank_account_number": "GB00BANK0000"
+        }
+    }))
+    .unwrap()
+}
+
+fn raw_event_message() -> MessageBusMessage {
+    serde_json::from_value(json!({
+        "meta_data": {
+            "source": "KYC_SERVICE",
+            "created_at": "2024-05-01T12:00:00Z",
+            "token": "",
+            "idempotency_key": "key-2",
+            "event": "KYC_EVENT"
+        },
+        "payload": "applicant reviewed"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_structured_mode_maps_attributes_and_extensions() {
+    let event = CloudEvent::try_from(&payment_message()).unwrap();
+    let value = serde_json::to_value(&event).unwrap();
+
+    assert_eq!(value["specversion"], "1.0");
+    assert_eq!(value["id"], "key-1");
+    assert_eq!(value["source"], "BANKING_SERVICE");
+    assert_eq!(value["type"], "com.mykobo.instruction.PAYMENT");
+    assert_eq!(value["time"], "2024-05-01T12:00:00Z");
+    assert_eq!(value["datacontenttype"], JSON_CONTENT_TYPE);
+    assert_eq!(value["ipaddress"], "10.0.0.1");
+    assert_eq!(value["schemaversion"], 1);
+    assert_eq!(value["correlationid"], "corr-1");
+    assert_eq!(value["causationid"], "cause-1");
+    assert_eq!(value["expiresat"], "2024-05-02T12:00:00Z");
+    assert_eq!(value["tenant"], "acme");
+    assert_eq!(value["data"]["external_reference"], "ext-1");
+}
+
+#[test]
+fn test_structured_mode_round_trip() {
+    for message in [payment_message(), raw_event_message()] {
+        let event = CloudEvent::try_from(&message)
+            .unwrap()
+            .with_token(&message.meta_data.token);
+        let json = serde_json::to_string(&event).unwrap();
+        let event: CloudEvent = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(MessageBusMessage::try_from(event).unwrap(), message);
+    }
+}
+
+#[test]
+fn test_binary_mode_round_trip() {
+    for message in [payment_message(), raw_event_message()] {
+        let event = CloudEvent::try_from(&message)
+            .unwrap()
+            .with_token(&message.meta_data.token);
+        let headers: HashMap<String, String> = event.kafka_headers().into_iter().collect();
+        let body = event.kafka_body();
+
+        let received = CloudEvent::from_kafka(&headers, &body).unwrap();
+        assert_eq!(received, event);
+        assert_eq!(MessageBusMessage::try_from(received).unwrap(), message);
+    }
+}
+
+#[test]
+fn test_token_is_only_carried_on_request() {
+    let event = CloudEvent::try_from(&payment_message()).unwrap();
+    let headers: HashMap<String, String> = event.kafka_headers().into_iter().collect();
+
+    assert!(!event.extensions.contains_key(TOKEN_EXTENSION));
+    assert!(!headers.contains_key("ce_token"));
+    let message = MessageBusMessage::try_from(event).unwrap();
+    assert_eq!(message.meta_data.token, "");
+
+    let event = CloudEvent::try_from(&payment_message())
+        .unwrap()
+        .with_token("service.token");
+    let headers: HashMap<String, String> = event.kafka_headers().into_iter().collect();
+    assert_eq!(headers["ce_token"], "service.token");
+}
+
+#[test]
+fn test_messages_without_a_type_are_rejected() {
+    let mut message = payment_message();
+    message.meta_data.instruction_type = None;
+
+    assert!(matches!(
+        CloudEvent::try_from(message),
+        Err(CloudEventError::MissingType)
+    ));
+}
+
+#[test]
+fn test_binary_mode_headers() {
+    let event = CloudEvent::try_from(&raw_event_message()).unwrap();
+    let headers: HashMap<String, String> = event.kafka_headers().into_iter().collect();
+
+    assert_eq!(headers["ce_specversion"], "1.0");
+    assert_eq!(headers["ce_id"], "key-2");
+    assert_eq!(headers["ce_type"], "com.mykobo.event.KYC_EVENT");
+    assert_eq!(headers["content-type"], TEXT_CONTENT_TYPE);
+    assert_eq!(event.kafka_body(), b"applicant reviewed");
+}
+
+#[test]
+fn test_foreign_events_are_rejected() {
+    let mut event = CloudEvent::try_from(&payment_message()).unwrap();
+    event.event_type = "org.example.order.created".to_string();
+    assert!(matches!(
+        MessageBusMessage::try_from(event),
+        Err(CloudEventError::UnknownType(event_type)) if event_type == "org.example.order.created"
+    ));
+
+    let mut event = CloudEvent::try_from(&payment_message()).unwrap();
+    event.specversion = "0.3".to_string();
+    assert!(matches!(
+        MessageBusMessage::try_from(event),
+        Err(CloudEventError::UnsupportedSpecVersion(_))
+    ));
+
+    let mut headers: HashMap<String, String> = CloudEvent::try_from(&payment_message())
+        .unwrap()
+        .kafka_headers()
+        .into_iter()
+        .collect();
+    headers.remove("ce_id");
+    assert!(matches!(
+        CloudEvent::from_kafka(&headers, b"{}"),
+        Err(CloudEventError::MissingAttribute("id"))
+    ));
+}
+
+#[test]
+fn test_unknown_types_round_trip() {
+    let event: CloudEvent = serde_json::from_value(json!({
+        "specversion": "1.0",
+        "id": "key-3",
+        "source": "LEDGER_SERVICE",
+        "type": "com.mykobo.event.LEDGER_CLOSED",
+        "time": "2024-05-01T12:00:00Z",
+        "data": { "day": "2024-04-30" }
+    }))
+    .unwrap();
+
+    let message = MessageBusMessage::try_from(event).unwrap();
+    assert!(message.meta_data.has_unknown_type());
+    assert_eq!(
+        message.payload,
+        Payload::Unknown(json!({ "day": "2024-04-30" }))
+    );
+    assert_eq!(
+        CloudEvent::try_from(&message).unwrap().event_type,
+        "com.mykobo.event.LEDGER_CLOSED"
+    );
+}
+
+#[test]
+fn test_debug_masks_data_and_secrets() {
+    let message = payment_message();
+    let event = CloudEvent::try_from(&message)
+        .unwrap()
+        .with_token(&message.meta_data.token);
+    let debug = format!("{event:?}");
+
+    assert!(debug.contains("key-1"));
+    assert!(!debug.contains("service.token"));
+    assert!(!debug.contains("10.0.0.1"));
+    assert!(!debug.contains("Jane Doe"));
+}
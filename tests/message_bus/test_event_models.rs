@@ -1,3 +1,4 @@
+use mykobo_rs::message_bus::models::base::PayloadParseError;
 use mykobo_rs::message_bus::models::event::*;
 use mykobo_rs::message_bus::TransactionType;
 use std::collections::HashMap;
@@ -473,3 +474,38 @@ fn test_bank_payment_event_payload_serialization_without_optionals() {
     assert_eq!(payload, deserialized);
     assert_eq!(deserialized.message, None);
 }
+
+#[test]
+fn test_event_payload_parse() {
+    let json = r#"{
+        "created_at": "2021-01-01T00:00:00Z",
+        "kind": "DEPOSIT",
+        "reference": "TXN123",
+        "source": "ANCHOR"
+    }"#;
+
+    let payload: NewTransactionEventPayload = json.parse().unwrap();
+    assert_eq!(payload.kind, TransactionType::Deposit);
+}
+
+#[test]
+fn test_event_payload_parse_errors() {
+    assert!(matches!(
+        KycEventPayload::try_from("[]"),
+        Err(PayloadParseError::Json {
+            class_name: "KycEventPayload",
+            ..
+        })
+    ));
+
+    let json = r#"{
+        "created_at": "2021-01-01T00:00:00Z",
+        "kind": "DEPOSIT",
+        "reference": "",
+        "source": "ANCHOR"
+    }"#;
+    assert!(matches!(
+        json.parse::<NewTransactionEventPayload>(),
+        Err(PayloadParseError::Validation(_))
+    ));
+}
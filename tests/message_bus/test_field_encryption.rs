@@ -0,0 +1,277 @@
+use mykobo_rs::message_bus::models::encryption::{
+    encrypted_fields, EncryptionError, FieldEncryptor, ENCRYPTED_PREFIX,
+};
+use mykobo_rs::message_bus::models::event::{
+    AddressOnboardedEventPayload, PasswordResetEventPayload,
+};
+use mykobo_rs::message_bus::{EventType, MessageBusMessage, Payload, PayloadKind};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+const KEK_2024: [u8; 32] = [7; 32];
+const KEK_2025: [u8; 32] = [9; 32];
+
+fn password_reset_message() -> MessageBusMessage {
+    MessageBusMessage::event(
+        EventType::PasswordResetRequested,
+        PasswordResetEventPayload::new(
+            "user@example.com".to_string(),
+            "Reset your password".to_string(),
+            "hunter2".to_string(),
+        )
+        .unwrap(),
+    )
+    .source("IDENTITY_SERVICE")
+    .token("service.token")
+    .idempotency_key("key-1")
+    .build()
+    .unwrap()
+}
+
+fn update_profile_message() -> Value {
+    json!({
+        "meta_data": {
+            "source": "PROFILE_SERVICE",
+            "created_at": "2024-05-01T12:00:00Z",
+            "token": "",
+            "idempotency_key": "key-2",
+            "instruction_type": "UPDATE_PROFILE"
+        },
+        "payload": {
+            "profile_id": "profile-1",
+            "tax_id": "AB123456C",
+            "bank_number": "12-34-56",
+            "address_line_1": null
+        }
+    })
+}
+
+fn password(message: &MessageBusMessage) -> String {
+    match &message.payload {
+        Payload::PasswordReset(payload) => payload.password.clone(),
+        other => panic!("unexpected payload {other:?}"),
+    }
+}
+
+#[test]
+fn test_round_trip_encrypts_only_annotated_fields() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let message = password_reset_message();
+
+    let encrypted = encryptor.encrypt(&message).unwrap();
+    let value = serde_json::to_value(&encrypted).unwrap();
+
+    assert!(password(&encrypted).starts_with("enc:v1:kek-2024:"));
+    assert!(value["payload"]["to"]
+        .as_str()
+        .unwrap()
+        .starts_with("enc:v1:kek-2024:"));
+    assert_eq!(value["payload"]["subject"], "Reset your password");
+    assert!(!value.to_string().contains("hunter2"));
+    assert!(!value.to_string().contains("user@example.com"));
+    assert_eq!(encryptor.decrypt(&encrypted).unwrap(), message);
+}
+
+#[test]
+fn test_encrypted_messages_still_validate() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let encrypted = encryptor.encrypt(&password_reset_message()).unwrap();
+
+    // A consumer without the KEK can't check the format of `to`
+    encrypted.validate().unwrap();
+}
+
+#[test]
+fn test_nested_sensitive_values_are_not_encrypted() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let message = MessageBusMessage::event(
+        EventType::AddressOnboarded,
+        AddressOnboardedEventPayload::new(
+            "user@example.com".to_string(),
+            [("address".to_string(), "GABC".to_string())].into(),
+        )
+        .unwrap(),
+    )
+    .source("WALLET_SERVICE")
+    .token("service.token")
+    .idempotency_key("key-3")
+    .build()
+    .unwrap();
+
+    let value = serde_json::to_value(encryptor.encrypt(&message).unwrap()).unwrap();
+    assert!(value["payload"]["email"]
+        .as_str()
+        .unwrap()
+        .starts_with(ENCRYPTED_PREFIX));
+    assert_eq!(value["payload"]["payload"], json!({ "address": "GABC" }));
+}
+
+#[test]
+fn test_encrypting_twice_leaves_values_alone() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let once = encryptor.encrypt(&password_reset_message()).unwrap();
+
+    assert_eq!(encryptor.encrypt(&once).unwrap(), once);
+}
+
+#[test]
+fn test_bytes_round_trip_skips_nulls() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let message = serde_json::to_vec(&update_profile_message()).unwrap();
+
+    let encrypted: Value =
+        serde_json::from_slice(&encryptor.encrypt_bytes(&message).unwrap()).unwrap();
+    assert!(encrypted["payload"]["tax_id"]
+        .as_str()
+        .unwrap()
+        .starts_with(ENCRYPTED_PREFIX));
+    assert!(encrypted["payload"]["bank_number"]
+        .as_str()
+        .unwrap()
+        .starts_with(ENCRYPTED_PREFIX));
+    assert_eq!(encrypted["payload"]["address_line_1"], Value::Null);
+    assert_eq!(encrypted["payload"]["profile_id"], "profile-1");
+
+    let decrypted = encryptor
+        .decrypt_bytes(&serde_json::to_vec(&encrypted).unwrap())
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<Value>(&decrypted).unwrap(),
+        update_profile_message()
+    );
+}
+
+#[test]
+fn test_key_rotation() {
+    let old = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let rotated = FieldEncryptor::new("kek-2025", &KEK_2025)
+        .unwrap()
+        .with_key("kek-2024", &KEK_2024)
+        .unwrap();
+    let message = password_reset_message();
+
+    let from_old = old.encrypt(&message).unwrap();
+    let from_rotated = rotated.encrypt(&message).unwrap();
+
+    assert_eq!(rotated.active_key_id(), "kek-2025");
+    assert!(password(&from_rotated).starts_with("enc:v1:kek-2025:"));
+    assert_eq!(rotated.decrypt(&from_old).unwrap(), message);
+    assert_eq!(rotated.decrypt(&from_rotated).unwrap(), message);
+}
+
+#[test]
+fn test_unknown_key_leaves_field_encrypted() {
+    let producer = FieldEncryptor::new("kek-2025", &KEK_2025).unwrap();
+    let consumer = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let encrypted = producer.encrypt(&password_reset_message()).unwrap();
+
+    assert_eq!(consumer.decrypt(&encrypted).unwrap(), encrypted);
+}
+
+#[test]
+fn test_tampering_and_wrong_keys_fail() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let impostor = FieldEncryptor::new("kek-2024", &KEK_2025).unwrap();
+    let value = encryptor
+        .encrypt_field("key-1", "password", "hunter2")
+        .unwrap();
+
+    // Flip a character inside the ciphertext; the last one may only carry padding bits
+    let mut tampered = value.clone().into_bytes();
+    let i = tampered.len() - 5;
+    tampered[i] = if tampered[i] == b'A' { b'B' } else { b'A' };
+    let tampered = String::from_utf8(tampered).unwrap();
+    let decryption_failed = Err(EncryptionError::Decryption {
+        field: "password".to_string(),
+        key_id: "kek-2024".to_string(),
+    });
+
+    assert_eq!(
+        encryptor
+            .decrypt_field("key-1", "password", &value)
+            .unwrap(),
+        Some("hunter2".to_string())
+    );
+    assert_eq!(
+        encryptor.decrypt_field("key-1", "password", &tampered),
+        decryption_failed
+    );
+    assert_eq!(
+        impostor.decrypt_field("key-1", "password", &value),
+        decryption_failed
+    );
+    assert_eq!(
+        encryptor.decrypt_field("key-1", "tax_id", &value),
+        Err(EncryptionError::Decryption {
+            field: "tax_id".to_string(),
+            key_id: "kek-2024".to_string(),
+        })
+    );
+    assert_eq!(
+        encryptor.decrypt_field("key-2", "password", &value),
+        decryption_failed
+    );
+    assert_eq!(
+        encryptor.decrypt_field("key-1", "password", "enc:v1:kek-2024:nope"),
+        Err(EncryptionError::Malformed {
+            field: "password".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_invalid_keys_are_rejected() {
+    assert!(matches!(
+        FieldEncryptor::new("kek-2024", &[1; 16]),
+        Err(EncryptionError::InvalidKey { .. })
+    ));
+    assert!(matches!(
+        FieldEncryptor::new("kek:2024", &KEK_2024),
+        Err(EncryptionError::InvalidKey { .. })
+    ));
+}
+
+#[test]
+fn test_encrypted_fields_table() {
+    assert_eq!(
+        encrypted_fields(PayloadKind::PasswordReset),
+        &["to", "password"]
+    );
+    assert_eq!(
+        encrypted_fields(PayloadKind::Payment),
+        &["payer_name", "bank_account_number"]
+    );
+    assert!(encrypted_fields(PayloadKind::Mint).is_empty());
+    assert_eq!(
+        encrypted_fields(PayloadKind::VerificationRequested),
+        &["to"]
+    );
+    assert_eq!(
+        encrypted_fields(PayloadKind::CustomerNotification),
+        &["data"]
+    );
+}
+
+#[test]
+fn test_encrypted_values_are_bound_to_their_message() {
+    let encryptor = FieldEncryptor::new("kek-2024", &KEK_2024).unwrap();
+    let encrypted =
+        serde_json::to_value(encryptor.encrypt(&password_reset_message()).unwrap()).unwrap();
+
+    let mut moved = encrypted.clone();
+    moved["meta_data"]["idempotency_key"] = json!("key-2");
+    assert!(matches!(
+        encryptor.decrypt_json(&mut moved),
+        Err(EncryptionError::Decryption { field, .. }) if field == "to"
+    ));
+
+    let mut anonymous = encrypted;
+    anonymous["meta_data"]
+        .as_object_mut()
+        .unwrap()
+        .remove("idempotency_key");
+    assert!(matches!(
+        encryptor.decrypt_json(&mut anonymous),
+        Err(EncryptionError::InvalidMessage(_))
+    ));
+}
@@ -1,4 +1,4 @@
-use mykobo_rs::message_bus::models::base::PaymentDirection;
+use mykobo_rs::message_bus::models::base::{PayloadParseError, PaymentDirection};
 use mykobo_rs::message_bus::models::instruction::*;
 use mykobo_rs::message_bus::TransactionType;
 
@@ -87,8 +87,8 @@ fn test_payment_payload_from_string() {
 
     let payload: PaymentPayload = json.to_string().into();
     assert_eq!(payload.external_reference, "P763763453G");
-    assert_eq!(payload.currency, "EUR");
-    assert_eq!(payload.value, "123.00");
+    assert_eq!(payload.value.currency.code(), "EUR");
+    assert_eq!(payload.value.amount_string(), "123.00");
     assert_eq!(payload.source, "BANK_MODULR");
     assert_eq!(payload.direction, PaymentDirection::Inbound);
     assert_eq!(payload.reference, "MYK123344545");
@@ -133,8 +133,8 @@ fn test_correction_payload_from_string() {
 
     let payload: CorrectionPayload = json.to_string().into();
     assert_eq!(payload.reference, "REF123");
-    assert_eq!(payload.value, "50.00");
-    assert_eq!(payload.currency, "USD");
+    assert_eq!(payload.value.amount_string(), "50.00");
+    assert_eq!(payload.value.currency.code(), "USD");
 }
 
 #[test]
@@ -215,8 +215,8 @@ fn test_bank_payment_request_payload_from_string() {
 
     let payload: BankPaymentRequestPayload = json.to_string().into();
     assert_eq!(payload.reference, "BANK_REF123");
-    assert_eq!(payload.value, "500.00");
-    assert_eq!(payload.currency, "GBP");
+    assert_eq!(payload.value.amount_string(), "500.00");
+    assert_eq!(payload.value.currency.code(), "GBP");
     assert_eq!(payload.profile_id, "PROF456");
     assert_eq!(payload.message, Some("Bank transfer".to_string()));
 }
@@ -799,8 +799,8 @@ fn test_mint_payload_valid_without_message() {
     );
     assert!(payload.is_ok());
     let p = payload.unwrap();
-    assert_eq!(p.value, "50.00");
-    assert_eq!(p.currency, "USD");
+    assert_eq!(p.value.amount_string(), "50.00");
+    assert_eq!(p.value.currency.code(), "USD");
     assert_eq!(p.reference, "MYK789012");
     assert_eq!(p.message, None);
 }
@@ -852,8 +852,8 @@ fn test_mint_payload_from_string() {
     }"#;
 
     let payload: MintPayload = json.to_string().into();
-    assert_eq!(payload.value, "250.00");
-    assert_eq!(payload.currency, "GBP");
+    assert_eq!(payload.value.amount_string(), "250.00");
+    assert_eq!(payload.value.currency.code(), "GBP");
     assert_eq!(payload.reference, "MYK_MINT_001");
     assert_eq!(payload.chain, "stellar");
     assert_eq!(payload.message, Some("Minting tokens".to_string()));
@@ -920,8 +920,8 @@ fn test_burn_payload_valid_without_message() {
     );
     assert!(payload.is_ok());
     let p = payload.unwrap();
-    assert_eq!(p.value, "200.00");
-    assert_eq!(p.currency, "USD");
+    assert_eq!(p.value.amount_string(), "200.00");
+    assert_eq!(p.value.currency.code(), "USD");
     assert_eq!(p.reference, "MYK111222");
     assert_eq!(p.message, None);
 }
@@ -973,8 +973,8 @@ fn test_burn_payload_from_string() {
     }"#;
 
     let payload: BurnPayload = json.to_string().into();
-    assert_eq!(payload.value, "500.00");
-    assert_eq!(payload.currency, "USD");
+    assert_eq!(payload.value.amount_string(), "500.00");
+    assert_eq!(payload.value.currency.code(), "USD");
     assert_eq!(payload.reference, "MYK_BURN_001");
     assert_eq!(payload.chain, "stellar");
     assert_eq!(payload.message, Some("Burning tokens".to_string()));
@@ -1015,3 +1015,64 @@ fn test_burn_payload_serialization_without_optionals() {
     let deserialized: BurnPayload = serde_json::from_str(&serialized).unwrap();
     assert_eq!(payload, deserialized);
 }
+
+#[test]
+fn test_payment_payload_parse() {
+    let json = r#"{
+        "external_reference": "P763763453G",
+        "currency": "EUR",
+        "value": "123.00",
+        "source": "BANK_MODULR",
+        "direction": "INBOUND",
+        "reference": "MYK123344545"
+    }"#;
+
+    let payload: PaymentPayload = json.parse().unwrap();
+    assert_eq!(payload.external_reference, "P763763453G");
+    assert_eq!(PaymentPayload::try_from(json).unwrap(), payload);
+}
+
+#[test]
+fn test_payload_parse_malformed_json_returns_error() {
+    let error = "{not json".parse::<TransactionPayload>().unwrap_err();
+
+    assert!(matches!(
+        error,
+        PayloadParseError::Json {
+            class_name: "TransactionPayload",
+            ..
+        }
+    ));
+    assert!(error
+        .to_string()
+        .starts_with("failed to deserialize TransactionPayload:"));
+}
+
+#[test]
+fn test_payload_parse_runs_validation() {
+    let json = r#"{
+        "external_reference": "P763763453G",
+        "currency": "EUR",
+        "value": "123.00",
+        "source": "BANK_MODULR",
+        "direction": "INBOUND",
+        "reference": "  "
+    }"#;
+
+    match json.parse::<PaymentPayload>() {
+        Err(PayloadParseError::Validation(error)) => {
+            assert_eq!(error.class_name, "PaymentPayload");
+            assert_eq!(error.fields, vec!["reference".to_string()]);
+        }
+        other => panic!("Expected validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_update_profile_payload_parse_requires_profile_id() {
+    let result = UpdateProfilePayload::try_from(r#"{"profile_id": ""}"#);
+    assert!(matches!(result, Err(PayloadParseError::Validation(_))));
+
+    let payload = UpdateProfilePayload::try_from(r#"{"profile_id": "urn:usr:123"}"#).unwrap();
+    assert_eq!(payload.profile_id, "urn:usr:123");
+}
@@ -0,0 +1,223 @@
+use mykobo_rs::message_bus::kafka::admin::{ObservedTopic, TopicAdmin, TopicDrift, TopicSpec};
+use mykobo_rs::models::error::KafkaError;
+use serial_test::serial;
+use std::collections::BTreeMap;
+use std::env;
+use std::time::Duration;
+
+fn clear_kafka_env() {
+    env::remove_var("KAFKA_API_KEY");
+    env::remove_var("KAFKA_API_SECRET");
+    env::remove_var("KAFKA_API_PROTOCOL");
+    env::remove_var("KAFKA_API_SASL_MECHANISM");
+}
+
+fn observed(name: &str, partitions: i32, config: &[(&str, &str)]) -> ObservedTopic {
+    ObservedTopic {
+        name: name.to_string(),
+        partitions,
+        replication_factor: 1,
+        config: config
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+// ─── Spec tests ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_expand_without_companions_yields_main_topic_only() {
+    let spec = TopicSpec::new("mykobo.instructions", 6, 3);
+    let topics = spec.expand();
+
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "mykobo.instructions");
+}
+
+#[test]
+fn test_expand_with_retry_and_dead_letter() {
+    let spec = TopicSpec::new("mykobo.instructions", 6, 3)
+        .with_retention(Duration::from_secs(86_400))
+        .with_retry()
+        .with_dead_letter();
+
+    let names: Vec<String> = spec.expand().into_iter().map(|t| t.name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "mykobo.instructions",
+            "mykobo.instructions.retry",
+            "mykobo.instructions.dlq",
+        ]
+    );
+
+    for topic in spec.expand() {
+        assert_eq!(topic.partitions, 6);
+        assert_eq!(topic.retention_ms, Some(86_400_000));
+        assert!(!topic.retry && !topic.dead_letter);
+    }
+}
+
+#[test]
+fn test_expected_config_includes_retention() {
+    let spec = TopicSpec::new("events", 3, 1)
+        .with_retention(Duration::from_millis(1000))
+        .with_config("cleanup.policy", "delete");
+
+    let config = spec.expected_config();
+    assert_eq!(config.get("retention.ms"), Some(&"1000".to_string()));
+    assert_eq!(config.get("cleanup.policy"), Some(&"delete".to_string()));
+}
+
+#[test]
+fn test_from_yaml() {
+    let yaml = r#"
+- name: mykobo.instructions
+  partitions: 6
+  replication_factor: 3
+  retention_ms: 604800000
+  retry: true
+  dead_letter: true
+- name: mykobo.events
+  partitions: 3
+  replication_factor: 3
+  config:
+    cleanup.policy: delete
+"#;
+
+    let specs = TopicSpec::from_yaml(yaml).unwrap();
+    assert_eq!(specs.len(), 2);
+    assert_eq!(specs[0].expand().len(), 3);
+    assert_eq!(specs[0].retention_ms, Some(604_800_000));
+    assert_eq!(specs[1].retention_ms, None);
+    assert_eq!(
+        specs[1].config.get("cleanup.policy"),
+        Some(&"delete".to_string())
+    );
+}
+
+#[test]
+fn test_from_yaml_rejects_malformed_spec() {
+    let result = TopicSpec::from_yaml("- name: missing-partitions");
+    assert!(matches!(result, Err(KafkaError::Admin(_))));
+}
+
+// ─── Drift tests ─────────────────────────────────────────────────────────────
+
+#[test]
+fn test_no_drift_when_topic_matches() {
+    let spec = TopicSpec::new("events", 3, 1).with_retention(Duration::from_millis(1000));
+    let topic = observed(
+        "events",
+        3,
+        &[("retention.ms", "1000"), ("segment.ms", "1")],
+    );
+
+    assert!(spec.drift(Some(&topic)).is_empty());
+}
+
+#[test]
+fn test_drift_reports_missing_topic() {
+    let spec = TopicSpec::new("events", 3, 1);
+
+    assert_eq!(
+        spec.drift(None),
+        vec![TopicDrift::Missing {
+            topic: "events".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_drift_reports_partition_and_config_mismatches() {
+    let spec = TopicSpec::new("events", 6, 1)
+        .with_retention(Duration::from_millis(1000))
+        .with_config("cleanup.policy", "delete");
+    let topic = observed("events", 3, &[("retention.ms", "2000")]);
+
+    let drift = spec.drift(Some(&topic));
+    assert_eq!(
+        drift,
+        vec![
+            TopicDrift::PartitionCount {
+                topic: "events".to_string(),
+                expected: 6,
+                actual: 3,
+            },
+            TopicDrift::Config {
+                topic: "events".to_string(),
+                key: "cleanup.policy".to_string(),
+                expected: "delete".to_string(),
+                actual: None,
+            },
+            TopicDrift::Config {
+                topic: "events".to_string(),
+                key: "retention.ms".to_string(),
+                expected: "1000".to_string(),
+                actual: Some("2000".to_string()),
+            },
+        ]
+    );
+    assert_eq!(
+        drift[0].to_string(),
+        "events: expected 6 partitions, found 3"
+    );
+    assert_eq!(
+        drift[1].to_string(),
+        "events: expected cleanup.policy=delete, found <unset>"
+    );
+}
+
+#[test]
+fn test_drift_reports_replication_factor_mismatch() {
+    let spec = TopicSpec::new("events", 3, 3);
+    let topic = observed("events", 3, &[]);
+
+    let drift = spec.drift(Some(&topic));
+    assert_eq!(
+        drift,
+        vec![TopicDrift::ReplicationFactor {
+            topic: "events".to_string(),
+            expected: 3,
+            actual: 1,
+        }]
+    );
+    assert_eq!(
+        drift[0].to_string(),
+        "events: expected replication factor 3, found 1"
+    );
+}
+
+// ─── Client tests ────────────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_admin_creation_with_plaintext_protocol() {
+    clear_kafka_env();
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+
+    let admin = TopicAdmin::new("localhost:9092", 5);
+
+    assert!(
+        admin.is_ok(),
+        "Admin client should be created with PLAINTEXT protocol without credentials"
+    );
+}
+
+#[test]
+#[serial]
+fn test_admin_creation_fails_without_api_key_for_sasl_ssl() {
+    clear_kafka_env();
+
+    match TopicAdmin::new("localhost:9092", 5) {
+        Err(KafkaError::ClientCreation(msg)) => {
+            assert!(
+                msg.contains("KAFKA_API_KEY"),
+                "Error should mention KAFKA_API_KEY, got: {msg}"
+            );
+        }
+        Err(e) => panic!("Expected ClientCreation error, got: {e:?}"),
+        Ok(_) => panic!("Expected error but admin client was created successfully"),
+    }
+}
@@ -1,12 +1,22 @@
-use mykobo_rs::message_bus::kafka::consumer::EventConsumer;
+use chrono::{TimeZone, Utc};
+use mykobo_rs::message_bus::kafka::consumer::{
+    backfill_target, is_expired, EventConsumer, PartitionOffset, StartPosition,
+};
 use mykobo_rs::message_bus::kafka::producer::{
     build_message_headers, EventProducer, MESSAGE_SOURCE,
 };
+use mykobo_rs::message_bus::models::decode::StrictDecoder;
+use mykobo_rs::message_bus::models::message::PayloadKind;
+use mykobo_rs::message_bus::models::SchemaRegistry;
+use mykobo_rs::message_bus::{EventType, MessageBusMessage};
 use mykobo_rs::models::error::KafkaError;
 use rdkafka::message::Headers;
+use rdkafka::Offset;
 use serial_test::serial;
 use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[allow(unused_imports)]
 use mykobo_rs::message_bus::kafka::models::IncomingMessage;
@@ -131,6 +141,234 @@ async fn test_consumer_creation_with_sasl_ssl_credentials() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn test_consumer_seek_to_committed_keeps_subscription() {
+    clear_kafka_env();
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+
+    let (tx, _rx) = tokio::sync::mpsc::channel::<IncomingMessage<serde_json::Value>>(1);
+    let consumer = EventConsumer::<serde_json::Value>::new(
+        "localhost:9092",
+        "test-group",
+        "test-client",
+        3,
+        &["test-topic"],
+        tx,
+    )
+    .unwrap();
+
+    assert!(consumer.seek_to(StartPosition::Committed).is_ok());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_consumer_with_strict_decoding() {
+    clear_kafka_env();
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+
+    let (tx, _rx) = tokio::sync::mpsc::channel::<IncomingMessage<MessageBusMessage>>(1);
+    let consumer = EventConsumer::<MessageBusMessage>::new(
+        "localhost:9092",
+        "test-group",
+        "test-client",
+        3,
+        &["test-topic"],
+        tx,
+    )
+    .map(|consumer| {
+        consumer.with_strict_decoding(StrictDecoder::new().allow_raw_event(EventType::KycEvent))
+    });
+
+    assert!(consumer.is_ok());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_consumer_with_schemas() {
+    clear_kafka_env();
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+
+    let mut schemas = SchemaRegistry::new();
+    schemas.register(PayloadKind::Payment, 2).unwrap();
+    schemas
+        .register_upcaster(PayloadKind::Payment, 1, Ok)
+        .unwrap();
+    let (tx, _rx) = tokio::sync::mpsc::channel::<IncomingMessage<MessageBusMessage>>(1);
+    let consumer = EventConsumer::<MessageBusMessage>::new(
+        "localhost:9092",
+        "test-group",
+        "test-client",
+        3,
+        &["test-topic"],
+        tx,
+    )
+    .map(|consumer| consumer.with_schemas(Arc::new(schemas)));
+
+    assert!(consumer.is_ok());
+}
+
+#[test]
+fn test_is_expired_reads_only_meta_data_expires_at() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let message = |expires_at: serde_json::Value| {
+        serde_json::to_vec(&serde_json::json!({
+            "meta_data": {"source": "S", "expires_at": expires_at},
+            "payload": {"anything": true}
+        }))
+        .unwrap()
+    };
+
+    assert!(is_expired(&message("2024-05-01T11:59:59Z".into()), now));
+    assert!(is_expired(&message("2024-05-01T12:00:00Z".into()), now));
+    assert!(!is_expired(&message("2024-05-01T12:00:01Z".into()), now));
+    assert!(!is_expired(&message(serde_json::Value::Null), now));
+    assert!(!is_expired(&message("tomorrow".into()), now));
+    assert!(!is_expired(br#"{"meta_data": {}}"#, now));
+    assert!(!is_expired(b"not json", now));
+}
+
+// ─── Replay position tests ───────────────────────────────────────────────────
+
+fn two_partitions() -> Vec<(String, i32)> {
+    vec![("events".to_string(), 0), ("events".to_string(), 1)]
+}
+
+fn offsets_of(
+    position: &StartPosition,
+    partitions: &[(String, i32)],
+) -> Vec<(String, i32, Offset)> {
+    position
+        .partition_list(partitions)
+        .unwrap()
+        .elements()
+        .iter()
+        .map(|e| (e.topic().to_string(), e.partition(), e.offset()))
+        .collect()
+}
+
+#[test]
+fn test_start_position_beginning_assigns_every_partition() {
+    assert_eq!(
+        offsets_of(&StartPosition::Beginning, &two_partitions()),
+        vec![
+            ("events".to_string(), 0, Offset::Beginning),
+            ("events".to_string(), 1, Offset::Beginning),
+        ]
+    );
+}
+
+#[test]
+fn test_start_position_committed_uses_stored_offsets() {
+    assert_eq!(
+        offsets_of(&StartPosition::Committed, &two_partitions()),
+        vec![
+            ("events".to_string(), 0, Offset::Stored),
+            ("events".to_string(), 1, Offset::Stored),
+        ]
+    );
+}
+
+#[test]
+fn test_start_position_offsets_only_assigns_listed_partitions() {
+    let position = StartPosition::Offsets(vec![PartitionOffset {
+        topic: "events".to_string(),
+        partition: 1,
+        offset: 42,
+    }]);
+
+    assert_eq!(
+        offsets_of(&position, &two_partitions()),
+        vec![("events".to_string(), 1, Offset::Offset(42))]
+    );
+}
+
+#[test]
+fn test_start_position_timestamp_encodes_millis() {
+    let timestamp = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+    let position = StartPosition::Timestamp(timestamp);
+
+    assert_eq!(
+        offsets_of(&position, &two_partitions()),
+        vec![
+            (
+                "events".to_string(),
+                0,
+                Offset::Offset(timestamp.timestamp_millis())
+            ),
+            (
+                "events".to_string(),
+                1,
+                Offset::Offset(timestamp.timestamp_millis())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_backfill_target_skips_partitions_already_caught_up() {
+    let watermarks = (10, 20);
+
+    assert_eq!(
+        backfill_target(Offset::Beginning, Offset::Offset(20), watermarks),
+        Some(20)
+    );
+    assert_eq!(
+        backfill_target(Offset::Offset(15), Offset::Invalid, watermarks),
+        Some(20)
+    );
+    assert_eq!(
+        backfill_target(Offset::Offset(20), Offset::Invalid, watermarks),
+        None
+    );
+    assert_eq!(
+        backfill_target(Offset::End, Offset::Invalid, watermarks),
+        None
+    );
+    assert_eq!(
+        backfill_target(Offset::Beginning, Offset::Invalid, (20, 20)),
+        None
+    );
+
+    // Stored offsets start from the committed offset, or the beginning without one
+    assert_eq!(
+        backfill_target(Offset::Stored, Offset::Offset(25), watermarks),
+        None
+    );
+    assert_eq!(
+        backfill_target(Offset::Stored, Offset::Offset(12), watermarks),
+        Some(20)
+    );
+    assert_eq!(
+        backfill_target(Offset::Stored, Offset::Invalid, watermarks),
+        Some(20)
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_consume_until_now_needs_a_manual_assignment() {
+    clear_kafka_env();
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+
+    let (tx, _rx) = tokio::sync::mpsc::channel::<IncomingMessage<serde_json::Value>>(1);
+    let consumer = EventConsumer::<serde_json::Value>::new(
+        "localhost:9092",
+        "test-group",
+        "test-client",
+        3,
+        &["test-topic"],
+        tx,
+    )
+    .unwrap();
+    consumer.seek_to(StartPosition::Committed).unwrap();
+
+    assert!(matches!(
+        consumer.consume_until_now(Duration::from_secs(1)).await,
+        Err(KafkaError::Seek(_))
+    ));
+}
+
 // ─── Producer tests ──────────────────────────────────────────────────────────
 
 #[test]
@@ -0,0 +1,231 @@
+use chrono::{TimeZone, Utc};
+use mykobo_rs::message_bus::models::event::KycEventPayload;
+use mykobo_rs::message_bus::models::instruction::{MintPayload, StatusUpdatePayload};
+use mykobo_rs::message_bus::models::{
+    CustomerNotificationPayload, InstructionPayload, MessageBuilder, NotificationSubject,
+};
+use mykobo_rs::message_bus::{EventType, InstructionType, MessageBusMessage, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn mint_payload() -> MintPayload {
+    MintPayload::new(
+        "100.00".to_string(),
+        "EURC".to_string(),
+        "REF123".to_string(),
+        "stellar".to_string(),
+        None,
+    )
+    .unwrap()
+}
+
+fn kyc_payload() -> KycEventPayload {
+    KycEventPayload::new(
+        "KYC Update".to_string(),
+        "user-1".to_string(),
+        Some("completed".to_string()),
+        Some("approved".to_string()),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_instruction_type_comes_from_payload() {
+    let message = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        message.meta_data.instruction_type,
+        Some(InstructionType::Mint)
+    );
+    assert_eq!(message.meta_data.event, None);
+    assert_eq!(message.meta_data.source, "LEDGER_SERVICE");
+    assert!(matches!(message.payload, Payload::Mint(_)));
+    assert_eq!(
+        <StatusUpdatePayload as InstructionPayload>::INSTRUCTION_TYPE,
+        InstructionType::StatusUpdate
+    );
+}
+
+#[test]
+fn test_builder_fills_created_at_and_random_key() {
+    let first = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .build()
+        .unwrap();
+    let second = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .build()
+        .unwrap();
+
+    assert!(chrono::DateTime::parse_from_rfc3339(&first.meta_data.created_at).is_ok());
+    assert!(uuid::Uuid::parse_str(&first.meta_data.idempotency_key).is_ok());
+    assert_ne!(
+        first.meta_data.idempotency_key,
+        second.meta_data.idempotency_key
+    );
+}
+
+#[test]
+fn test_event_builder_with_explicit_fields() {
+    let created_at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let message = MessageBusMessage::event(EventType::KycEvent, kyc_payload())
+        .source("IDENTITY_SERVICE")
+        .token("test.token.here")
+        .created_at(created_at)
+        .idempotency_key("key-123")
+        .ip_address("127.0.0.1")
+        .build()
+        .unwrap();
+
+    assert_eq!(message.meta_data.event, Some(EventType::KycEvent));
+    assert_eq!(message.meta_data.instruction_type, None);
+    assert_eq!(message.meta_data.created_at, "2024-05-01T12:00:00Z");
+    assert_eq!(message.meta_data.idempotency_key, "key-123");
+    assert_eq!(message.meta_data.ip_address.as_deref(), Some("127.0.0.1"));
+}
+
+#[test]
+fn test_builder_derives_idempotency_key() {
+    let subject = NotificationSubject::Relay {
+        id: "abc-123".into(),
+        source_chain: "stellar".into(),
+        destination_chain: "solana".into(),
+    };
+    let payload = CustomerNotificationPayload {
+        subject,
+        data: json!({"email": "u@e.com"}),
+    };
+    let event = MessageBusMessage::event(EventType::RelayInitiated, payload)
+        .source("CIRCLE_SERVICE")
+        .token("test.token.here")
+        .idempotency_key_for("circle", "abc-123")
+        .build()
+        .unwrap();
+    assert_eq!(
+        event.meta_data.idempotency_key,
+        "circle:relay_initiated:abc-123"
+    );
+
+    let instruction = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .idempotency_key_for("ledger", "REF123")
+        .build()
+        .unwrap();
+    assert_eq!(instruction.meta_data.idempotency_key, "ledger:mint:REF123");
+}
+
+#[test]
+fn test_event_payload_mismatch_fails_build() {
+    let result = MessageBusMessage::event(EventType::NewUser, kyc_payload())
+        .source("IDENTITY_SERVICE")
+        .token("test.token.here")
+        .build();
+
+    let error = result.unwrap_err();
+    assert_eq!(error.class_name, "MessageBusMessage");
+}
+
+#[test]
+fn test_builder_from_decoded_payload() {
+    let instruction =
+        MessageBuilder::for_instruction(InstructionType::Mint, Payload::Mint(mint_payload()))
+            .source("LEDGER_SERVICE")
+            .token("test.token.here")
+            .build()
+            .unwrap();
+    assert_eq!(
+        instruction.meta_data.instruction_type,
+        Some(InstructionType::Mint)
+    );
+
+    let mismatched = MessageBuilder::for_event(EventType::NewUser, Payload::Mint(mint_payload()))
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .build();
+    assert_eq!(mismatched.unwrap_err().class_name, "MessageBusMessage");
+}
+
+#[test]
+fn test_builder_reports_missing_source_and_token() {
+    let error = MessageBusMessage::instruction(mint_payload())
+        .build()
+        .unwrap_err();
+
+    assert_eq!(error.class_name, "MetaData");
+    assert_eq!(
+        error.fields,
+        vec!["source".to_string(), "token".to_string()]
+    );
+}
+
+#[test]
+fn test_builder_matches_create() {
+    let created = MessageBusMessage::create(
+        "LEDGER_SERVICE".to_string(),
+        Payload::Mint(mint_payload()),
+        "test.token.here".to_string(),
+        Some(InstructionType::Mint),
+        None,
+        Some("key-123".to_string()),
+        None,
+    )
+    .unwrap();
+    let built = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .idempotency_key("key-123")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        built.meta_data.instruction_type,
+        created.meta_data.instruction_type
+    );
+    assert_eq!(built.payload, created.payload);
+    assert_eq!(
+        serde_json::to_value(&built.payload).unwrap(),
+        serde_json::to_value(&created.payload).unwrap()
+    );
+}
+
+#[test]
+fn test_builder_links_child_to_parent_and_sets_ttl() {
+    let created_at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let parent = MessageBusMessage::instruction(mint_payload())
+        .source("LEDGER_SERVICE")
+        .token("test.token.here")
+        .idempotency_key("parent-key")
+        .tenant("wallet.example.com")
+        .build()
+        .unwrap();
+
+    let child = MessageBusMessage::event(EventType::KycEvent, kyc_payload())
+        .source("IDENTITY_SERVICE")
+        .token("test.token.here")
+        .ttl(chrono::Duration::minutes(10))
+        .created_at(created_at)
+        .caused_by(&parent.meta_data)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        child.meta_data.correlation_id.as_deref(),
+        Some("parent-key")
+    );
+    assert_eq!(child.meta_data.causation_id.as_deref(), Some("parent-key"));
+    assert_eq!(
+        child.meta_data.tenant.as_deref(),
+        Some("wallet.example.com")
+    );
+    assert_eq!(
+        child.meta_data.expires_at.as_deref(),
+        Some("2024-05-01T12:10:00Z")
+    );
+}
@@ -46,8 +46,8 @@ fn test_deserialize_payment_message() {
     match message.payload {
         Payload::Payment(payload) => {
             assert_eq!(payload.external_reference, "P763763453G");
-            assert_eq!(payload.currency, "EUR");
-            assert_eq!(payload.value, "123.00");
+            assert_eq!(payload.value.currency.code(), "EUR");
+            assert_eq!(payload.value.amount_string(), "123.00");
             assert_eq!(payload.payer_name, Some("John Doe".to_string()));
         }
         _ => panic!("Expected Payment payload"),
@@ -158,8 +158,8 @@ fn test_deserialize_correction_message() {
     match message.payload {
         Payload::Correction(payload) => {
             assert_eq!(payload.reference, "REF456");
-            assert_eq!(payload.value, "75.00");
-            assert_eq!(payload.currency, "GBP");
+            assert_eq!(payload.value.amount_string(), "75.00");
+            assert_eq!(payload.value.currency.code(), "GBP");
             assert_eq!(payload.message, "Corrected amount due to error");
         }
         _ => panic!("Expected Correction payload"),
@@ -248,8 +248,8 @@ fn test_deserialize_bank_payment_request_message() {
     match message.payload {
         Payload::BankPaymentRequest(payload) => {
             assert_eq!(payload.reference, "BANK_REF123");
-            assert_eq!(payload.value, "500.00");
-            assert_eq!(payload.currency, "USD");
+            assert_eq!(payload.value.amount_string(), "500.00");
+            assert_eq!(payload.value.currency.code(), "USD");
             assert_eq!(payload.profile_id, "PROF456");
             assert_eq!(payload.message, Some("Bank transfer request".to_string()));
         }
@@ -660,7 +660,85 @@ fn test_deserialize_raw_payload_message() {
     }
 }
 
+/// Payload may come before meta_data, and unknown top level fields are ignored
+#[test]
+fn test_deserialize_payload_before_meta_data() {
+    let json = r#"{
+        "payload": {"value": "10.00", "currency": "EUR", "reference": "MINT-1", "chain": "STELLAR"},
+        "trace": {"span": 7},
+        "meta_data": {
+            "source": "LEDGER_SERVICE",
+            "created_at": "2021-01-01T00:00:00Z",
+            "token": "test.token.here",
+            "idempotency_key": "key-123",
+            "instruction_type": "MINT"
+        }
+    }"#;
+
+    let message: MessageBusMessage = serde_json::from_str(json).unwrap();
+
+    match message.payload {
+        Payload::Mint(payload) => assert_eq!(payload.reference, "MINT-1"),
+        other => panic!("Expected Mint payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_deserialize_rejects_duplicate_and_missing_fields() {
+    let meta_data = r#"{
+        "source": "RAW_SERVICE",
+        "created_at": "2022-04-01T00:00:00Z",
+        "token": "raw.token",
+        "idempotency_key": "raw-key-404",
+        "instruction_type": "PAYMENT"
+    }"#;
+
+    let duplicate = format!(r#"{{"meta_data": {meta_data}, "payload": "a", "payload": "b"}}"#);
+    let error = serde_json::from_str::<MessageBusMessage>(&duplicate).unwrap_err();
+    assert!(error.to_string().contains("duplicate field `payload`"));
+
+    let missing = format!(r#"{{"meta_data": {meta_data}}}"#);
+    let error = serde_json::from_str::<MessageBusMessage>(&missing).unwrap_err();
+    assert!(error.to_string().contains("missing field `payload`"));
+}
+
 /// Test full roundtrip serialization/deserialization for Payment message
+/// A type added by a newer producer decodes with its payload kept as raw JSON
+#[test]
+fn test_deserialize_unknown_event_message() {
+    let json = r#"{
+        "meta_data": {
+            "source": "LENDING_SERVICE",
+            "created_at": "2021-01-01T00:00:00Z",
+            "token": "test.token.here",
+            "idempotency_key": "key-123",
+            "event": "LOAN_DISBURSED"
+        },
+        "payload": {"loan_id": "L-1", "amount": "250.00"}
+    }"#;
+
+    let message: MessageBusMessage = serde_json::from_str(json).unwrap();
+
+    assert_eq!(
+        message.meta_data.event,
+        Some(EventType::Unknown("LOAN_DISBURSED".to_string()))
+    );
+    assert!(message.meta_data.has_unknown_type());
+    assert_eq!(message.meta_data.payload_kind(), None);
+    assert_eq!(
+        message.payload,
+        Payload::Unknown(serde_json::json!({"loan_id": "L-1", "amount": "250.00"}))
+    );
+    assert!(message.validate().is_ok());
+
+    let reserialized = serde_json::to_value(&message).unwrap();
+    assert_eq!(reserialized["meta_data"]["event"], "LOAN_DISBURSED");
+    assert_eq!(
+        serde_json::to_string(&reserialized["payload"]).unwrap(),
+        r#"{"loan_id":"L-1","amount":"250.00"}"#
+    );
+}
+
 #[test]
 fn test_payment_message_roundtrip() {
     let payload = PaymentPayload::new(
@@ -912,7 +990,7 @@ fn test_roundtrip_complex_transaction_message() {
             assert_eq!(payload.first_name, "María José");
             assert_eq!(payload.last_name, "García-Rodríguez");
             assert_eq!(payload.transaction_type, TransactionType::Withdraw);
-            assert_eq!(payload.value, "1234.56");
+            assert_eq!(payload.value.amount_string(), "1234.56");
             assert_eq!(
                 payload.payee,
                 Some("IBAN:GB29NWBK60161331926819".to_string())
@@ -1,3 +1,4 @@
+use chrono::{Duration, TimeZone, Utc};
 use mykobo_rs::message_bus::models::base::PaymentDirection;
 use mykobo_rs::message_bus::models::event::*;
 use mykobo_rs::message_bus::models::instruction::*;
@@ -871,7 +872,6 @@ fn test_message_with_address_onboarded_event() {
     assert_eq!(msg.meta_data.event, Some(EventType::AddressOnboarded));
 }
 
-
 #[test]
 fn test_address_onboarded_validates_payload_type() {
     let payload =
@@ -918,3 +918,104 @@ fn test_message_address_onboarded_serialization_roundtrip() {
     assert_eq!(message, deserialized);
 }
 
+fn parent_metadata() -> MetaData {
+    MetaData::new(
+        "BANKING_SERVICE".to_string(),
+        "2021-01-01T00:00:00Z".to_string(),
+        "test.token.here".to_string(),
+        "parent-key".to_string(),
+        Some(InstructionType::Payment),
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+fn child_metadata() -> MetaData {
+    MetaData::new(
+        "LEDGER_SERVICE".to_string(),
+        "2021-01-01T00:00:01Z".to_string(),
+        "test.token.here".to_string(),
+        "child-key".to_string(),
+        None,
+        Some(EventType::NewTransaction),
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_metadata_caused_by_starts_correlation_from_parent() {
+    let parent = parent_metadata().with_tenant("wallet.example.com");
+    let child = child_metadata().caused_by(&parent);
+
+    assert_eq!(child.correlation_id.as_deref(), Some("parent-key"));
+    assert_eq!(child.causation_id.as_deref(), Some("parent-key"));
+    assert_eq!(child.tenant.as_deref(), Some("wallet.example.com"));
+
+    let grandchild = child_metadata().caused_by(&child);
+    assert_eq!(grandchild.correlation_id.as_deref(), Some("parent-key"));
+    assert_eq!(grandchild.causation_id.as_deref(), Some("child-key"));
+}
+
+#[test]
+fn test_metadata_caused_by_keeps_own_tenant_and_drops_expiry() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let parent = parent_metadata()
+        .with_tenant("a.example.com")
+        .with_expires_at(now);
+    let child = child_metadata()
+        .with_tenant("b.example.com")
+        .caused_by(&parent);
+
+    assert_eq!(child.tenant.as_deref(), Some("b.example.com"));
+    assert_eq!(child.expires_at, None);
+}
+
+#[test]
+fn test_metadata_expiry() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let metadata = parent_metadata().with_expires_at(now + Duration::minutes(5));
+
+    assert_eq!(metadata.expires_at.as_deref(), Some("2024-05-01T12:05:00Z"));
+    assert!(!metadata.is_expired_at(now));
+    assert!(metadata.is_expired_at(now + Duration::minutes(5)));
+    assert!(!parent_metadata().is_expired_at(now));
+}
+
+#[test]
+fn test_metadata_rejects_malformed_expires_at() {
+    let mut metadata = parent_metadata();
+    metadata.expires_at = Some("in five minutes".to_string());
+
+    let error = metadata.validate().unwrap_err();
+    assert_eq!(
+        error.fields,
+        vec!["expires_at: must be an RFC 3339 timestamp, got \"in five minutes\"".to_string()]
+    );
+}
+
+#[test]
+fn test_metadata_correlation_fields_round_trip() {
+    let json = r#"{
+        "source": "BANKING_SERVICE",
+        "created_at": "2021-01-01T00:00:00Z",
+        "token": "test.token.here",
+        "idempotency_key": "child-key",
+        "event": "NEW_TRANSACTION",
+        "correlation_id": "root-key",
+        "causation_id": "parent-key",
+        "expires_at": "2021-01-01T00:05:00Z",
+        "client_domain": "wallet.example.com"
+    }"#;
+    let metadata: MetaData = serde_json::from_str(json).unwrap();
+    assert_eq!(metadata.tenant.as_deref(), Some("wallet.example.com"));
+
+    let value = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(value["tenant"], "wallet.example.com");
+    assert_eq!(value["correlation_id"], "root-key");
+    assert!(serde_json::to_value(parent_metadata())
+        .unwrap()
+        .get("correlation_id")
+        .is_none());
+}
@@ -0,0 +1,135 @@
+use mykobo_rs::message_bus::kafka::consumer::EventConsumer;
+use mykobo_rs::message_bus::kafka::metrics::BusMetrics;
+use mykobo_rs::message_bus::kafka::models::IncomingMessage;
+use mykobo_rs::message_bus::kafka::producer::EventProducer;
+use mykobo_rs::models::error::KafkaError;
+use prometheus::Registry;
+use rdkafka::statistics::{Partition, Statistics, Topic};
+use serial_test::serial;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn consumer_statistics(topic: &str, lags: &[(i32, i64)]) -> Statistics {
+    let partitions = lags
+        .iter()
+        .map(|(partition, lag)| {
+            (
+                *partition,
+                Partition {
+                    partition: *partition,
+                    consumer_lag: *lag,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    Statistics {
+        client_type: "consumer".to_string(),
+        topics: HashMap::from([(
+            topic.to_string(),
+            Topic {
+                topic: topic.to_string(),
+                partitions,
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_record_statistics_exports_partition_lag() {
+    let metrics = BusMetrics::new().unwrap();
+    metrics.record_statistics(&consumer_statistics("events", &[(0, 12), (1, 0), (-1, 99)]));
+
+    let rendered = metrics.render().unwrap();
+    assert!(rendered.contains(r#"mykobo_bus_consumer_lag{partition="0",topic="events"} 12"#));
+    assert!(rendered.contains(r#"mykobo_bus_consumer_lag{partition="1",topic="events"} 0"#));
+    assert!(!rendered.contains(r#"partition="-1""#));
+}
+
+#[test]
+fn test_record_statistics_skips_unconsumed_partitions() {
+    let metrics = BusMetrics::new().unwrap();
+    metrics.record_statistics(&consumer_statistics("events", &[(0, -1)]));
+
+    assert!(!metrics
+        .render()
+        .unwrap()
+        .contains("mykobo_bus_consumer_lag{"));
+}
+
+#[test]
+fn test_record_statistics_exports_producer_queue_depth() {
+    let metrics = BusMetrics::new().unwrap();
+    metrics.record_statistics(&Statistics {
+        client_type: "producer".to_string(),
+        msg_cnt: 42,
+        ..Default::default()
+    });
+
+    assert!(metrics
+        .render()
+        .unwrap()
+        .contains("mykobo_bus_producer_queue_depth 42"));
+}
+
+#[test]
+fn test_consumer_loop_counters_and_latency() {
+    let metrics = BusMetrics::new().unwrap();
+    metrics.record_processed("events", Duration::from_millis(5));
+    metrics.record_processed("events", Duration::from_millis(7));
+    metrics.record_retry("events");
+    metrics.record_expired("events");
+    metrics.record_quarantined("events");
+    metrics.record_failed("events", Duration::from_secs(3));
+    metrics.record_delivery("instructions", Duration::from_millis(20));
+
+    let rendered = metrics.render().unwrap();
+    assert!(rendered.contains(r#"mykobo_bus_messages_processed_total{topic="events"} 2"#));
+    assert!(rendered.contains(r#"mykobo_bus_messages_retried_total{topic="events"} 1"#));
+    assert!(rendered.contains(r#"mykobo_bus_messages_failed_total{topic="events"} 1"#));
+    assert!(rendered.contains(r#"mykobo_bus_messages_expired_total{topic="events"} 1"#));
+    assert!(rendered.contains(r#"mykobo_bus_messages_quarantined_total{topic="events"} 1"#));
+    assert!(rendered.contains(r#"mykobo_bus_handler_latency_seconds_count{topic="events"} 3"#));
+    assert!(rendered
+        .contains(r#"mykobo_bus_producer_delivery_latency_seconds_count{topic="instructions"} 1"#));
+}
+
+#[test]
+fn test_registering_twice_in_a_shared_registry_fails() {
+    let registry = Registry::new();
+    BusMetrics::with_registry(registry.clone()).unwrap();
+
+    assert!(matches!(
+        BusMetrics::with_registry(registry),
+        Err(KafkaError::Metrics(_))
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_clients_creation_with_metrics() {
+    env::remove_var("KAFKA_API_KEY");
+    env::remove_var("KAFKA_API_SECRET");
+    env::set_var("KAFKA_API_PROTOCOL", "PLAINTEXT");
+    let metrics = Arc::new(BusMetrics::new().unwrap());
+
+    let (tx, _rx) = tokio::sync::mpsc::channel::<IncomingMessage<serde_json::Value>>(1);
+    let consumer = EventConsumer::<serde_json::Value>::new_with_metrics(
+        "localhost:9092",
+        "test-group",
+        "test-client",
+        3,
+        &["test-topic"],
+        tx,
+        metrics.clone(),
+    );
+    let producer = EventProducer::new_with_metrics("localhost:9092", 5, "test-topic", metrics);
+
+    assert!(consumer.is_ok());
+    assert!(producer.is_ok());
+}
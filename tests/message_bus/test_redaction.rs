@@ -0,0 +1,165 @@
+use mykobo_rs::message_bus::kafka::models::IncomingMessage;
+use mykobo_rs::message_bus::models::event::PasswordResetEventPayload;
+use mykobo_rs::message_bus::models::instruction::MintPayload;
+use mykobo_rs::message_bus::models::{Redact, Sensitivity, REDACTED};
+use mykobo_rs::message_bus::{EventType, MessageBusMessage, MetaData, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn password_reset_message() -> MessageBusMessage {
+    MessageBusMessage::event(
+        EventType::PasswordResetRequested,
+        PasswordResetEventPayload::new(
+            "user@example.com".to_string(),
+            "Reset your password".to_string(),
+            "hunter2".to_string(),
+        )
+        .unwrap(),
+    )
+    .source("IDENTITY_SERVICE")
+    .token("secret.service.token")
+    .ip_address("203.0.113.7")
+    .idempotency_key("key-1")
+    .build()
+    .unwrap()
+}
+
+#[test]
+fn test_debug_redacts_secrets_and_pii() {
+    let message = password_reset_message();
+    let debug = format!("{:?}", message);
+
+    for leaked in [
+        "secret.service.token",
+        "203.0.113.7",
+        "user@example.com",
+        "hunter2",
+    ] {
+        assert!(!debug.contains(leaked), "{leaked} leaked in {debug}");
+    }
+    assert!(debug.contains("Reset your password"));
+    assert!(debug.contains("IDENTITY_SERVICE"));
+    assert!(!message.payload.to_string().contains("hunter2"));
+}
+
+#[test]
+fn test_redacted_value_keeps_shape() {
+    let value = password_reset_message().redacted_value();
+
+    assert_eq!(value["meta_data"]["token"], REDACTED);
+    assert_eq!(value["meta_data"]["ip_address"], REDACTED);
+    assert_eq!(value["meta_data"]["source"], "IDENTITY_SERVICE");
+    assert_eq!(
+        value["payload"],
+        json!({"to": REDACTED, "subject": "Reset your password", "password": REDACTED})
+    );
+}
+
+#[test]
+fn test_unset_optional_fields_stay_null() {
+    let meta_data = MetaData::new(
+        "LEDGER_SERVICE".to_string(),
+        "2024-05-01T12:00:00Z".to_string(),
+        "token".to_string(),
+        "key-1".to_string(),
+        None,
+        Some(EventType::NewUser),
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(meta_data.redacted_value()["token"], REDACTED);
+    assert!(meta_data.redacted_value().get("ip_address").is_none());
+}
+
+#[test]
+fn test_raw_payloads_are_redacted_entirely() {
+    let payload = Payload::Raw("card 4111 1111 1111 1111".to_string());
+
+    assert_eq!(format!("{:?}", payload), r#"Raw("[REDACTED]")"#);
+    assert_eq!(payload.redacted_value(), json!(REDACTED));
+}
+
+#[test]
+fn test_unredacted_opt_in() {
+    let message = password_reset_message();
+    let full = message.unredacted().to_string();
+
+    assert!(full.contains("hunter2"));
+    assert!(full.contains("secret.service.token"));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&full).unwrap(),
+        serde_json::to_value(&message).unwrap()
+    );
+}
+
+#[test]
+fn test_incoming_message_display_is_redacted() {
+    let incoming = IncomingMessage::new(
+        HashMap::from([("source".to_string(), "ledger".to_string())]),
+        password_reset_message(),
+    )
+    .with_key("ledger:password_reset:1")
+    .with_position("mykobo.events", 0, 42);
+
+    let displayed = incoming.to_string();
+    assert!(!displayed.contains("hunter2"));
+    assert!(!displayed.contains("secret.service.token"));
+    assert!(displayed.contains(r#""source":"ledger""#));
+    assert!(displayed.contains(r#""key":"ledger:password_reset:1""#));
+    assert!(displayed.contains(r#""topic":"mykobo.events","partition":0,"offset":42"#));
+    assert!(incoming.unredacted().to_string().contains("hunter2"));
+}
+
+#[test]
+fn test_incoming_message_debug_is_redacted() {
+    let payload = serde_json::to_value(password_reset_message()).unwrap();
+    let incoming =
+        IncomingMessage::new(HashMap::new(), payload).with_position("mykobo.events", 3, 7);
+
+    let debugged = format!("{incoming:?}");
+    assert!(!debugged.contains("hunter2"));
+    assert!(!debugged.contains("secret.service.token"));
+    assert!(debugged.starts_with("IncomingMessage {"));
+    assert!(debugged.contains(r#"topic: "mykobo.events", partition: 3, offset: 7"#));
+}
+
+#[test]
+fn test_untyped_incoming_message_display_redacts_the_payload() {
+    let payload = serde_json::to_value(password_reset_message()).unwrap();
+    let incoming = IncomingMessage::new(
+        HashMap::from([("source".to_string(), "ledger".to_string())]),
+        payload.clone(),
+    );
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&incoming.to_string()).unwrap(),
+        json!({
+            "headers": { "source": "ledger" },
+            "payload": REDACTED,
+            "key": null,
+            "topic": "",
+            "partition": 0,
+            "offset": 0,
+        })
+    );
+    assert!(incoming.unredacted().to_string().contains("hunter2"));
+    assert_eq!(
+        serde_json::Value::Null.redacted_value(),
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn test_sensitive_field_annotations() {
+    let sensitivities: Vec<_> = PasswordResetEventPayload::SENSITIVE_FIELDS
+        .iter()
+        .map(|field| (field.name, field.sensitivity))
+        .collect();
+    assert_eq!(
+        sensitivities,
+        vec![("to", Sensitivity::Pii), ("password", Sensitivity::Secret)]
+    );
+    assert!(MintPayload::SENSITIVE_FIELDS.is_empty());
+}
@@ -0,0 +1,202 @@
+use mykobo_rs::message_bus::kafka::routing::{Route, RoutingError, RoutingTable, ROUTING_TABLE};
+use mykobo_rs::message_bus::models::{EventType, InstructionType};
+use mykobo_rs::message_bus::MessageBusMessage;
+use mykobo_rs::notification_contract::{Registry, Severity};
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+fn routing_yaml() -> String {
+    std::fs::read_to_string("src/message_bus/kafka/routing.yaml").unwrap()
+}
+
+fn fixture(path: &str) -> Value {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn message(value: Value) -> MessageBusMessage {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_every_type_is_routed() {
+    for instruction_type in InstructionType::ALL {
+        assert!(ROUTING_TABLE.instructions.contains_key(instruction_type));
+    }
+    for event in EventType::ALL {
+        assert!(ROUTING_TABLE.events.contains_key(event));
+    }
+    assert_eq!(
+        ROUTING_TABLE.topics().into_iter().collect::<Vec<_>>(),
+        vec![
+            "mykobo.events",
+            "mykobo.events.critical",
+            "mykobo.instructions"
+        ]
+    );
+}
+
+#[test]
+fn test_instructions_are_keyed_by_reference() {
+    let payment = message(fixture("tests/fixtures/message_bus/payment.json"));
+    assert_eq!(
+        ROUTING_TABLE.route(&payment).unwrap(),
+        Route {
+            topic: "mykobo.instructions",
+            key: "MYK123344545".to_string(),
+            lane: None,
+        }
+    );
+}
+
+#[test]
+fn test_key_fields_are_tried_in_order() {
+    let completed = message(fixture(
+        "tests/fixtures/notification/customer_relay_completed.json",
+    ));
+    assert_eq!(
+        ROUTING_TABLE.route(&completed).unwrap(),
+        Route {
+            topic: "mykobo.events",
+            key: "abc-123".to_string(),
+            lane: None,
+        }
+    );
+
+    let mut failed = fixture("tests/fixtures/notification/platform_relay_failed.json");
+    assert_eq!(
+        ROUTING_TABLE.route(&message(failed.clone())).unwrap().key,
+        "circle:relay_failed:abc-123"
+    );
+    failed["meta_data"]["correlation_id"] = "relay:abc-123".into();
+    assert_eq!(
+        ROUTING_TABLE.route(&message(failed)).unwrap().key,
+        "relay:abc-123"
+    );
+}
+
+#[test]
+fn test_critical_notifications_use_the_critical_lane() {
+    let lane = &ROUTING_TABLE.lanes[&Severity::Critical];
+    assert_eq!(lane.topic, "mykobo.events.critical");
+    assert_eq!(lane.producer["linger.ms"], "0");
+
+    let failed = message(fixture(
+        "tests/fixtures/notification/platform_relay_failed.json",
+    ));
+    assert_eq!(
+        ROUTING_TABLE.route(&failed).unwrap(),
+        Route {
+            topic: "mykobo.events.critical",
+            key: "circle:relay_failed:abc-123".to_string(),
+            lane: Some(Severity::Critical),
+        }
+    );
+
+    let burst = message(fixture(
+        "tests/fixtures/notification/platform_circle_api_5xx_burst.json",
+    ));
+    let route = ROUTING_TABLE.route(&burst).unwrap();
+    assert_eq!((route.topic, route.lane), ("mykobo.events", None));
+}
+
+#[test]
+fn test_lanes_follow_registry_severity() {
+    let registry_yaml = std::fs::read_to_string("src/notification_contract/registry.yaml").unwrap();
+    let registry = Registry::from_str(&registry_yaml.replace(
+        "CIRCLE_API_5XX_BURST:   { kind: notification, audience: platform, severity: warning }",
+        "CIRCLE_API_5XX_BURST:   { kind: notification, audience: platform, severity: critical }",
+    ))
+    .unwrap();
+    let table = RoutingTable::from_yaml_with_registry(&routing_yaml(), &registry).unwrap();
+
+    let burst = message(fixture(
+        "tests/fixtures/notification/platform_circle_api_5xx_burst.json",
+    ));
+    let route = table.route(&burst).unwrap();
+    assert_eq!(
+        (route.topic, route.lane),
+        ("mykobo.events.critical", Some(Severity::Critical))
+    );
+
+    // Without lanes every event keeps its own topic
+    let yaml = routing_yaml();
+    let start = yaml.find("lanes:").unwrap();
+    let end = yaml.find("instructions:").unwrap();
+    let table = RoutingTable::from_yaml(&format!("{}{}", &yaml[..start], &yaml[end..])).unwrap();
+    assert!(table.lanes.is_empty());
+    let failed = message(fixture(
+        "tests/fixtures/notification/platform_relay_failed.json",
+    ));
+    assert_eq!(table.route(&failed).unwrap().topic, "mykobo.events");
+}
+
+#[test]
+fn test_custom_table_routes_to_its_topics() {
+    let yaml = routing_yaml().replace(
+        "PAYMENT:              { topic: mykobo.instructions, key: payload.reference }",
+        "PAYMENT:              { topic: banking.payments, key: payload.external_reference }",
+    );
+    let table = RoutingTable::from_yaml(&yaml).unwrap();
+
+    let payment = message(fixture("tests/fixtures/message_bus/payment.json"));
+    assert_eq!(
+        table.route(&payment).unwrap(),
+        Route {
+            topic: "banking.payments",
+            key: "P763763453G".to_string(),
+            lane: None,
+        }
+    );
+    assert!(table.topics().contains("banking.payments"));
+}
+
+#[test]
+fn test_missing_key_is_an_error() {
+    let yaml = routing_yaml().replace(
+        "PAYMENT:              { topic: mykobo.instructions, key: payload.reference }",
+        "PAYMENT:              { topic: mykobo.instructions, key: payload.missing }",
+    );
+    let table = RoutingTable::from_yaml(&yaml).unwrap();
+
+    let payment = message(fixture("tests/fixtures/message_bus/payment.json"));
+    assert!(matches!(
+        table.route(&payment),
+        Err(RoutingError::MissingKey { fields, .. }) if fields == vec!["payload.missing"]
+    ));
+}
+
+#[test]
+fn test_invalid_tables_are_rejected() {
+    let yaml = routing_yaml();
+    let invalid = [
+        yaml.replace("  MINT:  ", "  # MINT:"),
+        yaml.replace("  RELAY_FAILED:  ", "  # RELAY_FAILED:"),
+        yaml.replace("  MINT:  ", "  MINTED:"),
+        yaml.replace("  RELAY_FAILED:  ", "  RELAY_FAILURE:"),
+        yaml.replace("key: payload.profile_id", "key: profile_id"),
+        yaml.replace("key: payload.profile_id", "key: payload..profile_id"),
+        yaml.replace(
+            "topic: mykobo.instructions, key: payload.profile_id",
+            "topic: '', key: payload.profile_id",
+        ),
+        yaml.replace("version: 1", "version: 2"),
+        yaml.replace("topic: mykobo.events.critical", "topic: ' '"),
+    ];
+    for yaml in invalid {
+        assert!(matches!(
+            RoutingTable::from_yaml(&yaml),
+            Err(RoutingError::Invalid(_))
+        ));
+    }
+
+    assert!(matches!(
+        RoutingTable::from_yaml(
+            &yaml.replace("key: payload.profile_id", "keys: payload.profile_id")
+        ),
+        Err(RoutingError::Yaml(_))
+    ));
+    assert!(matches!(
+        RoutingTable::from_yaml(&yaml.replace("  critical:\n", "  urgent:\n")),
+        Err(RoutingError::Yaml(_))
+    ));
+}
@@ -0,0 +1,285 @@
+use mykobo_rs::message_bus::models::message::PayloadKind;
+use mykobo_rs::message_bus::models::schema::{
+    SchemaError, SchemaRegistry, DEFAULT_SCHEMA_VERSION, SCHEMA_REGISTRY,
+};
+use mykobo_rs::message_bus::{InstructionType, MessageBusMessage, MetaData, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+// Hypothetical history of PaymentPayload: v1 called `value` `amount`, v2 had no `source`.
+fn rename_amount_to_value(mut payload: Value) -> Result<Value, String> {
+    let object = payload.as_object_mut().ok_or("payload is not an object")?;
+    let amount = object.remove("amount").ok_or("missing amount")?;
+    object.insert("value".to_string(), amount);
+    Ok(payload)
+}
+
+fn add_default_source(mut payload: Value) -> Result<Value, String> {
+    let object = payload.as_object_mut().ok_or("payload is not an object")?;
+    object
+        .entry("source")
+        .or_insert_with(|| Value::String("UNKNOWN".to_string()));
+    Ok(payload)
+}
+
+fn payment_registry() -> SchemaRegistry {
+    let mut registry = SchemaRegistry::new();
+    registry.register(PayloadKind::Payment, 3).unwrap();
+    registry
+        .register_upcaster(PayloadKind::Payment, 1, rename_amount_to_value)
+        .unwrap();
+    registry
+        .register_upcaster(PayloadKind::Payment, 2, add_default_source)
+        .unwrap();
+    registry
+}
+
+fn payment_message(schema_version: Option<u32>, payload: Value) -> Value {
+    let mut meta_data = json!({
+        "source": "BANKING_SERVICE",
+        "created_at": "2021-01-01T00:00:00Z",
+        "token": "test.token.here",
+        "idempotency_key": "key-123",
+        "instruction_type": "PAYMENT"
+    });
+    if let Some(version) = schema_version {
+        meta_data["schema_version"] = json!(version);
+    }
+    json!({ "meta_data": meta_data, "payload": payload })
+}
+
+#[test]
+fn test_builtin_payloads_are_at_default_version() {
+    for kind in PayloadKind::ALL {
+        assert_eq!(
+            SCHEMA_REGISTRY.current_version(*kind),
+            DEFAULT_SCHEMA_VERSION
+        );
+    }
+    assert!(SCHEMA_REGISTRY.validate().is_ok());
+}
+
+#[test]
+fn test_default_version_messages_omit_schema_version() {
+    let meta_data = MetaData::new(
+        "BANKING_SERVICE".to_string(),
+        "2021-01-01T00:00:00Z".to_string(),
+        "token".to_string(),
+        "key-123".to_string(),
+        Some(InstructionType::Payment),
+        None,
+        None,
+    )
+    .unwrap();
+    let message = MessageBusMessage::new(meta_data, Payload::Raw("raw".to_string())).unwrap();
+
+    let json: Value = serde_json::to_value(&message).unwrap();
+    assert!(json["meta_data"].get("schema_version").is_none());
+}
+
+#[test]
+fn test_upcasts_v1_payload_through_the_chain() {
+    let message = payment_message(
+        None,
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "amount": "100.00",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    );
+
+    let message = MessageBusMessage::from_value(message, &payment_registry()).unwrap();
+
+    assert_eq!(message.meta_data.schema_version, Some(3));
+    match message.payload {
+        Payload::Payment(payload) => {
+            assert_eq!(payload.value.amount_string(), "100.00");
+            assert_eq!(payload.source, "UNKNOWN");
+        }
+        other => panic!("Expected Payment payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_from_slice_upcasts_like_from_value() {
+    let message = payment_message(
+        Some(2),
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "value": "100.00",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    );
+    let registry = payment_registry();
+
+    let from_slice =
+        MessageBusMessage::from_slice(&serde_json::to_vec(&message).unwrap(), &registry).unwrap();
+
+    assert_eq!(from_slice.meta_data.schema_version, Some(3));
+    assert_eq!(
+        from_slice,
+        MessageBusMessage::from_value(message, &registry).unwrap()
+    );
+    assert!(registry.needs_upcast(PayloadKind::Payment, Some(2)));
+    assert!(!registry.needs_upcast(PayloadKind::Payment, Some(3)));
+    assert!(!registry.needs_upcast(PayloadKind::Mint, None));
+}
+
+#[test]
+fn test_upcast_json_lets_deserialize_use_registered_upcasters() {
+    // What `EventConsumer::with_schemas` does before deserializing into the consumer's type
+    let mut message = payment_message(
+        None,
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "amount": "100.00",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    );
+    let registry = payment_registry();
+    let expected = MessageBusMessage::from_value(message.clone(), &registry).unwrap();
+
+    MessageBusMessage::upcast_json(&mut message, &registry).unwrap();
+
+    assert_eq!(message["meta_data"]["schema_version"], json!(3));
+    assert_eq!(message["payload"]["value"], json!("100.00"));
+    let decoded: MessageBusMessage = serde_json::from_value(message.clone()).unwrap();
+    assert_eq!(decoded, expected);
+
+    // Already upcast, and raw payloads are never upcast
+    let upcast = message.clone();
+    MessageBusMessage::upcast_json(&mut message, &registry).unwrap();
+    assert_eq!(message, upcast);
+    let mut raw = payment_message(None, json!("not json"));
+    MessageBusMessage::upcast_json(&mut raw, &registry).unwrap();
+    assert_eq!(raw, payment_message(None, json!("not json")));
+
+    let mut failing = payment_message(Some(1), json!({ "value": "100.00" }));
+    assert!(matches!(
+        MessageBusMessage::upcast_json(&mut failing, &registry),
+        Err(SchemaError::Upcast {
+            from_version: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_upcasts_from_intermediate_version() {
+    let message = payment_message(
+        Some(2),
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "value": "100.00",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    );
+
+    let message = MessageBusMessage::from_value(message, &payment_registry()).unwrap();
+
+    assert_eq!(message.meta_data.schema_version, Some(3));
+    match message.payload {
+        Payload::Payment(payload) => assert_eq!(payload.source, "UNKNOWN"),
+        other => panic!("Expected Payment payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_current_version_payload_is_decoded_unchanged() {
+    let message = payment_message(
+        Some(3),
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "value": "100.00",
+            "source": "BANK_MODULR",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    );
+
+    let message = MessageBusMessage::from_value(message, &payment_registry()).unwrap();
+
+    assert_eq!(message.meta_data.schema_version, Some(3));
+    match message.payload {
+        Payload::Payment(payload) => assert_eq!(payload.source, "BANK_MODULR"),
+        other => panic!("Expected Payment payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_raw_payload_is_not_upcast() {
+    let message = payment_message(None, json!("not json"));
+
+    let message = MessageBusMessage::from_value(message, &payment_registry()).unwrap();
+
+    assert_eq!(message.meta_data.schema_version, None);
+    assert_eq!(message.payload, Payload::Raw("not json".to_string()));
+}
+
+#[test]
+fn test_missing_upcaster_fails_decoding() {
+    let mut registry = SchemaRegistry::new();
+    registry.register(PayloadKind::Payment, 2).unwrap();
+
+    assert_eq!(
+        registry.validate(),
+        Err(SchemaError::MissingUpcaster {
+            payload: "PaymentPayload",
+            from_version: 1,
+        })
+    );
+
+    let message = payment_message(None, json!({ "amount": "100.00" }));
+    let error = MessageBusMessage::from_value(message, &registry).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("PaymentPayload: no upcaster registered from schema version 1"));
+}
+
+#[test]
+fn test_failing_upcaster_reports_reason() {
+    let message = payment_message(Some(1), json!({ "value": "100.00" }));
+
+    let error = MessageBusMessage::from_value(message, &payment_registry()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("PaymentPayload: upcasting from schema version 1 failed: missing amount"));
+}
+
+#[test]
+fn test_register_rejects_version_zero() {
+    let mut registry = SchemaRegistry::new();
+
+    assert!(matches!(
+        registry.register(PayloadKind::Mint, 0),
+        Err(SchemaError::InvalidVersion { version: 0, .. })
+    ));
+}
+
+#[test]
+fn test_payload_kind_matches_metadata() {
+    let message: MessageBusMessage = serde_json::from_value(payment_message(
+        None,
+        json!({
+            "external_reference": "P123",
+            "currency": "EUR",
+            "value": "100.00",
+            "source": "BANK_MODULR",
+            "direction": "INBOUND",
+            "reference": "REF123"
+        }),
+    ))
+    .unwrap();
+
+    assert_eq!(message.meta_data.payload_kind(), Some(PayloadKind::Payment));
+    assert_eq!(message.payload.kind(), Some(PayloadKind::Payment));
+}
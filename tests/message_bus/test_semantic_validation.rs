@@ -0,0 +1,273 @@
+use mykobo_rs::message_bus::models::base::{PayloadParseError, PaymentDirection};
+use mykobo_rs::message_bus::models::event::*;
+use mykobo_rs::message_bus::models::instruction::*;
+use mykobo_rs::message_bus::models::validation::{
+    is_known_chain, is_known_currency, is_plausible_email, FieldValidator,
+};
+use mykobo_rs::message_bus::TransactionType;
+use pretty_assertions::assert_eq;
+
+fn payment(
+    currency: &str,
+    value: &str,
+) -> Result<PaymentPayload, mykobo_rs::message_bus::ValidationError> {
+    PaymentPayload::new(
+        "P763763453G".to_string(),
+        currency.to_string(),
+        value.to_string(),
+        "BANK_MODULR".to_string(),
+        PaymentDirection::Inbound,
+        "MYK123344545".to_string(),
+        None,
+        None,
+    )
+}
+
+#[test]
+fn test_payment_rejects_malformed_value_and_currency_together() {
+    let error = payment("euro", "abc").unwrap_err();
+    assert_eq!(error.class_name, "PaymentPayload");
+    assert_eq!(
+        error.fields,
+        vec![
+            "value: must be a decimal, got \"abc\"".to_string(),
+            "currency: must be an ISO 4217 code or known crypto asset, got \"euro\"".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_payment_value_must_be_positive() {
+    for value in ["0", "0.00", "-5.00"] {
+        let error = payment("EUR", value).unwrap_err();
+        assert_eq!(
+            error.fields,
+            vec![format!("value: must be greater than zero, got {value:?}")]
+        );
+    }
+}
+
+#[test]
+fn test_payment_value_rejects_exponents_and_padding() {
+    for value in ["1e3", " 10.00", "10.00 ", "1.2.3", "."] {
+        assert!(
+            payment("EUR", value).is_err(),
+            "{value:?} should be rejected"
+        );
+    }
+    for value in ["100", "100.00", "0.0001", "+1.5"] {
+        assert!(
+            payment("EUR", value).is_ok(),
+            "{value:?} should be accepted"
+        );
+    }
+}
+
+#[test]
+fn test_missing_fields_are_reported_once() {
+    let error = payment("", "").unwrap_err();
+    assert_eq!(
+        error.fields,
+        vec!["currency".to_string(), "value".to_string()]
+    );
+    assert_eq!(
+        error.to_string(),
+        "PaymentPayload missing required fields: currency, value"
+    );
+}
+
+#[test]
+fn test_currency_accepts_iso_4217_and_crypto_assets() {
+    for code in ["EUR", "USD", "GBP", "JPY", "USDC", "EURC", "XLM"] {
+        assert!(is_known_currency(code), "{code}");
+    }
+    for code in ["eur", "EURO", "XXX", " EUR", ""] {
+        assert!(!is_known_currency(code), "{code}");
+    }
+}
+
+#[test]
+fn test_mint_rejects_unknown_chain() {
+    let error = MintPayload::new(
+        "10.00".to_string(),
+        "EURC".to_string(),
+        "REF123".to_string(),
+        "ethereum ".to_string(),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.fields,
+        vec!["chain: must be a known chain, got \"ethereum \"".to_string()]
+    );
+
+    assert!(is_known_chain("Stellar"));
+    assert!(!is_known_chain("dogechain"));
+}
+
+#[test]
+fn test_burn_validates_amount_currency_and_chain() {
+    let error = BurnPayload::new(
+        "-1".to_string(),
+        "euro".to_string(),
+        "REF123".to_string(),
+        "moon".to_string(),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(error.fields.len(), 3);
+}
+
+#[test]
+fn test_correction_allows_negative_value() {
+    let payload = CorrectionPayload::new(
+        "REF123".to_string(),
+        "-10.50".to_string(),
+        "Refund overpayment".to_string(),
+        "EUR".to_string(),
+        "BANKING_SERVICE".to_string(),
+    );
+    assert!(payload.is_ok());
+}
+
+#[test]
+fn test_transaction_reports_every_violation() {
+    let error = TransactionPayload::new(
+        "EXT123".to_string(),
+        "BANKING_SERVICE".to_string(),
+        "REF123".to_string(),
+        "John".to_string(),
+        "".to_string(),
+        TransactionType::Deposit,
+        "PENDING".to_string(),
+        "EUR".to_string(),
+        "dollars".to_string(),
+        "100.00".to_string(),
+        "-1.50".to_string(),
+        None,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.fields,
+        vec![
+            "payer (required for DEPOSIT transactions)".to_string(),
+            "last_name".to_string(),
+            "fee: must not be negative, got \"-1.50\"".to_string(),
+            "outgoing_currency: must be an ISO 4217 code or known crypto asset, got \"dollars\""
+                .to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_transaction_allows_zero_fee() {
+    let payload = TransactionPayload::new(
+        "EXT123".to_string(),
+        "BANKING_SERVICE".to_string(),
+        "REF123".to_string(),
+        "John".to_string(),
+        "Doe".to_string(),
+        TransactionType::Withdraw,
+        "PENDING".to_string(),
+        "EURC".to_string(),
+        "EUR".to_string(),
+        "100.00".to_string(),
+        "0".to_string(),
+        None,
+        Some("payee@example.com".to_string()),
+    );
+    assert!(payload.is_ok());
+}
+
+#[test]
+fn test_new_transaction_event_requires_rfc3339_created_at() {
+    let valid = NewTransactionEventPayload::new(
+        "2024-05-01T12:30:00+02:00".to_string(),
+        TransactionType::Deposit,
+        "REF123".to_string(),
+        "BANKING_SERVICE".to_string(),
+    );
+    assert!(valid.is_ok());
+
+    let error = NewTransactionEventPayload::new(
+        "01/05/2024".to_string(),
+        TransactionType::Deposit,
+        "REF123".to_string(),
+        "BANKING_SERVICE".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.fields,
+        vec!["created_at: must be an RFC 3339 timestamp, got \"01/05/2024\"".to_string()]
+    );
+}
+
+#[test]
+fn test_update_profile_checks_optional_timestamps() {
+    let json = r#"{"profile_id": "P1", "suspended_at": "yesterday", "deleted_at": "2024-05-01T00:00:00Z"}"#;
+    match json.parse::<UpdateProfilePayload>() {
+        Err(PayloadParseError::Validation(error)) => assert_eq!(
+            error.fields,
+            vec!["suspended_at: must be an RFC 3339 timestamp, got \"yesterday\"".to_string()]
+        ),
+        other => panic!("Expected validation error, got {other:?}"),
+    }
+
+    let json = r#"{"profile_id": "P1"}"#;
+    assert!(json.parse::<UpdateProfilePayload>().is_ok());
+}
+
+#[test]
+fn test_email_fields_are_checked() {
+    assert!(PasswordResetEventPayload::new(
+        "not-an-email".to_string(),
+        "Reset".to_string(),
+        "secret".to_string()
+    )
+    .is_err());
+    assert!(VerificationRequestedEventPayload::new(
+        "user@localhost".to_string(),
+        "Verify".to_string()
+    )
+    .is_err());
+    assert!(
+        AddressOnboardedEventPayload::new("user@example.com".to_string(), Default::default())
+            .is_ok()
+    );
+
+    for email in ["user@example.com", "first.last+tag@mail.example.co.uk"] {
+        assert!(is_plausible_email(email), "{email}");
+    }
+    for email in [
+        "",
+        "@example.com",
+        "user@",
+        "user@@example.com",
+        "a b@example.com",
+        "user@example.",
+    ] {
+        assert!(!is_plausible_email(email), "{email}");
+    }
+}
+
+#[test]
+fn test_field_validator_accumulates_in_order() {
+    let result = FieldValidator::new("Custom")
+        .required("name", " ")
+        .positive_amount("amount", "1e5")
+        .timestamp("at", "2024-05-01T00:00:00Z")
+        .invalid("other", "not allowed")
+        .finish();
+
+    let error = result.unwrap_err();
+    assert_eq!(error.class_name, "Custom");
+    assert_eq!(
+        error.fields,
+        vec![
+            "name".to_string(),
+            "amount: must be a decimal, got \"1e5\"".to_string(),
+            "other: not allowed".to_string(),
+        ]
+    );
+}
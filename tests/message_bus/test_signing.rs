@@ -0,0 +1,219 @@
+use mykobo_rs::message_bus::kafka::signing::{
+    canonical_bytes, MessageSigner, SignatureVerifier, SigningAlgorithm, SigningError,
+    HIGH_VALUE_INSTRUCTIONS, KEY_ID_HEADER, SIGNATURE_HEADER,
+};
+use mykobo_rs::message_bus::models::avro::{decode_datum, encode_datum, AVRO_SCHEMA};
+use mykobo_rs::message_bus::models::instruction::MintPayload;
+use mykobo_rs::message_bus::MessageBusMessage;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::read_file;
+
+fn mint_message() -> Vec<u8> {
+    let message = MessageBusMessage::instruction(
+        MintPayload::new(
+            "100.00".to_string(),
+            "EURC".to_string(),
+            "REF123".to_string(),
+            "stellar".to_string(),
+            None,
+        )
+        .unwrap(),
+    )
+    .source("LEDGER_SERVICE")
+    .token("test.token.here")
+    .idempotency_key("key-1")
+    .build()
+    .unwrap();
+    serde_json::to_vec(&message).unwrap()
+}
+
+fn headers_for(signer: &MessageSigner, message: &[u8]) -> HashMap<String, String> {
+    signer
+        .sign(message)
+        .unwrap()
+        .headers()
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_canonical_bytes_ignore_key_order_and_whitespace() {
+    let a = br#"{"meta_data": {"source": "S", "token": "T"}, "payload": {"b": 1, "a": [{"y": 1, "x": 2}]}}"#;
+    let b = br#"{"payload":{"a":[{"x":2,"y":1}],"b":1},"meta_data":{"token":"T","source":"S"}}"#;
+
+    assert_eq!(canonical_bytes(a).unwrap(), canonical_bytes(b).unwrap());
+    assert_eq!(
+        String::from_utf8(canonical_bytes(a).unwrap()).unwrap(),
+        r#"{"meta_data":{"source":"S","token":"T"},"payload":{"a":[{"x":2,"y":1}],"b":1}}"#
+    );
+    assert!(matches!(
+        canonical_bytes(br#"{"payload": {}}"#),
+        Err(SigningError::InvalidMessage(_))
+    ));
+    assert_eq!(
+        canonical_bytes(br#"{"meta_data": {"source": "S", "ip_address": null}, "payload": {}}"#)
+            .unwrap(),
+        canonical_bytes(br#"{"meta_data": {"source": "S"}, "payload": {}}"#).unwrap()
+    );
+}
+
+#[test]
+fn test_hmac_signature_round_trip_and_tampering() {
+    let signer = MessageSigner::hmac("bus-2024-05", b"first-secret");
+    let verifier = SignatureVerifier::new().with_hmac_key("bus-2024-05", b"first-secret");
+    let message = mint_message();
+    let headers = headers_for(&signer, &message);
+
+    assert_eq!(headers["signature_algorithm"], "HS256");
+    assert_eq!(verifier.verify(&message, &headers), Ok(()));
+
+    let mut tampered: serde_json::Value = serde_json::from_slice(&message).unwrap();
+    tampered["payload"]["value"] = json!("100000.00");
+    assert_eq!(
+        verifier.verify(&serde_json::to_vec(&tampered).unwrap(), &headers),
+        Err(SigningError::InvalidSignature("bus-2024-05".to_string()))
+    );
+}
+
+#[test]
+fn test_key_rotation() {
+    let message = mint_message();
+    let old = headers_for(&MessageSigner::hmac("old", b"old-secret"), &message);
+    let new = headers_for(&MessageSigner::hmac("new", b"new-secret"), &message);
+
+    let verifier = SignatureVerifier::new()
+        .with_hmac_key("old", b"old-secret")
+        .with_hmac_key("new", b"new-secret");
+    assert_eq!(verifier.verify(&message, &old), Ok(()));
+    assert_eq!(verifier.verify(&message, &new), Ok(()));
+
+    let verifier = verifier.without_key("old");
+    assert_eq!(
+        verifier.verify(&message, &old),
+        Err(SigningError::UnknownKey("old".to_string()))
+    );
+    assert_eq!(verifier.verify(&message, &new), Ok(()));
+}
+
+#[test]
+fn test_ed25519_signature() {
+    let signer = MessageSigner::ed25519(
+        "ledger-ed",
+        read_file("tests/stubs/signing/ed25519_private.pem").as_bytes(),
+    )
+    .unwrap();
+    let message = mint_message();
+    let headers = headers_for(&signer, &message);
+    assert_eq!(headers["signature_algorithm"], "EdDSA");
+
+    let verifier = SignatureVerifier::new()
+        .with_ed25519_key(
+            "ledger-ed",
+            read_file("tests/stubs/signing/ed25519_public.pem").as_bytes(),
+        )
+        .unwrap();
+    assert_eq!(verifier.verify(&message, &headers), Ok(()));
+
+    let wrong_key = SignatureVerifier::new()
+        .with_ed25519_key(
+            "ledger-ed",
+            read_file("tests/stubs/signing/ed25519_other_public.pem").as_bytes(),
+        )
+        .unwrap();
+    assert_eq!(
+        wrong_key.verify(&message, &headers),
+        Err(SigningError::InvalidSignature("ledger-ed".to_string()))
+    );
+}
+
+#[test]
+fn test_verifier_rejects_algorithm_swap() {
+    let message = mint_message();
+    let headers = headers_for(
+        &MessageSigner::hmac("ledger-ed", b"public-key-bytes"),
+        &message,
+    );
+    let verifier = SignatureVerifier::new()
+        .with_ed25519_key(
+            "ledger-ed",
+            read_file("tests/stubs/signing/ed25519_public.pem").as_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        verifier.verify(&message, &headers),
+        Err(SigningError::AlgorithmMismatch {
+            key_id: "ledger-ed".to_string(),
+            expected: SigningAlgorithm::Ed25519,
+            actual: "HS256".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_required_signatures() {
+    let message = mint_message();
+    let verifier = SignatureVerifier::new()
+        .with_hmac_key("k1", b"secret")
+        .require_signatures(HIGH_VALUE_INSTRUCTIONS);
+
+    assert_eq!(
+        verifier.verify(&message, &HashMap::new()),
+        Err(SigningError::MissingSignature("MINT".to_string()))
+    );
+    assert_eq!(
+        SignatureVerifier::new().verify(&message, &HashMap::new()),
+        Ok(())
+    );
+
+    let partial = HashMap::from([
+        (SIGNATURE_HEADER.to_string(), "abc".to_string()),
+        (KEY_ID_HEADER.to_string(), "k1".to_string()),
+    ]);
+    assert_eq!(
+        verifier.verify(&message, &partial),
+        Err(SigningError::IncompleteHeaders)
+    );
+}
+
+#[test]
+fn test_signature_survives_avro_round_trip() {
+    let signer = MessageSigner::hmac("k1", b"secret");
+    let message = mint_message();
+    let headers = headers_for(&signer, &message);
+
+    let datum = encode_datum(&serde_json::from_slice(&message).unwrap()).unwrap();
+    let decoded = serde_json::to_vec(&decode_datum(&datum, &AVRO_SCHEMA).unwrap()).unwrap();
+    assert_ne!(decoded, message);
+
+    let verifier = SignatureVerifier::new()
+        .with_hmac_key("k1", b"secret")
+        .require_signatures(HIGH_VALUE_INSTRUCTIONS);
+    assert_eq!(verifier.verify(&decoded, &headers), Ok(()));
+    assert_eq!(
+        verifier.verify(&decoded, &HashMap::new()),
+        Err(SigningError::MissingSignature("MINT".to_string()))
+    );
+}
+
+#[test]
+fn test_unreadable_unsigned_messages_are_rejected() {
+    let verifier = SignatureVerifier::new().require_signatures(HIGH_VALUE_INSTRUCTIONS);
+    let datum = encode_datum(&serde_json::from_slice(&mint_message()).unwrap()).unwrap();
+
+    assert!(matches!(
+        verifier.verify(&datum, &HashMap::new()),
+        Err(SigningError::InvalidMessage(_))
+    ));
+    assert!(matches!(
+        verifier.verify(
+            br#"{"meta_data": {"instruction_type": 7}, "payload": {}}"#,
+            &HashMap::new()
+        ),
+        Err(SigningError::InvalidMessage(_))
+    ));
+}
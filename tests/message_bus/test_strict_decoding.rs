@@ -0,0 +1,203 @@
+use mykobo_rs::message_bus::models::decode::{DecodeError, StrictDecoder};
+use mykobo_rs::message_bus::models::message::PayloadKind;
+use mykobo_rs::message_bus::models::schema::SchemaRegistry;
+use mykobo_rs::message_bus::{EventType, InstructionType, MessageBusMessage, Payload};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+fn payment_message(payload: Value) -> Value {
+    json!({
+        "meta_data": {
+            "source": "BANKING_SERVICE",
+            "created_at": "2021-01-01T00:00:00Z",
+            "token": "test.token.here",
+            "idempotency_key": "key-123",
+            "instruction_type": "PAYMENT"
+        },
+        "payload": payload
+    })
+}
+
+fn payment_payload() -> Value {
+    json!({
+        "external_reference": "P123",
+        "currency": "EUR",
+        "value": "100.00",
+        "source": "BANK_MODULR",
+        "direction": "INBOUND",
+        "reference": "REF123",
+        "payer_name": null
+    })
+}
+
+#[test]
+fn test_strict_decodes_typed_payload() {
+    let message = StrictDecoder::new()
+        .decode_value(payment_message(payment_payload()))
+        .unwrap();
+
+    match message.payload {
+        Payload::Payment(payload) => assert_eq!(payload.reference, "REF123"),
+        other => panic!("Expected Payment payload, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_rejects_raw_payload_by_default() {
+    let json = payment_message(json!("raw text"));
+
+    // The lenient decoder accepts it
+    let lenient: MessageBusMessage = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(lenient.payload, Payload::Raw("raw text".to_string()));
+
+    match StrictDecoder::new().decode_value(json) {
+        Err(DecodeError::RawPayload { message_type }) => assert_eq!(message_type, "PAYMENT"),
+        other => panic!("Expected RawPayload error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_rejects_unknown_types_unless_allowed() {
+    let mut json = payment_message(json!({"refund_id": "R-1"}));
+    json["meta_data"]["instruction_type"] = json!("REFUND");
+
+    match StrictDecoder::new().decode_value(json.clone()) {
+        Err(DecodeError::RawPayload { message_type }) => assert_eq!(message_type, "REFUND"),
+        other => panic!("Expected RawPayload error, got {other:?}"),
+    }
+
+    let message = StrictDecoder::new()
+        .allow_raw_instruction(InstructionType::Unknown("REFUND".to_string()))
+        .decode_value(json)
+        .unwrap();
+    assert_eq!(
+        message.payload,
+        Payload::Unknown(json!({"refund_id": "R-1"}))
+    );
+}
+
+#[test]
+fn test_strict_accepts_raw_payload_for_registered_types() {
+    let decoder = StrictDecoder::new()
+        .allow_raw_instruction(InstructionType::Payment)
+        .allow_raw_event(EventType::KycEvent);
+
+    let message = decoder
+        .decode_value(payment_message(json!("raw text")))
+        .unwrap();
+    assert_eq!(message.payload, Payload::Raw("raw text".to_string()));
+
+    let kyc = json!({
+        "meta_data": {
+            "source": "IDENTITY_SERVICE",
+            "created_at": "2021-01-01T00:00:00Z",
+            "token": "test.token.here",
+            "idempotency_key": "key-123",
+            "event": "KYC_EVENT"
+        },
+        "payload": "raw text"
+    });
+    assert!(decoder.decode_value(kyc).is_ok());
+}
+
+#[test]
+fn test_strict_rejects_unknown_fields() {
+    let mut payload = payment_payload();
+    payload["amount"] = json!("100.00");
+    payload["note"] = json!("extra");
+
+    match StrictDecoder::new().decode_value(payment_message(payload)) {
+        Err(DecodeError::UnknownFields { payload, fields }) => {
+            assert_eq!(payload, "PaymentPayload");
+            assert_eq!(fields, vec!["amount".to_string(), "note".to_string()]);
+        }
+        other => panic!("Expected UnknownFields error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_rejects_unknown_nested_fields() {
+    let notification = |subject: Value| {
+        json!({
+            "meta_data": {
+                "source": "ledger",
+                "created_at": "2021-01-01T00:00:00Z",
+                "token": "test.token.here",
+                "idempotency_key": "key-123",
+                "event": "CUSTOMER_FUNDS_RECEIVED"
+            },
+            "payload": { "subject": subject, "data": { "anything": { "goes": true } } }
+        })
+    };
+    let decoder = StrictDecoder::new();
+
+    assert!(decoder
+        .decode_value(notification(
+            json!({ "type": "transaction", "reference": "TX-1" })
+        ))
+        .is_ok());
+    match decoder.decode_value(notification(json!({
+        "type": "transaction",
+        "reference": "TX-1",
+        "user_id": "user-1"
+    }))) {
+        Err(DecodeError::UnknownFields { payload, fields }) => {
+            assert_eq!(payload, "CustomerNotificationPayload");
+            assert_eq!(fields, vec!["subject.user_id".to_string()]);
+        }
+        other => panic!("Expected UnknownFields error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_checks_fields_after_upcasting() {
+    fn rename_amount_to_value(mut payload: Value) -> Result<Value, String> {
+        let object = payload.as_object_mut().ok_or("payload is not an object")?;
+        let amount = object.remove("amount").ok_or("missing amount")?;
+        object.insert("value".to_string(), amount);
+        Ok(payload)
+    }
+
+    let mut schemas = SchemaRegistry::new();
+    schemas.register(PayloadKind::Payment, 2).unwrap();
+    schemas
+        .register_upcaster(PayloadKind::Payment, 1, rename_amount_to_value)
+        .unwrap();
+
+    let mut payload = payment_payload();
+    let value = payload.as_object_mut().unwrap().remove("value").unwrap();
+    payload["amount"] = value;
+
+    let message = StrictDecoder::new()
+        .with_schemas(schemas)
+        .decode_value(payment_message(payload))
+        .unwrap();
+    assert_eq!(message.meta_data.schema_version, Some(2));
+}
+
+#[test]
+fn test_strict_requires_type_hint() {
+    let json = r#"{
+        "meta_data": {
+            "source": "BANKING_SERVICE",
+            "created_at": "2021-01-01T00:00:00Z",
+            "token": "test.token.here",
+            "idempotency_key": "key-123"
+        },
+        "payload": {"reference": "REF123", "status": "DONE"}
+    }"#;
+
+    assert!(serde_json::from_str::<MessageBusMessage>(json).is_ok());
+    assert!(matches!(
+        StrictDecoder::new().decode_str(json),
+        Err(DecodeError::MissingTypeHint)
+    ));
+}
+
+#[test]
+fn test_strict_reports_malformed_json() {
+    assert!(matches!(
+        StrictDecoder::new().decode_slice(b"{not json"),
+        Err(DecodeError::Json(_))
+    ));
+}
@@ -0,0 +1,214 @@
+use mykobo_rs::message_bus::models::avro::{avro_schema, decode_datum, encode_datum, AVRO_SCHEMA};
+use mykobo_rs::message_bus::models::wire::{
+    AvroCodec, FileSchemaRegistry, RegistryError, SchemaRegistryClient, WireError, WireFormat,
+    AVRO_CONTENT_TYPE, AVRO_SUBJECT, CONTENT_TYPE_HEADER,
+};
+use mykobo_rs::message_bus::MessageBusMessage;
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const FIXTURES: &[&str] = &[
+    "message_bus/payment.json",
+    "message_bus/transaction.json",
+    "message_bus/raw.json",
+    "notification/customer_relay_completed.json",
+    "notification/platform_relay_failed.json",
+];
+
+fn fixture(path: &str) -> Vec<u8> {
+    std::fs::read(PathBuf::from("tests/fixtures").join(path)).unwrap()
+}
+
+fn registry_path() -> PathBuf {
+    std::env::temp_dir().join(format!("schema-registry-{}.json", uuid::Uuid::new_v4()))
+}
+
+fn codec(path: &PathBuf) -> AvroCodec {
+    AvroCodec::new(Arc::new(FileSchemaRegistry::new(path)))
+}
+
+#[tokio::test]
+async fn test_avro_round_trip() {
+    let path = registry_path();
+    let codec = codec(&path);
+
+    for name in FIXTURES {
+        let json = fixture(name);
+        let avro = codec.encode(&json).await.unwrap();
+        assert_eq!(avro[0], 0, "{name}");
+        assert!(avro.len() < json.len(), "{name}");
+
+        let decoded = codec.decode(&avro).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<MessageBusMessage>(&decoded).unwrap(),
+            serde_json::from_slice::<MessageBusMessage>(&json).unwrap(),
+            "{name}"
+        );
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_consumer_with_its_own_registry_client_decodes() {
+    let path = registry_path();
+    let producer = codec(&path);
+    let consumer = codec(&path);
+
+    let json = fixture("message_bus/payment.json");
+    let avro = producer.encode(&json).await.unwrap();
+    let decoded = consumer.to_json(WireFormat::Avro, &avro).await.unwrap();
+
+    assert_eq!(
+        serde_json::from_slice::<Value>(&decoded).unwrap()["payload"]["external_reference"],
+        serde_json::from_slice::<Value>(&json).unwrap()["payload"]["external_reference"]
+    );
+    assert_eq!(
+        consumer.to_json(WireFormat::Json, &json).await.unwrap(),
+        json
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_older_writer_schema_is_resolved() {
+    let path = registry_path();
+    let registry = FileSchemaRegistry::new(&path);
+
+    // The message schema before `tenant` was added to `MetaData`
+    let mut old_schema = avro_schema();
+    old_schema["fields"][0]["type"]["fields"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|field| field["name"] != "tenant");
+    let old_schema = apache_avro::Schema::parse(&old_schema).unwrap();
+    let old_id = registry
+        .register(AVRO_SUBJECT, &old_schema.canonical_form())
+        .await
+        .unwrap();
+
+    // A message written by a producer still on the old schema
+    let current = codec(&path)
+        .encode(&fixture("message_bus/payment.json"))
+        .await
+        .unwrap();
+    let mut datum = &current[5..];
+    let mut record = apache_avro::from_avro_datum(&AVRO_SCHEMA, &mut datum, None).unwrap();
+    if let apache_avro::types::Value::Record(fields) = &mut record {
+        if let apache_avro::types::Value::Record(meta_data) = &mut fields[0].1 {
+            meta_data.retain(|(name, _)| name != "tenant");
+        }
+    }
+    let mut framed = vec![0];
+    framed.extend_from_slice(&old_id.to_be_bytes());
+    framed.extend(apache_avro::to_avro_datum(&old_schema, record).unwrap());
+
+    let decoded: MessageBusMessage =
+        serde_json::from_slice(&codec(&path).decode(&framed).await.unwrap()).unwrap();
+    assert_eq!(decoded.meta_data.tenant, None);
+    assert_eq!(
+        decoded.payload,
+        serde_json::from_slice::<MessageBusMessage>(&fixture("message_bus/payment.json"))
+            .unwrap()
+            .payload
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_registry_ids_and_versions() {
+    let path = registry_path();
+    let registry = FileSchemaRegistry::new(&path);
+    let schema = avro_schema().to_string();
+    let other = json!({ "type": "record", "name": "Other", "fields": [] }).to_string();
+
+    let id = registry.register(AVRO_SUBJECT, &schema).await.unwrap();
+    assert_eq!(registry.register(AVRO_SUBJECT, &schema).await.unwrap(), id);
+    let other_id = registry.register(AVRO_SUBJECT, &other).await.unwrap();
+    assert_eq!(other_id, id + 1);
+
+    // A fresh client reads what the first one wrote
+    let reopened = FileSchemaRegistry::new(&path);
+    assert_eq!(reopened.schema(id).await.unwrap(), schema);
+    assert!(matches!(
+        reopened.schema(99).await,
+        Err(RegistryError::NotFound(99))
+    ));
+    assert!(matches!(
+        reopened.register(AVRO_SUBJECT, "not a schema").await,
+        Err(RegistryError::InvalidSchema(_))
+    ));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_content_type_header() {
+    let mut headers = HashMap::new();
+    assert_eq!(
+        WireFormat::from_headers(&headers).unwrap(),
+        WireFormat::Json
+    );
+
+    headers.insert(
+        CONTENT_TYPE_HEADER.to_string(),
+        AVRO_CONTENT_TYPE.to_string(),
+    );
+    assert_eq!(
+        WireFormat::from_headers(&headers).unwrap(),
+        WireFormat::Avro
+    );
+
+    headers.insert(
+        CONTENT_TYPE_HEADER.to_string(),
+        "application/xml".to_string(),
+    );
+    assert!(matches!(
+        WireFormat::from_headers(&headers),
+        Err(WireError::UnsupportedContentType(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_unencodable_messages_fail() {
+    let path = registry_path();
+    let codec = codec(&path);
+
+    assert!(matches!(
+        codec.decode(b"{}").await,
+        Err(WireError::Framing(_))
+    ));
+
+    let mut message: Value = serde_json::from_slice(&fixture("message_bus/payment.json")).unwrap();
+    message["payload"]["unexpected"] = json!("field");
+    let error = codec
+        .encode(&serde_json::to_vec(&message).unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "message.payload.unexpected: not a field of PaymentPayload"
+    );
+}
+
+#[test]
+fn test_unknown_types_and_field_order() {
+    let mut message: Value = serde_json::from_slice(&fixture("message_bus/raw.json")).unwrap();
+    message["meta_data"]["event"] = json!("SOME_FUTURE_EVENT");
+    let datum = encode_datum(&message).unwrap();
+    let decoded = decode_datum(&datum, &AVRO_SCHEMA).unwrap();
+    assert_eq!(decoded["meta_data"]["event"], "SOME_FUTURE_EVENT");
+    assert_eq!(decoded["payload"], message["payload"]);
+
+    let schema = avro_schema();
+    let names: Vec<&str> = schema["fields"][0]["type"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap())
+        .collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+}
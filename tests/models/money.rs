@@ -0,0 +1,243 @@
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::BigDecimal;
+use mykobo_rs::anchor::models::DappIntentPayload;
+use mykobo_rs::message_bus::models::base::PaymentDirection;
+use mykobo_rs::message_bus::models::instruction::{
+    CorrectionPayload, MintPayload, PaymentPayload, TransactionPayload,
+};
+use mykobo_rs::message_bus::models::message::MessageBusMessage;
+use mykobo_rs::message_bus::TransactionType;
+use mykobo_rs::models::{Currency, Money, MoneyError, RoundingMode};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+fn eur(amount: &str) -> Money {
+    Money::parse(amount, "EUR").unwrap()
+}
+
+#[test]
+fn test_currency_minor_units() {
+    assert_eq!(Currency::EUR.minor_units(), 2);
+    assert_eq!("JPY".parse::<Currency>().unwrap().minor_units(), 0);
+    assert_eq!("KWD".parse::<Currency>().unwrap().minor_units(), 3);
+    assert_eq!(Currency::USDC.minor_units(), 6);
+    assert_eq!("XLM".parse::<Currency>().unwrap().minor_units(), 7);
+    assert!(Currency::EURC.is_crypto());
+    assert!(!Currency::GBP.is_crypto());
+}
+
+#[test]
+fn test_currency_rejects_unknown_codes() {
+    for code in ["euro", "eur", "EURO", " EUR", ""] {
+        assert_eq!(
+            code.parse::<Currency>(),
+            Err(MoneyError::UnknownCurrency(code.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_parse_rejects_malformed_amounts() {
+    for amount in ["abc", "1e3", " 10.00", "", "1.2.3"] {
+        assert_eq!(
+            Money::parse(amount, "EUR"),
+            Err(MoneyError::InvalidAmount(amount.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_rounding_modes() {
+    let amount = eur("10.125");
+    assert_eq!(amount.round(RoundingMode::HalfUp).amount_string(), "10.13");
+    assert_eq!(
+        amount.round(RoundingMode::HalfEven).amount_string(),
+        "10.12"
+    );
+    assert_eq!(amount.round(RoundingMode::Down).amount_string(), "10.12");
+    assert_eq!(
+        eur("-10.121").round(RoundingMode::Floor).amount_string(),
+        "-10.13"
+    );
+    assert_eq!(
+        eur("10").round(RoundingMode::HalfUp).amount_string(),
+        "10.00"
+    );
+}
+
+#[test]
+fn test_minor_units_round_trip() {
+    let money = Money::from_minor_units(12345, Currency::EUR);
+    assert_eq!(money.amount_string(), "123.45");
+    assert_eq!(
+        money.to_minor_units(RoundingMode::HalfUp),
+        BigInt::from(12345)
+    );
+
+    let yen = Money::parse("1500", "JPY").unwrap();
+    assert_eq!(yen.to_minor_units(RoundingMode::HalfUp), BigInt::from(1500));
+    assert_eq!(
+        eur("0.005").to_minor_units(RoundingMode::HalfEven),
+        BigInt::from(0)
+    );
+}
+
+#[test]
+fn test_checked_arithmetic() {
+    assert_eq!(
+        eur("100.00").checked_add(&eur("1.50")).unwrap(),
+        eur("101.50")
+    );
+    assert_eq!(eur("100.00").checked_sub(&eur("101")).unwrap(), eur("-1"));
+    assert!(eur("-1").is_negative());
+    assert_eq!(eur("1").checked_cmp(&eur("1.00")).unwrap(), Ordering::Equal);
+
+    let usd = Money::parse("1", "USD").unwrap();
+    assert_eq!(
+        eur("1").checked_add(&usd),
+        Err(MoneyError::CurrencyMismatch {
+            left: Currency::EUR,
+            right: Currency::USD
+        })
+    );
+    assert!(eur("1").checked_cmp(&usd).is_err());
+}
+
+#[test]
+fn test_mul_and_div() {
+    let rate = BigDecimal::from_str("1.0825").unwrap();
+    assert_eq!(eur("100.00").mul(&rate).amount_string(), "108.250000");
+
+    let third = eur("100.00")
+        .checked_div(&BigDecimal::from(3), RoundingMode::HalfUp)
+        .unwrap();
+    assert_eq!(third.amount_string(), "33.33");
+    assert_eq!(
+        eur("1").checked_div(&BigDecimal::from(0), RoundingMode::HalfUp),
+        Err(MoneyError::DivisionByZero)
+    );
+}
+
+#[test]
+fn test_display_and_amount_string() {
+    let money = Money::parse("100.00", "USDC").unwrap();
+    assert_eq!(money.amount_string(), "100.00");
+    assert_eq!(money.to_string(), "100.00 USDC");
+
+    // Very small amounts keep their plain decimal form
+    let tiny = Money::parse("0.0000001", "XLM").unwrap();
+    assert_eq!(tiny.amount_string(), "0.0000001");
+
+    assert!(Money::parse("1", "euro").is_err());
+    assert!(Money::parse("1e3", "EUR").is_err());
+}
+
+#[test]
+fn test_payload_amounts_are_money() {
+    let payment = PaymentPayload::new(
+        "P1".to_string(),
+        "EUR".to_string(),
+        "123.00".to_string(),
+        "BANK_MODULR".to_string(),
+        PaymentDirection::Inbound,
+        "REF1".to_string(),
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(payment.value, eur("123"));
+
+    let transaction = TransactionPayload::new(
+        "EXT123".to_string(),
+        "BANKING_SERVICE".to_string(),
+        "REF123".to_string(),
+        "John".to_string(),
+        "Doe".to_string(),
+        TransactionType::Deposit,
+        "PENDING".to_string(),
+        "EUR".to_string(),
+        "EURC".to_string(),
+        "100.00".to_string(),
+        "1.50".to_string(),
+        Some("payer".to_string()),
+        None,
+    )
+    .unwrap();
+    let net = transaction.value.checked_sub(&transaction.fee).unwrap();
+    assert_eq!(net, eur("98.50"));
+
+    let intent: DappIntentPayload = serde_json::from_value(json!({
+        "transaction_type": "DEPOSIT",
+        "wallet_address": "GABC",
+        "email_address": "user@example.com",
+        "value": "25.5",
+        "currency": "EURC",
+        "ip_address": "127.0.0.1"
+    }))
+    .unwrap();
+    assert_eq!(intent.value.currency, Currency::EURC);
+    assert_eq!(
+        intent.value.round(RoundingMode::HalfUp).amount_string(),
+        "25.500000"
+    );
+}
+
+#[test]
+fn test_payload_amounts_keep_their_string_form() {
+    let fixture = std::fs::read_to_string("tests/fixtures/message_bus/payment.json").unwrap();
+    let message: MessageBusMessage = serde_json::from_str(&fixture).unwrap();
+    assert_eq!(
+        serde_json::to_value(&message).unwrap(),
+        serde_json::from_str::<serde_json::Value>(&fixture).unwrap()
+    );
+
+    let correction = r#"{"reference":"REF1","value":"-10.50","message":"refund","currency":"EUR","source":"LEDGER"}"#;
+    let payload: CorrectionPayload = serde_json::from_str(correction).unwrap();
+    assert_eq!(payload.value, eur("-10.5"));
+    assert_eq!(serde_json::to_string(&payload).unwrap(), correction);
+
+    let transaction: TransactionPayload = serde_json::from_value(json!({
+        "external_reference": "EXT123",
+        "source": "BANKING",
+        "reference": "MYK123",
+        "first_name": "Ada",
+        "last_name": "Lovelace",
+        "transaction_type": "DEPOSIT",
+        "status": "PENDING",
+        "incoming_currency": "EUR",
+        "outgoing_currency": "EURC",
+        "value": "100.00",
+        "fee": "1.50",
+        "payer": "Account 123"
+    }))
+    .unwrap();
+    assert_eq!(transaction.fee, eur("1.5"));
+    let json = serde_json::to_value(&transaction).unwrap();
+    assert_eq!(json["incoming_currency"], "EUR");
+    assert_eq!(json["fee"], "1.50");
+}
+
+#[test]
+fn test_payload_with_malformed_amount_fails_to_deserialize() {
+    let error = serde_json::from_value::<MintPayload>(json!({
+        "value": "1e3",
+        "currency": "USDC",
+        "reference": "REF1",
+        "chain": "stellar"
+    }))
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("value: invalid amount"),
+        "{error}"
+    );
+
+    assert!(serde_json::from_value::<MintPayload>(json!({
+        "value": "10",
+        "currency": "euro",
+        "reference": "REF1",
+        "chain": "stellar"
+    }))
+    .is_err());
+}
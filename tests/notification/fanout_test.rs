@@ -0,0 +1,187 @@
+use mykobo_rs::message_bus::kafka::routing::ROUTING_TABLE;
+use mykobo_rs::message_bus::models::base::EventType;
+use mykobo_rs::message_bus::models::{
+    CustomerNotificationPayload, MessageBusMessage, NewUserEventPayload, NotificationSubject,
+    Payload, PlatformNotificationPayload, Severity, TransactionStatusEventPayload,
+    VerificationRequestedEventPayload,
+};
+use mykobo_rs::notification::{FanOutError, NotificationFanOut};
+use mykobo_rs::notification_contract::{Entry, NotificationRule, REGISTRY};
+use serde_json::json;
+
+fn status_update(status: &str) -> MessageBusMessage {
+    let payload =
+        TransactionStatusEventPayload::new("TX-1".to_string(), status.to_string(), None).unwrap();
+    MessageBusMessage::event(EventType::TransactionStatusUpdate, payload)
+        .source("ledger")
+        .token("ledger-token")
+        .idempotency_key(format!("ledger:status:TX-1:{status}"))
+        .build()
+        .unwrap()
+}
+
+fn fan_out() -> NotificationFanOut {
+    NotificationFanOut::new("ledger", "ledger-token")
+}
+
+#[test]
+fn funds_received_fires_customer_and_platform_notifications() {
+    let domain = status_update("FUNDS_RECEIVED");
+    let notifications = fan_out().fan_out(&domain).unwrap();
+
+    let events: Vec<_> = notifications
+        .iter()
+        .map(|n| n.meta_data.event.clone().unwrap())
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            EventType::CustomerFundsReceived,
+            EventType::TransactionFundedInfo
+        ]
+    );
+
+    let data = json!({
+        "external_reference": null,
+        "reference": "TX-1",
+        "status": "FUNDS_RECEIVED",
+    });
+    assert_eq!(
+        notifications[0].payload,
+        Payload::CustomerNotification(CustomerNotificationPayload {
+            subject: NotificationSubject::Transaction {
+                reference: "TX-1".into()
+            },
+            data: data.clone(),
+        })
+    );
+    assert_eq!(
+        notifications[1].payload,
+        Payload::PlatformNotification(PlatformNotificationPayload {
+            severity: Severity::Info,
+            data,
+            subject: Some("transaction:TX-1".into()),
+        })
+    );
+
+    let customer = &notifications[0].meta_data;
+    assert_eq!(
+        customer.idempotency_key,
+        "ledger:customer_funds_received:ledger:status:TX-1:FUNDS_RECEIVED"
+    );
+    assert_eq!(customer.source, "ledger");
+    assert_eq!(
+        customer.causation_id.as_deref(),
+        Some("ledger:status:TX-1:FUNDS_RECEIVED")
+    );
+    assert_eq!(
+        customer.correlation_id.as_deref(),
+        Some("ledger:status:TX-1:FUNDS_RECEIVED")
+    );
+}
+
+#[test]
+fn severity_comes_from_the_registry_and_picks_the_lane() {
+    let notifications = fan_out().fan_out(&status_update("FAILED")).unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert!(matches!(
+        &notifications[0].payload,
+        Payload::PlatformNotification(p) if p.severity == Severity::Critical
+    ));
+    assert_eq!(
+        ROUTING_TABLE.route(&notifications[0]).unwrap().topic,
+        "mykobo.events.critical"
+    );
+
+    let held = fan_out().fan_out(&status_update("HELD")).unwrap();
+    assert!(matches!(
+        &held[0].payload,
+        Payload::PlatformNotification(p) if p.severity == Severity::Warning
+    ));
+}
+
+#[test]
+fn redelivered_events_get_the_same_idempotency_keys() {
+    let keys = |message: &MessageBusMessage| -> Vec<String> {
+        fan_out()
+            .fan_out(message)
+            .unwrap()
+            .into_iter()
+            .map(|n| n.meta_data.idempotency_key)
+            .collect()
+    };
+    let domain = status_update("FUNDS_RECEIVED");
+    assert_eq!(keys(&domain), keys(&domain));
+}
+
+#[test]
+fn distinct_events_on_the_same_subject_get_distinct_keys() {
+    let key = |message: &MessageBusMessage| {
+        fan_out().fan_out(message).unwrap()[0]
+            .meta_data
+            .idempotency_key
+            .clone()
+    };
+    let first = status_update("FUNDS_RECEIVED");
+    let mut second = status_update("FUNDS_RECEIVED");
+    second.meta_data.idempotency_key = "ledger:status:TX-1:FUNDS_RECEIVED:2".into();
+    assert_ne!(key(&first), key(&second));
+}
+
+#[test]
+fn only_matching_domain_events_fire() {
+    let fan_out = fan_out();
+    let pending = status_update("PENDING");
+    assert!(fan_out.is_domain_event(&pending));
+    assert!(fan_out.fan_out(&pending).unwrap().is_empty());
+
+    let notification = fan_out.fan_out(&status_update("FAILED")).unwrap().remove(0);
+    assert!(!fan_out.is_domain_event(&notification));
+    assert!(fan_out.fan_out(&notification).unwrap().is_empty());
+}
+
+#[test]
+fn custom_registry_rules_and_subjects() {
+    let mut registry = REGISTRY.clone();
+    for event in [EventType::NewUser, EventType::VerificationRequested] {
+        registry.entries.insert(
+            event,
+            Entry::Domain {
+                notifies: vec![NotificationRule {
+                    when: None,
+                    fires: vec![EventType::CustomerFundsReceived],
+                }],
+                reason: None,
+            },
+        );
+    }
+    let fan_out = fan_out().with_registry(registry);
+
+    let new_user = MessageBusMessage::event(
+        EventType::NewUser,
+        NewUserEventPayload::new("Welcome".into(), "user-1".into()).unwrap(),
+    )
+    .source("identity")
+    .token("identity-token")
+    .build()
+    .unwrap();
+    let notifications = fan_out.fan_out(&new_user).unwrap();
+    assert!(matches!(
+        &notifications[0].payload,
+        Payload::CustomerNotification(p)
+            if p.subject == NotificationSubject::Profile { user_id: "user-1".into() }
+    ));
+
+    let verification = MessageBusMessage::event(
+        EventType::VerificationRequested,
+        VerificationRequestedEventPayload::new("user@example.com".into(), "Verify".into()).unwrap(),
+    )
+    .source("identity")
+    .token("identity-token")
+    .build()
+    .unwrap();
+    assert!(matches!(
+        fan_out.fan_out(&verification),
+        Err(FanOutError::NoSubject(event)) if event == "VERIFICATION_REQUESTED"
+    ));
+}
@@ -1,5 +1,5 @@
 use chrono::{Duration, TimeZone, Utc};
-use mykobo_rs::message_bus::models::base::EventType;
+use mykobo_rs::message_bus::models::base::{EventType, InstructionType};
 use mykobo_rs::notification::IdempotencyKey;
 
 #[test]
@@ -103,3 +103,9 @@ fn for_bucket_rejects_subsecond_window() {
         Utc.with_ymd_and_hms(2026, 5, 30, 12, 0, 0).unwrap(),
     );
 }
+
+#[test]
+fn for_instruction_with_subject() {
+    let k = IdempotencyKey::for_instruction("ledger", InstructionType::BankPaymentRequest, "REF-1");
+    assert_eq!(k, "ledger:bank_payment_request:REF-1");
+}
@@ -1,2 +1,3 @@
 mod events_test;
+mod fanout_test;
 mod keys_test;
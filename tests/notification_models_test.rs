@@ -1,3 +1,5 @@
+use mykobo_rs::message_bus::models::base::EventType;
+use mykobo_rs::message_bus::models::message::{MessageBusMessage, MetaData, Payload};
 use mykobo_rs::message_bus::models::notification::{
     CustomerNotificationPayload, NotificationSubject, PlatformNotificationPayload, Severity,
 };
@@ -93,3 +95,38 @@ fn platform_payload_without_subject_drops_field() {
     let v = serde_json::to_value(&p).unwrap();
     assert!(v.get("subject").is_none(), "subject: None must drop from JSON (skip_serializing_none)");
 }
+
+fn notification_meta(event: EventType) -> MetaData {
+    MetaData::new(
+        "RELAY_SERVICE".into(),
+        "2026-05-30T12:00:00Z".into(),
+        "token".into(),
+        "idem-1".into(),
+        None,
+        Some(event),
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn every_notification_event_validates_with_its_audience_payload() {
+    let customer = Payload::CustomerNotification(CustomerNotificationPayload {
+        subject: NotificationSubject::Transaction { reference: "ref-1".into() },
+        data: json!({}),
+    });
+    let platform = Payload::PlatformNotification(PlatformNotificationPayload {
+        severity: Severity::Info,
+        data: json!({}),
+        subject: None,
+    });
+
+    for event in [EventType::DepositInitiated, EventType::WithdrawFailed, EventType::CustomerFundsReceived] {
+        assert!(MessageBusMessage::new(notification_meta(event.clone()), customer.clone()).is_ok(), "{event}");
+        assert!(MessageBusMessage::new(notification_meta(event.clone()), platform.clone()).is_err(), "{event}");
+    }
+    for event in [EventType::TransactionFundedInfo, EventType::BankPaymentExecutionFailedAlert] {
+        assert!(MessageBusMessage::new(notification_meta(event.clone()), platform.clone()).is_ok(), "{event}");
+        assert!(MessageBusMessage::new(notification_meta(event.clone()), customer.clone()).is_err(), "{event}");
+    }
+}